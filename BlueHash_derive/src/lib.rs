@@ -0,0 +1,109 @@
+//! `#[derive(BlueHashable)]` for the BlueHash crate.
+//!
+//! The generated implementation frames the type name, every field name and
+//! every variant name with its length, so the encoding fed to the hasher is
+//! injective and independent of memory layout. Enable it through the
+//! `derive` feature of `BlueHash` rather than depending on this crate directly.
+
+#![allow(non_snake_case)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields, GenericParam, Index};
+
+#[proc_macro_derive(BlueHashable)]
+pub fn derive_blue_hashable(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+    for param in &mut input.generics.params {
+        if let GenericParam::Type(ty) = param {
+            ty.bounds.push(parse_quote!(::BlueHash::BlueHashable));
+        }
+    }
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, fields) = destructure(quote!(#name), &data.fields);
+            quote! {
+                let #pattern = self;
+                #fields
+            }
+        }
+        Data::Enum(data) => {
+            let arms = data.variants.iter().enumerate().map(|(index, variant)| {
+                let index = index as u32;
+                let variant_ident = &variant.ident;
+                let variant_str = variant_ident.to_string();
+                let (pattern, fields) = destructure(quote!(#name::#variant_ident), &variant.fields);
+                quote! {
+                    #pattern => {
+                        ::BlueHash::Digest::update(hasher, &#index.to_be_bytes());
+                        hasher.update_framed(#variant_str.as_bytes());
+                        #fields
+                    }
+                }
+            });
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return syn::Error::new(
+                Span::call_site(),
+                "BlueHashable cannot be derived for unions",
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    quote! {
+        impl #impl_generics ::BlueHash::BlueHashable for #name #ty_generics #where_clause {
+            fn hash_into(&self, hasher: &mut ::BlueHash::BlueHashCore) {
+                hasher.update_framed(#name_str.as_bytes());
+                #body
+            }
+        }
+    }
+    .into()
+}
+
+/// Builds a destructuring pattern for `path` and the statements hashing each
+/// bound field (field count, then framed field name followed by its value).
+fn destructure(path: TokenStream2, fields: &Fields) -> (TokenStream2, TokenStream2) {
+    let names: Vec<String> = match fields {
+        Fields::Named(named) => named
+            .named
+            .iter()
+            .map(|f| f.ident.as_ref().unwrap().to_string())
+            .collect(),
+        Fields::Unnamed(unnamed) => (0..unnamed.unnamed.len()).map(|i| i.to_string()).collect(),
+        Fields::Unit => Vec::new(),
+    };
+    let bindings: Vec<_> = (0..names.len())
+        .map(|i| format_ident!("__field{}", i))
+        .collect();
+    let pattern = match fields {
+        Fields::Named(named) => {
+            let idents = named.named.iter().map(|f| f.ident.as_ref().unwrap());
+            quote!(#path { #(#idents: #bindings),* })
+        }
+        Fields::Unnamed(_) => {
+            let indices = (0..names.len()).map(Index::from);
+            quote!(#path { #(#indices: #bindings),* })
+        }
+        Fields::Unit => quote!(#path),
+    };
+    let count = names.len() as u64;
+    let statements = quote! {
+        ::BlueHash::Digest::update(hasher, &#count.to_be_bytes());
+        #(
+            hasher.update_framed(#names.as_bytes());
+            ::BlueHash::BlueHashable::hash_into(#bindings, hasher);
+        )*
+    };
+    (pattern, statements)
+}
@@ -0,0 +1,75 @@
+use BlueHash::{BlueHashable, DigestSize};
+
+#[derive(BlueHashable)]
+struct Record {
+    name: String,
+    tags: Vec<String>,
+    size: u64,
+}
+
+#[derive(BlueHashable)]
+struct Renamed {
+    title: String,
+    tags: Vec<String>,
+    size: u64,
+}
+
+#[derive(BlueHashable)]
+struct Pair<T>(T, T);
+
+#[derive(BlueHashable)]
+enum Shape {
+    Empty,
+    Circle { radius: u32 },
+    Line(u32, u32),
+}
+
+#[test]
+fn derived_hash_is_deterministic() {
+    let record = Record {
+        name: "config".to_string(),
+        tags: vec!["a".to_string(), "bc".to_string()],
+        size: 42,
+    };
+    assert_eq!(
+        record.blue_hash(DigestSize::Bit256),
+        record.blue_hash(DigestSize::Bit256)
+    );
+}
+
+#[test]
+fn field_names_and_splits_are_bound() {
+    let record = Record {
+        name: "x".to_string(),
+        tags: vec!["ab".to_string(), "c".to_string()],
+        size: 1,
+    };
+    let split = Record {
+        name: "x".to_string(),
+        tags: vec!["a".to_string(), "bc".to_string()],
+        size: 1,
+    };
+    let renamed = Renamed {
+        title: "x".to_string(),
+        tags: vec!["ab".to_string(), "c".to_string()],
+        size: 1,
+    };
+    let digest = record.blue_hash(DigestSize::Bit128);
+    assert_ne!(digest, split.blue_hash(DigestSize::Bit128));
+    assert_ne!(digest, renamed.blue_hash(DigestSize::Bit128));
+}
+
+#[test]
+fn enum_variants_are_distinguished() {
+    let digests = [
+        Shape::Empty.blue_hash(DigestSize::Bit128),
+        Shape::Circle { radius: 3 }.blue_hash(DigestSize::Bit128),
+        Shape::Line(3, 0).blue_hash(DigestSize::Bit128),
+        Pair(3u32, 0u32).blue_hash(DigestSize::Bit128),
+    ];
+    for i in 0..digests.len() {
+        for j in i + 1..digests.len() {
+            assert_ne!(digests[i], digests[j]);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! `#![no_std]` consumer of BlueHash.
+//!
+//! This crate depends on BlueHash with `default-features = false` and is
+//! itself `#![no_std]`, so building it on its own (`cargo build -p
+//! BlueHash_no_std`, or with `--target thumbv7em-none-eabihf`) checks that
+//! the streaming API is usable on embedded targets. The integration tests
+//! install a counting global allocator to check that streaming, `finalize_into`
+//! and `verify_image` never touch the heap.
+
+#![no_std]
+#![allow(non_snake_case)]
+
+use BlueHash::{BlueHashCore, Digest, DigestSize};
+
+/// Streams every chunk into a fresh hasher, as firmware would feed data
+/// arriving from a peripheral in arbitrarily sized pieces.
+///
+/// # Arguments
+///
+/// * `digest_size` - The digest size of the hasher.
+/// * `chunks` - The input, split into pieces of any length.
+///
+/// # Returns
+///
+/// The hasher with all chunks absorbed, ready to be finalized.
+pub fn absorb_chunks(digest_size: DigestSize, chunks: &[&[u8]]) -> BlueHashCore {
+    let mut hasher = BlueHashCore::new(digest_size);
+    for chunk in chunks {
+        hasher.update(chunk);
+    }
+    hasher
+}
@@ -0,0 +1,84 @@
+#![allow(non_snake_case)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use BlueHash::{verify_image, BlueHashCore, Digest, DigestSize, ImageManifest, ImageSection};
+use BlueHash_no_std::absorb_chunks;
+
+struct CountingAlloc;
+
+thread_local! {
+    // Counted per thread so that tests running in parallel do not interfere.
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[test]
+fn update_never_allocates() {
+    let data = [0x5au8; 300];
+    let chunks: [&[u8]; 6] = [
+        &data[..1],
+        &data[1..8],
+        &data[8..9],
+        &data[9..100],
+        &[],
+        &data[100..],
+    ];
+
+    let before = allocations();
+    let mut hasher = absorb_chunks(DigestSize::Bit256, &chunks);
+    hasher.update(&data);
+    let mut streamed = [0u8; 32];
+    hasher.finalize_into(&mut streamed);
+    assert_eq!(before, allocations());
+
+    let mut one_shot = BlueHashCore::new(DigestSize::Bit256);
+    one_shot.update(&data);
+    one_shot.update(&data);
+    assert_eq!(streamed.to_vec(), one_shot.finalize());
+}
+
+#[test]
+fn verify_image_never_allocates() {
+    let image = [0x3cu8; 40];
+    let mut section_digest = [0u8; 16];
+    let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+    hasher.update(&image[8..]);
+    hasher.finalize_into(&mut section_digest);
+    let mut image_digest = [0u8; 16];
+    let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+    hasher.update(&image);
+    hasher.finalize_into(&mut image_digest);
+    let sections = [ImageSection {
+        offset: 8,
+        len: 32,
+        digest: &section_digest,
+    }];
+    let manifest = ImageManifest {
+        digest_size: DigestSize::Bit128,
+        sections: &sections,
+        image_digest: &image_digest,
+    };
+
+    let before = allocations();
+    assert_eq!(verify_image(&image, &manifest), Ok(()));
+    assert_eq!(before, allocations());
+}
@@ -0,0 +1,151 @@
+//! Known-answer tests (KATs) for BlueHash that must pass on every target.
+//!
+//! The digest only uses integer arithmetic and encodes words big-endian, so
+//! it must not depend on the target's word size, byte order or FPU. This
+//! crate keeps the vectors in one table and checks them from an ordinary
+//! integration test, which `cargo xtask portability` runs on the targets that
+//! are most likely to expose a slip:
+//!
+//! | target | runner |
+//! |--------|--------|
+//! | `wasm32-wasip1` (32-bit) | wasmtime |
+//! | `powerpc64-unknown-linux-gnu` (big-endian) | `cross` (qemu) |
+//! | `s390x-unknown-linux-gnu` (big-endian) | Miri |
+//!
+//! A plain `cargo test` runs the same tests on the host.
+
+#![allow(non_snake_case)]
+
+use BlueHash::{BlueHashCore, Digest, DigestSize};
+
+/// How a known answer is computed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KatMode {
+    /// `finalize`.
+    Digest,
+    /// `new_keyed` with the given key, then `finalize`.
+    Keyed(&'static [u8]),
+    /// `finalize_truncated` to the given length.
+    Truncated(usize),
+    /// The first bytes of `finalize_xof`, as many as given.
+    Xof(usize),
+    /// `export_state` after absorbing the input.
+    ExportedState,
+}
+
+/// One known answer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Kat {
+    pub digest_size: DigestSize,
+    pub mode: KatMode,
+    pub input: &'static [u8],
+    /// The expected output, lowercase hex.
+    pub expected: &'static str,
+}
+
+/// The known answers checked on every target.
+pub const KATS: &[Kat] = &[
+    Kat {
+        digest_size: DigestSize::Bit128,
+        mode: KatMode::Digest,
+        input: b"",
+        expected: "0db0dd6ec04af5f9a88558fab130796c",
+    },
+    Kat {
+        digest_size: DigestSize::Bit256,
+        mode: KatMode::Digest,
+        input: b"",
+        expected: "4f177c47ada4a301c1112b57c23bc9c2a2b679654b598365413263909e10e3ae",
+    },
+    Kat {
+        digest_size: DigestSize::Bit512,
+        mode: KatMode::Digest,
+        input: b"",
+        expected: "7ba1ad0f8a08f3cb7b3b61f02a60bda9b1c39fd2a35d1bc5b5c7911bd2ac997f\
+                   7bb026e7d24f0043aa9c006eb1cf7245dfbc00476ccb3a17218112dd18d3795f",
+    },
+    // More than one rate block, so the absorb permutation runs.
+    Kat {
+        digest_size: DigestSize::Bit256,
+        mode: KatMode::Digest,
+        input: &[0x61; 200],
+        expected: "39e0de1f4e417caf4430a7fc9920a8846e62ce69e3a6a44cabda268ecfaaacca",
+    },
+    Kat {
+        digest_size: DigestSize::Bit256,
+        mode: KatMode::Keyed(b"key"),
+        input: b"message",
+        expected: "52e7df2b12a9670e3ca970430acf609565417c6e917f9d3582ef6e7700999378",
+    },
+    Kat {
+        digest_size: DigestSize::Bit512,
+        mode: KatMode::Truncated(20),
+        input: b"abc",
+        expected: "eead142b8ef5b4c9e58449a2e9a4dd196307d3d5",
+    },
+    Kat {
+        digest_size: DigestSize::Bit128,
+        mode: KatMode::Xof(48),
+        input: b"abc",
+        expected: "d44a68adb2544939aa31b0fe97fb41956ba9e5288ad87536c39e517f1d82e3b8\
+                   038aab39f8a27202a4e27fe7aa5f4c54",
+    },
+    // Three output blocks, so the block counter reaches the permutation.
+    Kat {
+        digest_size: DigestSize::Bit256,
+        mode: KatMode::Xof(272),
+        input: b"abc",
+        expected: "9b16313a85efff9601a9e888933b8d72487a42f3e3a07cf0ae0bb80cefe20e6f\
+                   5ee17cf81c1673450cd11bd906af75c883fb978ce960f6d47a91eefda7076ef5\
+                   5cbf11053e10ffc009ac95d2c8d1abf06b2a3463fc56c1a8bc75ec93eb028787\
+                   398d6e89b59038f7b7d73b295109e03c7ece0f8ab080d21db1859651392f7b6b\
+                   e3ad9dd7fc1af2a6618e3b538878a846c046a304a1e8fb9f21a3df42f04b625d\
+                   bf51df28ae2500829071509ff58aa60ae77059a9a4de4221e30354f3d000fb15\
+                   4d81d0d24080a128e7611d8b5d8aab590c971d6948e22302e186283652b8dd32\
+                   342e4305620e47be3be679cee4117c04442d0538bd4ac010220286f3c1dd3abf\
+                   9c518f38c83cf818684516ddf030095b",
+    },
+    // Header, tail buffer and state words are all big-endian.
+    Kat {
+        digest_size: DigestSize::Bit128,
+        mode: KatMode::ExportedState,
+        input: b"eleven byte",
+        expected: "0101000000700000000000000000000000000000000b\
+                   00000000000004530000000000000001037974650000000000\
+                   644f2011ecc5ed8d23456789abcdef01456789abcdef01236789abcdef012345\
+                   89abcdef01234567abcdef0123456789cdef0123456789abef0123456789abcd\
+                   13579bdf02468ace2468ace13579bdf03579bdf02468ace1468ace13579bdf02\
+                   579bdf02468ace13068ace13579bdf24079bdf02468ace3508ace13579bdf468\
+                   09bdf02468ace5790ace13579bdf68ac0bdf02468ace79bd0ce13579bdf8ace0\
+                   0df02468ace9bdf10e13579bdface1350f02468acebdf0240123456789abcdef\
+                   89abcdef01234567",
+    },
+];
+
+/// Computes the output of `kat` on the current target.
+///
+/// # Arguments
+///
+/// * `kat` - The known answer to recompute.
+///
+/// # Returns
+///
+/// The output as lowercase hex, to be compared with `kat.expected`.
+pub fn compute(kat: &Kat) -> String {
+    let mut hasher = match kat.mode {
+        KatMode::Keyed(key) => BlueHashCore::new_keyed(kat.digest_size, key),
+        _ => BlueHashCore::new(kat.digest_size),
+    };
+    hasher.update(kat.input);
+    let output = match kat.mode {
+        KatMode::Digest | KatMode::Keyed(_) => hasher.finalize(),
+        KatMode::Truncated(len) => hasher.finalize_truncated(len),
+        KatMode::Xof(len) => {
+            let mut output = vec![0u8; len];
+            hasher.finalize_xof().squeeze(&mut output);
+            output
+        }
+        KatMode::ExportedState => hasher.export_state(),
+    };
+    output.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,27 @@
+#![allow(non_snake_case)]
+
+use BlueHash_portability::{compute, KatMode, KATS};
+
+#[test]
+fn known_answers_match_on_this_target() {
+    for kat in KATS {
+        assert_eq!(
+            compute(kat),
+            kat.expected,
+            "{:?} {:?} over {} bytes",
+            kat.digest_size,
+            kat.mode,
+            kat.input.len()
+        );
+    }
+}
+
+#[test]
+fn every_mode_is_covered() {
+    let covered = |wanted: fn(&KatMode) -> bool| KATS.iter().any(|kat| wanted(&kat.mode));
+    assert!(covered(|mode| matches!(mode, KatMode::Digest)));
+    assert!(covered(|mode| matches!(mode, KatMode::Keyed(_))));
+    assert!(covered(|mode| matches!(mode, KatMode::Truncated(_))));
+    assert!(covered(|mode| matches!(mode, KatMode::Xof(_))));
+    assert!(covered(|mode| matches!(mode, KatMode::ExportedState)));
+}
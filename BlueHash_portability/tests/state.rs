@@ -0,0 +1,36 @@
+#![allow(non_snake_case)]
+
+//! State import/export checks that stay cheap under Miri: nothing here runs
+//! the permutation, so `cargo xtask portability miri` covers every slicing
+//! and byte-order conversion in the (de)serializer without a long run.
+
+use BlueHash::{BlueHashCore, Digest, DigestSize};
+
+#[test]
+fn export_import_round_trips_for_every_size() {
+    for digest_size in [DigestSize::Bit128, DigestSize::Bit256, DigestSize::Bit512] {
+        let mut hasher = BlueHashCore::new(digest_size);
+        hasher.update(b"nine byte");
+        let exported = hasher.export_state();
+        assert_eq!(exported.len(), 47 + digest_size.state_size() * 8);
+        let restored = BlueHashCore::import_state(&exported).unwrap();
+        assert_eq!(restored.export_state(), exported);
+    }
+}
+
+#[test]
+fn import_rejects_every_truncation_and_extension() {
+    let exported = BlueHashCore::new(DigestSize::Bit128).export_state();
+    for len in 0..exported.len() {
+        assert!(BlueHashCore::import_state(&exported[..len]).is_err());
+    }
+    let mut extended = exported.clone();
+    extended.push(0);
+    assert!(BlueHashCore::import_state(&extended).is_err());
+    for tag in 0..=u8::MAX {
+        let mut corrupted = exported.clone();
+        corrupted[1] = tag;
+        // Tags of other digest sizes fail on the length; unknown tags on the tag.
+        let _ = BlueHashCore::import_state(&corrupted);
+    }
+}
@@ -43,7 +43,7 @@ pub fn parallel_collision_test(digest_size: DigestSize, trials: usize, num_threa
             }
             {
                 let mut global_hashes = hashes.lock().unwrap();
-                global_hashes.extend(local_hashes.into_iter());
+                global_hashes.extend(local_hashes);
             }
             {
                 let mut global_collisions = collisions.lock().unwrap();
@@ -0,0 +1,44 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use rayon::prelude::*;
+use BlueHash::DataGenerator;
+
+const CHUNK_LEN: usize = 1 << 20;
+
+/// 按 1 MiB 分块生成数据并以字节吞吐量报告，生成 1 GiB 语料的耗时约为单次耗时的 1024 倍
+pub fn bench_datagen(c: &mut Criterion) {
+    let mut group = c.benchmark_group("datagen");
+    group.throughput(Throughput::Bytes(CHUNK_LEN as u64));
+    group.sample_size(10);
+    let mut buf = vec![0u8; CHUNK_LEN];
+    group.bench_function("fill 1 MiB", |b| {
+        let mut generator = DataGenerator::new(7);
+        b.iter(|| generator.fill(black_box(&mut buf)));
+    });
+    // 随机访问远处的数据与顺序生成的速度相同
+    group.bench_function("fill_at 1 MiB at 1 TiB", |b| {
+        let mut generator = DataGenerator::new(7);
+        b.iter(|| generator.fill_at(black_box(1 << 40), black_box(&mut buf)));
+    });
+    group.finish();
+
+    // 各线程以 `fill_at` 独立生成互不重叠的分块，吞吐量随核数增长
+    let chunks = rayon::current_num_threads() * 2;
+    let mut group = c.benchmark_group("datagen parallel");
+    group.throughput(Throughput::Bytes((chunks * CHUNK_LEN) as u64));
+    group.sample_size(10);
+    let mut corpus = vec![0u8; chunks * CHUNK_LEN];
+    group.bench_function("fill_at on every core", |b| {
+        b.iter(|| {
+            corpus
+                .par_chunks_mut(CHUNK_LEN)
+                .enumerate()
+                .for_each(|(i, chunk)| {
+                    DataGenerator::new(7).fill_at((i * CHUNK_LEN) as u64, black_box(chunk));
+                });
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_datagen);
+criterion_main!(benches);
@@ -0,0 +1,29 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use std::io;
+use BlueHash::{open_file, seal_file, DataGenerator, ENVELOPE_HEADER_LEN};
+
+const FILE_LEN: usize = 4 << 20;
+
+/// 以默认 64 KiB 分帧加密与解密 4 MiB 数据，按明文字节吞吐量报告
+pub fn bench_envelope(c: &mut Criterion) {
+    let key = [0x42u8; 32];
+    let nonce = [7u8; 16];
+    let mut plaintext = vec![0u8; FILE_LEN];
+    DataGenerator::new(1).fill(&mut plaintext);
+    let mut sealed = Vec::with_capacity(ENVELOPE_HEADER_LEN + FILE_LEN + 4096);
+    seal_file(&key, &nonce, &plaintext[..], &mut sealed).unwrap();
+
+    let mut group = c.benchmark_group("envelope");
+    group.throughput(Throughput::Bytes(FILE_LEN as u64));
+    group.sample_size(10);
+    group.bench_function("seal 4 MiB", |b| {
+        b.iter(|| seal_file(&key, &nonce, black_box(&plaintext[..]), io::sink()).unwrap());
+    });
+    group.bench_function("open 4 MiB", |b| {
+        b.iter(|| open_file(&key, black_box(&sealed[..]), io::sink()).unwrap());
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_envelope);
+criterion_main!(benches);
@@ -0,0 +1,22 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use BlueHash::LtHash;
+
+/// 增量更新的单次开销：每次添加或删除一个元素，与集合大小无关
+pub fn bench_lthash(c: &mut Criterion) {
+    let mut checksum = LtHash::new();
+    for i in 0..100u32 {
+        checksum.add(&i.to_be_bytes());
+    }
+    let mut group = c.benchmark_group("lthash");
+    group.sample_size(20);
+    group.bench_function("add and remove one element", |b| {
+        b.iter(|| {
+            checksum.add(black_box(b"replicated row 42"));
+            checksum.remove(black_box(b"replicated row 42"));
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_lthash);
+criterion_main!(benches);
@@ -0,0 +1,35 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use BlueHash::{hash_shared_prefixes, BlueHashCore, Digest, DigestSize};
+
+/// 模拟深层目录下的文件列表：路径共享约 400 字节的前缀
+fn path_listing() -> Vec<Vec<u8>> {
+    let root = "/srv/build/workspace/target/x86_64-unknown-linux-gnu/release/build/\
+                bluehash-3f0c2a9e1d7b4c56/out/generated/protocol/schemas/v2/messages/\
+                internal/handshake/extensions/transport/parameters/encoding/tests/";
+    let root = root.repeat(2);
+    (0..500)
+        .map(|i| format!("{}module_{:03}/file_{:04}.rs", root, i / 50, i).into_bytes())
+        .collect()
+}
+
+pub fn bench_shared_prefixes(c: &mut Criterion) {
+    let paths = path_listing();
+    let inputs: Vec<&[u8]> = paths.iter().map(Vec::as_slice).collect();
+
+    // 逐个独立哈希与复用公共前缀中间状态的对比，两者摘要相同
+    c.bench_function("500 paths, independent", |b| {
+        b.iter(|| {
+            for input in &inputs {
+                let mut hash = BlueHashCore::new(DigestSize::Bit256);
+                hash.update(black_box(input));
+                black_box(hash.finalize());
+            }
+        });
+    });
+    c.bench_function("500 paths, shared prefixes", |b| {
+        b.iter(|| black_box(hash_shared_prefixes(DigestSize::Bit256, black_box(&inputs))));
+    });
+}
+
+criterion_group!(benches, bench_shared_prefixes);
+criterion_main!(benches);
@@ -0,0 +1,22 @@
+//! Feeds arbitrary bytes to `import_state` and drives any accepted hasher
+//! through `update` and every finalization. Run with
+//! `cargo +nightly fuzz run import_state`.
+
+#![no_main]
+#![allow(non_snake_case)]
+
+use libfuzzer_sys::fuzz_target;
+use BlueHash::{BlueHashCore, Digest};
+
+fuzz_target!(|input: &[u8]| {
+    let Ok(mut hasher) = BlueHashCore::import_state(input) else {
+        return;
+    };
+    assert_eq!(hasher.export_state(), input);
+    hasher.update(input);
+    let mut truncated = hasher.clone();
+    let mut xof = hasher.clone();
+    let digest = hasher.finalize();
+    truncated.finalize_truncated(digest.len() / 2);
+    let _: [u8; 200] = xof.finalize_xof().squeeze_array();
+});
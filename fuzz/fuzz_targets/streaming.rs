@@ -0,0 +1,39 @@
+//! Splits the input at fuzzer-chosen points and checks that streaming,
+//! finalization and XOF squeezing neither panic nor disagree with one-shot
+//! hashing. Run with `cargo +nightly fuzz run streaming`.
+
+#![no_main]
+#![allow(non_snake_case)]
+
+use libfuzzer_sys::fuzz_target;
+use BlueHash::{BlueHashCore, Digest, DigestSize};
+
+fuzz_target!(|input: &[u8]| {
+    let Some((&control, data)) = input.split_first() else {
+        return;
+    };
+    let digest_size = match control % 3 {
+        0 => DigestSize::Bit128,
+        1 => DigestSize::Bit256,
+        _ => DigestSize::Bit512,
+    };
+    let step = (control as usize >> 2) + 1;
+
+    let mut whole = BlueHashCore::new(digest_size);
+    whole.update(data);
+    let mut split = BlueHashCore::new(digest_size);
+    for piece in data.chunks(step) {
+        split.update(piece);
+    }
+    let mut xof_hasher = split.clone();
+    assert_eq!(whole.finalize(), split.finalize());
+
+    let mut reader = xof_hasher.finalize_xof();
+    let position = data
+        .iter()
+        .take(8)
+        .fold(0u64, |acc, &b| (acc << 8) | b as u64);
+    reader.seek(position);
+    let mut out = [0u8; 64];
+    reader.squeeze(&mut out[..step.min(64)]);
+});
@@ -0,0 +1,115 @@
+//! Machine-readable traces for design review (`debug-introspection` feature).
+//!
+//! `export_trace_json` hashes an input with a `TraceHasher` and renders the
+//! trace as JSON for external visualization tools:
+//!
+//! ```text
+//! {
+//!   "algorithm": "BlueHash-256", "state_words": 32, "round_count": 128,
+//!   "noise_seed": "…", "final_noise_seed": "…",
+//!   "absorbed_state": ["…", …],
+//!   "rounds": [
+//!     {"round": 0, "phase": "permutation", "constant": "…", "noise": "…", "state": ["…", …]},
+//!     …
+//!   ],
+//!   "digest": "…"
+//! }
+//! ```
+//!
+//! 64-bit words (state words, round constants, LWE noise values) are written
+//! as 16-digit hex strings, because JSON numbers lose precision above 2^53.
+//! `noise` is the LWE noise sample that went into the round constant.
+
+use crate::constants::NOISE_PRIME;
+use crate::noise::generate_lwe_noise_from_seed;
+use crate::trace::{HashTrace, TraceHasher, TracePhase};
+use crate::utils::to_hex_string;
+use crate::DigestSize;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+/// Traces BlueHash-256 over `input` and returns the trace as JSON.
+pub fn export_trace_json(input: &[u8]) -> String {
+    export_trace_json_with(DigestSize::Bit256, input)
+}
+
+/// Traces BlueHash over `input` with the given digest size and returns the
+/// trace as JSON.
+pub fn export_trace_json_with(digest_size: DigestSize, input: &[u8]) -> String {
+    let mut tracer = TraceHasher::new(digest_size);
+    tracer.update(input);
+    trace_to_json(&tracer.finalize())
+}
+
+fn word(value: u64) -> String {
+    format!("\"{:016x}\"", value)
+}
+
+fn words(values: &[u64]) -> String {
+    let items: Vec<String> = values.iter().map(|&v| word(v)).collect();
+    format!("[{}]", items.join(","))
+}
+
+/// Renders an existing trace as JSON, in the format of `export_trace_json`.
+pub fn trace_to_json(trace: &HashTrace) -> String {
+    let permutation_rounds = trace
+        .rounds
+        .iter()
+        .filter(|r| r.phase == TracePhase::Permutation)
+        .count();
+    let mut json = String::new();
+    // Writing to a `String` cannot fail.
+    let _ = write!(
+        json,
+        "{{\"algorithm\":\"BlueHash-{}\",\"state_words\":{},\"round_count\":{},\
+         \"noise_seed\":{},\"final_noise_seed\":{},\"absorbed_state\":{},\"rounds\":[",
+        trace.digest_size.digest_length() * 8,
+        trace.digest_size.state_size(),
+        permutation_rounds,
+        word(trace.noise_seed),
+        word(trace.final_noise_seed),
+        words(&trace.absorbed_state)
+    );
+    for (i, round) in trace.rounds.iter().enumerate() {
+        let (phase, seed) = match round.phase {
+            TracePhase::Permutation => ("permutation", trace.noise_seed),
+            TracePhase::FinalMix => ("final_mix", trace.final_noise_seed),
+        };
+        let noise = generate_lwe_noise_from_seed(seed, round.round, NOISE_PRIME);
+        let _ = write!(
+            json,
+            "{}{{\"round\":{},\"phase\":\"{}\",\"constant\":{},\"noise\":{},\"state\":{}}}",
+            if i == 0 { "" } else { "," },
+            round.round,
+            phase,
+            word(round.constant),
+            word(noise),
+            words(&round.state)
+        );
+    }
+    let _ = write!(json, "],\"digest\":\"{}\"}}", to_hex_string(&trace.digest));
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlueHashCore, Digest};
+
+    #[test]
+    fn test_export_trace_json() {
+        let json = export_trace_json_with(DigestSize::Bit128, b"abc");
+        let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+        hasher.update(b"abc");
+        let digest = to_hex_string(&hasher.finalize());
+
+        assert!(json.starts_with("{\"algorithm\":\"BlueHash-128\",\"state_words\":25,"));
+        assert!(json.contains("\"round_count\":112,"));
+        assert!(json.contains("{\"round\":0,\"phase\":\"permutation\",\"constant\":\""));
+        assert!(json.contains("{\"round\":115,\"phase\":\"final_mix\","));
+        assert!(json.ends_with(&format!("],\"digest\":\"{}\"}}", digest)));
+        assert_eq!(json.matches("\"round\":").count(), 116);
+    }
+}
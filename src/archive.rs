@@ -0,0 +1,98 @@
+//! One-pass hashing of tar archives.
+//!
+//! Supply-chain tooling usually needs both the digest of a release tarball
+//! and the digests of the files inside it. `hash_tar` reads the archive
+//! once: the raw stream is hashed through a `HashReader` while each member's
+//! contents are hashed as the `tar` crate yields them.
+
+use crate::{DigestSize, HashReader};
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+/// Digest of one archive member.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberDigest {
+    /// Path of the member as recorded in the archive.
+    pub path: PathBuf,
+    /// Size of the member's contents in bytes.
+    pub size: u64,
+    /// Digest of the member's contents.
+    pub digest: Vec<u8>,
+}
+
+/// Per-member digests and the digest of the whole archive stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveDigests {
+    /// Members in archive order, including directories and links (whose
+    /// contents are empty).
+    pub members: Vec<MemberDigest>,
+    /// Digest of every byte of the archive, including trailing padding.
+    pub archive: Vec<u8>,
+}
+
+/// Hashes a tar stream and each of its members in a single pass.
+///
+/// # Arguments
+///
+/// * `reader` - The tar stream (already decompressed).
+/// * `digest_size` - The BlueHash variant used for all digests.
+///
+/// # Returns
+///
+/// The digests, or the first I/O or tar format error.
+pub fn hash_tar<R: Read>(reader: R, digest_size: DigestSize) -> io::Result<ArchiveDigests> {
+    let mut archive = tar::Archive::new(HashReader::new(reader, digest_size));
+    let mut members = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let path = entry.path()?.into_owned();
+        let size = entry.size();
+        let mut contents = HashReader::new(entry, digest_size);
+        io::copy(&mut contents, &mut io::sink())?;
+        members.push(MemberDigest {
+            path,
+            size,
+            digest: contents.finalize(),
+        });
+    }
+    // The tar reader stops at the end-of-archive marker; hash the rest too.
+    let mut stream = archive.into_inner();
+    io::copy(&mut stream, &mut io::sink())?;
+    Ok(ArchiveDigests {
+        members,
+        archive: stream.finalize(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlueHashCore, Digest};
+
+    fn digest(data: &[u8]) -> Vec<u8> {
+        let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    #[test]
+    fn test_hash_tar_members_and_archive() {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, contents) in [("a.txt", &b"alpha"[..]), ("dir/b.bin", &[0u8; 700][..])] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, contents).unwrap();
+        }
+        let tarball = builder.into_inner().unwrap();
+
+        let digests = hash_tar(&tarball[..], DigestSize::Bit128).unwrap();
+        assert_eq!(digests.archive, digest(&tarball));
+        assert_eq!(digests.members.len(), 2);
+        assert_eq!(digests.members[0].path, PathBuf::from("a.txt"));
+        assert_eq!(digests.members[0].digest, digest(b"alpha"));
+        assert_eq!(digests.members[1].size, 700);
+        assert_eq!(digests.members[1].digest, digest(&[0u8; 700]));
+    }
+}
@@ -0,0 +1,188 @@
+//! Challenge-response authentication with a shared key.
+//!
+//! This packages the usual device-pairing exchange:
+//!
+//! 1. The verifier sends a fresh random challenge (`make_challenge`).
+//! 2. The prover answers with
+//!    `Mac(key, len(label) || label || len(context) || context || challenge)`
+//!    (`respond`), where `label = "bluehash challenge-response v1"` and the
+//!    context names the protocol step, e.g. `b"pair"` or `b"unlock"`.
+//! 3. The verifier checks the response in constant time (`verify`).
+//!
+//! `verify` also records accepted challenges in a `ReplayWindow`, so a
+//! challenge/response pair that was captured once is refused if it is
+//! presented again, even when the verifier accepts challenges chosen by the
+//! other side. The window remembers the most recent `capacity` challenges.
+
+use crate::mac::Mac;
+use crate::DigestSize;
+use alloc::collections::VecDeque;
+use core::fmt;
+use rand::{CryptoRng, RngCore};
+
+const AUTH_LABEL: &[u8] = b"bluehash challenge-response v1";
+
+/// Length of a challenge in bytes.
+pub const CHALLENGE_LEN: usize = 32;
+
+/// Length of a response in bytes.
+pub const RESPONSE_LEN: usize = 32;
+
+/// Error returned by `verify`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    /// The response does not match the challenge, key and context.
+    BadResponse,
+    /// The challenge was already answered.
+    Replayed,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::BadResponse => write!(f, "invalid response"),
+            AuthError::Replayed => write!(f, "challenge was already used"),
+        }
+    }
+}
+
+impl core::error::Error for AuthError {}
+
+/// Recently accepted challenges.
+#[derive(Debug, Clone)]
+pub struct ReplayWindow {
+    capacity: usize,
+    seen: VecDeque<[u8; CHALLENGE_LEN]>,
+}
+
+impl ReplayWindow {
+    /// Creates a window remembering up to `capacity` challenges.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            seen: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Returns `true` if `challenge` was accepted recently.
+    pub fn contains(&self, challenge: &[u8; CHALLENGE_LEN]) -> bool {
+        self.seen.contains(challenge)
+    }
+
+    fn record(&mut self, challenge: &[u8; CHALLENGE_LEN]) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.seen.len() == self.capacity {
+            self.seen.pop_front();
+        }
+        self.seen.push_back(*challenge);
+    }
+}
+
+/// Creates a fresh random challenge.
+pub fn make_challenge<R: RngCore + CryptoRng>(rng: &mut R) -> [u8; CHALLENGE_LEN] {
+    let mut challenge = [0u8; CHALLENGE_LEN];
+    rng.fill_bytes(&mut challenge);
+    challenge
+}
+
+fn response_mac(key: &[u8], challenge: &[u8; CHALLENGE_LEN], context: &[u8]) -> Mac {
+    let mut mac = Mac::new(DigestSize::Bit256, key);
+    mac.update_framed(AUTH_LABEL);
+    mac.update_framed(context);
+    mac.update(challenge);
+    mac
+}
+
+/// Answers a challenge.
+///
+/// # Arguments
+///
+/// * `key` - The shared key.
+/// * `challenge` - The challenge received from the verifier.
+/// * `context` - The protocol step the response is valid for.
+///
+/// # Returns
+///
+/// The response.
+pub fn respond(key: &[u8], challenge: &[u8; CHALLENGE_LEN], context: &[u8]) -> [u8; RESPONSE_LEN] {
+    let mut response = [0u8; RESPONSE_LEN];
+    response.copy_from_slice(&response_mac(key, challenge, context).finalize());
+    response
+}
+
+/// Verifies a response and records the challenge as used.
+///
+/// # Arguments
+///
+/// * `key` - The shared key.
+/// * `challenge` - The challenge the response answers.
+/// * `context` - The expected protocol step.
+/// * `response` - The response to check.
+/// * `window` - Challenges accepted so far; updated on success.
+///
+/// # Returns
+///
+/// `Ok(())` if the response is valid and the challenge is new.
+pub fn verify(
+    key: &[u8],
+    challenge: &[u8; CHALLENGE_LEN],
+    context: &[u8],
+    response: &[u8],
+    window: &mut ReplayWindow,
+) -> Result<(), AuthError> {
+    if !response_mac(key, challenge, context).verify(response) {
+        return Err(AuthError::BadResponse);
+    }
+    if window.contains(challenge) {
+        return Err(AuthError::Replayed);
+    }
+    window.record(challenge);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    const KEY: &[u8] = b"pairing key shared at setup";
+
+    #[test]
+    fn test_challenge_response() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let mut window = ReplayWindow::new(8);
+        let challenge = make_challenge(&mut rng);
+        let response = respond(KEY, &challenge, b"unlock");
+
+        assert_eq!(
+            verify(KEY, &challenge, b"pair", &response, &mut window),
+            Err(AuthError::BadResponse)
+        );
+        assert_eq!(
+            verify(b"other key", &challenge, b"unlock", &response, &mut window),
+            Err(AuthError::BadResponse)
+        );
+        assert_eq!(
+            verify(KEY, &challenge, b"unlock", &response, &mut window),
+            Ok(())
+        );
+        assert_eq!(
+            verify(KEY, &challenge, b"unlock", &response, &mut window),
+            Err(AuthError::Replayed)
+        );
+    }
+
+    #[test]
+    fn test_window_forgets_oldest() {
+        let mut window = ReplayWindow::new(2);
+        for byte in 0..3u8 {
+            window.record(&[byte; CHALLENGE_LEN]);
+        }
+        assert!(!window.contains(&[0; CHALLENGE_LEN]));
+        assert!(window.contains(&[1; CHALLENGE_LEN]));
+        assert!(window.contains(&[2; CHALLENGE_LEN]));
+    }
+}
@@ -0,0 +1,87 @@
+//! Fingerprints of build environments.
+//!
+//! Build caches key their entries on everything that can change the output:
+//! compiler flags, toolchain versions, lock files. `fingerprint_env` reduces
+//! a set of environment variables and input files to one BlueHash-256 digest:
+//!
+//! ```text
+//! digest = BlueHash-256(len(label) || label
+//!                       || n_vars  || for each (name, value): len(name) || name || len(value) || value
+//!                       || n_files || for each path:          len(path) || path || BlueHash-256(contents))
+//! ```
+//!
+//! with `label = "bluehash build env v1"` and 8-byte big-endian lengths and
+//! counts. Variables are sorted by name and value and files by path, so the
+//! order in which a build script gathers them does not matter. Paths are
+//! hashed as given, so pass them relative to the workspace root for keys
+//! that are stable across checkouts.
+
+use crate::reader::hash_file;
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+use std::io;
+use std::path::Path;
+
+const BUILD_ENV_LABEL: &[u8] = b"bluehash build env v1";
+
+/// Computes the fingerprint of a build environment.
+///
+/// # Arguments
+///
+/// * `vars` - `(name, value)` pairs, e.g. `RUSTFLAGS` and `CC`.
+/// * `files` - Input files such as `rust-toolchain.toml` or `Cargo.lock`.
+///
+/// # Returns
+///
+/// The 32-byte fingerprint, or the error from reading one of `files`.
+pub fn fingerprint_env<P: AsRef<Path>>(vars: &[(&str, &str)], files: &[P]) -> io::Result<Vec<u8>> {
+    let mut vars = vars.to_vec();
+    vars.sort_unstable();
+    let mut files: Vec<&Path> = files.iter().map(AsRef::as_ref).collect();
+    files.sort_unstable();
+
+    let mut hasher = BlueHashCore::new(DigestSize::Bit256);
+    hasher.update_framed(BUILD_ENV_LABEL);
+    hasher.update_u64_be(vars.len() as u64);
+    for (name, value) in vars {
+        hasher.update_str(name);
+        hasher.update_str(value);
+    }
+    hasher.update_u64_be(files.len() as u64);
+    for path in files {
+        hasher.update_framed(path.as_os_str().as_encoded_bytes());
+        hasher.update(&hash_file(path, DigestSize::Bit256)?);
+    }
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_ignores_order_but_not_contents() {
+        let dir = std::env::temp_dir();
+        let id = std::process::id();
+        let lock = dir.join(format!("bluehash_buildenv_test_{}.lock", id));
+        let toolchain = dir.join(format!("bluehash_buildenv_test_{}.toml", id));
+        std::fs::write(&lock, b"version = 3").unwrap();
+        std::fs::write(&toolchain, b"channel = \"1.87\"").unwrap();
+
+        let vars = [("RUSTFLAGS", "-C opt-level=3"), ("CC", "clang")];
+        let reversed = [vars[1], vars[0]];
+        let a = fingerprint_env(&vars, &[&lock, &toolchain]).unwrap();
+        let b = fingerprint_env(&reversed, &[&toolchain, &lock]).unwrap();
+        let other_flags =
+            fingerprint_env(&[("RUSTFLAGS", ""), ("CC", "clang")], &[&lock, &toolchain]);
+        std::fs::write(&lock, b"version = 4").unwrap();
+        let other_lock = fingerprint_env(&vars, &[&lock, &toolchain]);
+        std::fs::remove_file(&lock).unwrap();
+        std::fs::remove_file(&toolchain).unwrap();
+
+        assert_eq!(a.len(), 32);
+        assert_eq!(a, b);
+        assert_ne!(a, other_flags.unwrap());
+        assert_ne!(a, other_lock.unwrap());
+    }
+}
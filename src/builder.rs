@@ -0,0 +1,172 @@
+//! Builder for configured BlueHash instances.
+//!
+//! `BlueHashCore::new` keeps the default configuration; the builder is the
+//! sanctioned way to pick a non-default [`SecurityProfile`] or a key without
+//! forking the crate. With the `insecure-research` feature it can also swap
+//! in an alternative [`Permutation`] for experiments, and with the `keccak`
+//! feature it can select the keccak-f[1600] [`Backend`].
+
+use crate::{
+    Backend, BlueHashCore, Digest, DigestSize, NativePermutation, Permutation, SecurityProfile,
+};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Builder for [`BlueHashCore`]. `Debug` shows the key's length, not the key.
+#[derive(Clone)]
+pub struct BlueHashBuilder {
+    digest_size: DigestSize,
+    profile: SecurityProfile,
+    permutation: &'static dyn Permutation,
+    backend: Backend,
+    key: Option<Vec<u8>>,
+}
+
+impl fmt::Debug for BlueHashBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlueHashBuilder")
+            .field("digest_size", &self.digest_size)
+            .field("profile", &self.profile)
+            .field("permutation", &self.permutation)
+            .field("backend", &self.backend)
+            .field("key_len", &self.key.as_ref().map(Vec::len))
+            .finish()
+    }
+}
+
+impl BlueHashBuilder {
+    /// Starts a builder with the `Standard` profile and no key.
+    pub fn new(digest_size: DigestSize) -> Self {
+        Self {
+            digest_size,
+            profile: SecurityProfile::Standard,
+            permutation: &NativePermutation,
+            backend: Backend::Native,
+            key: None,
+        }
+    }
+
+    /// Selects the round-count profile.
+    pub fn profile(mut self, profile: SecurityProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Replaces the round function used for absorption, finalization and XOF
+    /// output.
+    ///
+    /// Digests produced with anything but `NativePermutation` are not
+    /// BlueHash digests and carry no security claim. Exported states do not
+    /// record the permutation; `import_state` always restores the native one.
+    #[cfg(feature = "insecure-research")]
+    pub fn permutation(mut self, permutation: &'static dyn Permutation) -> Self {
+        self.permutation = permutation;
+        self
+    }
+
+    /// Selects the sponge backend. The profile and permutation only apply to
+    /// the native backend.
+    #[cfg(feature = "keccak")]
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Enables keyed mode with the given key.
+    pub fn key(mut self, key: &[u8]) -> Self {
+        self.key = Some(key.to_vec());
+        self
+    }
+
+    /// Builds the configured hasher.
+    pub fn build(&self) -> BlueHashCore {
+        let mut hasher = BlueHashCore::new(self.digest_size);
+        hasher.round_count = self.profile.round_count(self.digest_size);
+        hasher.permutation = self.permutation;
+        hasher.backend = self.backend;
+        // Loads the initial state of the selected backend.
+        hasher.reset();
+        if let Some(key) = &self.key {
+            hasher.absorb_key(key);
+        }
+        hasher
+    }
+}
+
+impl BlueHashCore {
+    /// 返回配置构造器
+    pub fn builder(digest_size: DigestSize) -> BlueHashBuilder {
+        BlueHashBuilder::new(digest_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Digest;
+
+    fn digest(mut hasher: BlueHashCore) -> Vec<u8> {
+        hasher.update(b"profiles");
+        hasher.finalize()
+    }
+
+    #[test]
+    fn test_standard_profile_matches_new() {
+        let standard = BlueHashCore::builder(DigestSize::Bit256).build();
+        assert_eq!(
+            digest(standard),
+            digest(BlueHashCore::new(DigestSize::Bit256))
+        );
+        let builder = BlueHashCore::builder(DigestSize::Bit256).key(b"secret");
+        let debug = format!("{:?}", builder);
+        assert!(debug.contains("key_len: Some(6)") && !debug.contains("115"));
+        let keyed = BlueHashCore::builder(DigestSize::Bit256).key(b"k").build();
+        assert_eq!(
+            digest(keyed),
+            digest(BlueHashCore::new_keyed(DigestSize::Bit256, b"k"))
+        );
+    }
+
+    #[test]
+    fn test_profiles_differ() {
+        let fast = BlueHashCore::builder(DigestSize::Bit128)
+            .profile(SecurityProfile::Fast)
+            .build();
+        let paranoid = BlueHashCore::builder(DigestSize::Bit128)
+            .profile(SecurityProfile::Paranoid)
+            .build();
+        assert_eq!(SecurityProfile::Fast.round_count(DigestSize::Bit128), 56);
+        assert_eq!(
+            SecurityProfile::Paranoid.round_count(DigestSize::Bit512),
+            240
+        );
+        let standard = digest(BlueHashCore::new(DigestSize::Bit128));
+        assert_ne!(digest(fast), standard);
+        assert_ne!(digest(paranoid), standard);
+    }
+
+    #[cfg(feature = "insecure-research")]
+    #[test]
+    fn test_custom_permutation() {
+        #[derive(Debug)]
+        struct XorConstant;
+
+        impl Permutation for XorConstant {
+            fn mix(&self, state: &mut [u64], constant: u64) {
+                for word in state.iter_mut() {
+                    *word = (*word ^ constant).rotate_left(1);
+                }
+            }
+        }
+
+        static XOR_CONSTANT: XorConstant = XorConstant;
+        let builder = BlueHashCore::builder(DigestSize::Bit128).permutation(&XOR_CONSTANT);
+        let custom = digest(builder.build());
+        assert_ne!(custom, digest(BlueHashCore::new(DigestSize::Bit128)));
+        assert_eq!(custom, digest(builder.build()));
+
+        let mut native = BlueHashCore::new(DigestSize::Bit128).finalize_xof();
+        let mut swapped = builder.build().finalize_xof();
+        assert_ne!(native.squeeze_array::<32>(), swapped.squeeze_array::<32>());
+    }
+}
@@ -0,0 +1,194 @@
+//! Hash-consing cache keyed by BlueHash digests.
+//!
+//! `HashCache` stores each distinct value once, under the BlueHash digest of
+//! its canonical `BlueHashable` encoding. Interning an equal value again
+//! returns the existing key instead of storing a copy, which is what build
+//! systems and compilers want for ASTs, toolchain configurations or query
+//! results: the key doubles as a stable, content-derived identifier.
+//!
+//! Which entries to drop once the cache grows is decided by an
+//! `EvictionPolicy`. `NoEviction` never evicts; `LruEviction` keeps the most
+//! recently used entries up to a fixed count. Custom policies (by size, by
+//! age, ...) implement the trait themselves.
+
+use crate::{BlueHashable, DigestSize};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// Decides which entries a `HashCache` evicts.
+pub trait EvictionPolicy {
+    /// Called when `key` is inserted into the cache or looked up in it.
+    fn touch(&mut self, key: &[u8]);
+
+    /// Called when `key` leaves the cache, whether evicted or removed.
+    fn forget(&mut self, key: &[u8]);
+
+    /// Returns the key to evict from a cache holding `len` entries, or `None`
+    /// to keep them all. Called after every insertion until it returns `None`.
+    fn victim(&mut self, len: usize) -> Option<Vec<u8>>;
+}
+
+/// A policy that never evicts.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct NoEviction;
+
+impl EvictionPolicy for NoEviction {
+    fn touch(&mut self, _key: &[u8]) {}
+
+    fn forget(&mut self, _key: &[u8]) {}
+
+    fn victim(&mut self, _len: usize) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// Evicts the least recently used entry once the cache exceeds `capacity`.
+#[derive(Debug, Clone)]
+pub struct LruEviction {
+    capacity: usize,
+    tick: u64,
+    by_key: BTreeMap<Vec<u8>, u64>,
+    by_tick: BTreeMap<u64, Vec<u8>>,
+}
+
+impl LruEviction {
+    /// Creates a policy keeping at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            by_key: BTreeMap::new(),
+            by_tick: BTreeMap::new(),
+        }
+    }
+}
+
+impl EvictionPolicy for LruEviction {
+    fn touch(&mut self, key: &[u8]) {
+        self.forget(key);
+        self.tick += 1;
+        self.by_key.insert(key.to_vec(), self.tick);
+        self.by_tick.insert(self.tick, key.to_vec());
+    }
+
+    fn forget(&mut self, key: &[u8]) {
+        if let Some(tick) = self.by_key.remove(key) {
+            self.by_tick.remove(&tick);
+        }
+    }
+
+    fn victim(&mut self, len: usize) -> Option<Vec<u8>> {
+        if len <= self.capacity {
+            return None;
+        }
+        self.by_tick.first_key_value().map(|(_, key)| key.clone())
+    }
+}
+
+/// Deduplicating store of values keyed by their BlueHash digest.
+#[derive(Debug, Clone)]
+pub struct HashCache<T, P = NoEviction> {
+    digest_size: DigestSize,
+    entries: BTreeMap<Vec<u8>, T>,
+    policy: P,
+}
+
+impl<T: BlueHashable> HashCache<T> {
+    /// Creates a cache that never evicts.
+    pub fn new(digest_size: DigestSize) -> Self {
+        Self::with_policy(digest_size, NoEviction)
+    }
+}
+
+impl<T: BlueHashable, P: EvictionPolicy> HashCache<T, P> {
+    /// Creates a cache evicting according to `policy`.
+    pub fn with_policy(digest_size: DigestSize, policy: P) -> Self {
+        Self {
+            digest_size,
+            entries: BTreeMap::new(),
+            policy,
+        }
+    }
+
+    /// Returns the key `value` is (or would be) stored under.
+    pub fn key_of(&self, value: &T) -> Vec<u8> {
+        value.blue_hash(self.digest_size)
+    }
+
+    /// Stores `value` unless an equal value is already cached, and returns
+    /// its key.
+    pub fn intern(&mut self, value: T) -> Vec<u8> {
+        let key = self.key_of(&value);
+        self.entries.entry(key.clone()).or_insert(value);
+        self.policy.touch(&key);
+        while let Some(victim) = self.policy.victim(self.entries.len()) {
+            self.entries.remove(&victim);
+            self.policy.forget(&victim);
+        }
+        key
+    }
+
+    /// Returns the value stored under `key`, marking it as used.
+    pub fn get(&mut self, key: &[u8]) -> Option<&T> {
+        if self.entries.contains_key(key) {
+            self.policy.touch(key);
+        }
+        self.entries.get(key)
+    }
+
+    /// Returns the value stored under `key` without marking it as used.
+    pub fn peek(&self, key: &[u8]) -> Option<&T> {
+        self.entries.get(key)
+    }
+
+    /// Removes and returns the value stored under `key`.
+    pub fn remove(&mut self, key: &[u8]) -> Option<T> {
+        let value = self.entries.remove(key)?;
+        self.policy.forget(key);
+        Some(value)
+    }
+
+    pub fn contains(&self, key: &[u8]) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    #[test]
+    fn test_intern_deduplicates() {
+        let mut cache = HashCache::new(DigestSize::Bit128);
+        let a = cache.intern(String::from("fn main() {}"));
+        let b = cache.intern(String::from("fn main() {}"));
+        let c = cache.intern(String::from("fn lib() {}"));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.peek(&a).map(String::as_str), Some("fn main() {}"));
+        assert_eq!(cache.remove(&c), Some(String::from("fn lib() {}")));
+        assert!(!cache.contains(&c));
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut cache = HashCache::with_policy(DigestSize::Bit128, LruEviction::new(2));
+        let one = cache.intern(1u32);
+        let two = cache.intern(2u32);
+        assert_eq!(cache.get(&one), Some(&1));
+        let three = cache.intern(3u32);
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains(&one) && cache.contains(&three));
+        assert!(!cache.contains(&two));
+    }
+}
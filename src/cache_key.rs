@@ -0,0 +1,95 @@
+//! Domain-separated cache keys.
+//!
+//! Keys built by concatenating strings (`format!("{route}:{body}")`) collide
+//! as soon as a value contains the separator, which for an HTTP cache means
+//! one client's response can be served to another. `CacheKey` frames every
+//! name and value instead:
+//!
+//! ```text
+//! key = BlueHash(len(label) || label || len(namespace) || namespace
+//!                || for each field: len(name) || name || len(value) || value)
+//! ```
+//!
+//! with `label = "bluehash cache key v1"` and 8-byte big-endian lengths;
+//! `field_u64` values are framed as their 8-byte big-endian encoding. Fields
+//! are hashed in the order they are added, so always add them in the same
+//! order. Keys are BlueHash-256 unless another size is chosen.
+
+use crate::{BlueHashCore, Digest, DigestOutput, DigestSize};
+
+const CACHE_KEY_LABEL: &[u8] = b"bluehash cache key v1";
+
+/// Fluent builder for a cache key; see the module docs.
+#[derive(Debug, Clone)]
+pub struct CacheKey {
+    hasher: BlueHashCore,
+}
+
+impl CacheKey {
+    /// Starts a BlueHash-256 key in `namespace`, e.g. the service name.
+    pub fn new(namespace: &str) -> Self {
+        Self::with_digest_size(namespace, DigestSize::Bit256)
+    }
+
+    /// Starts a key of the given size in `namespace`.
+    pub fn with_digest_size(namespace: &str, digest_size: DigestSize) -> Self {
+        let mut hasher = BlueHashCore::new(digest_size);
+        hasher.update_framed(CACHE_KEY_LABEL);
+        hasher.update_str(namespace);
+        Self { hasher }
+    }
+
+    /// Adds a text field.
+    pub fn field(self, name: &str, value: &str) -> Self {
+        self.field_bytes(name, value.as_bytes())
+    }
+
+    /// Adds a binary field, e.g. a request body.
+    pub fn field_bytes(mut self, name: &str, value: &[u8]) -> Self {
+        self.hasher.update_str(name);
+        self.hasher.update_framed(value);
+        self
+    }
+
+    /// Adds an integer field, e.g. a page number or schema version.
+    pub fn field_u64(self, name: &str, value: u64) -> Self {
+        self.field_bytes(name, &value.to_be_bytes())
+    }
+
+    /// Returns the key; its `Display` form is lowercase hex.
+    pub fn build(mut self) -> DigestOutput {
+        DigestOutput::from_bytes(&self.hasher.finalize()).expect("BlueHash digest length")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields_are_framed() {
+        let key = |route: &str, query: &str| {
+            CacheKey::new("my-service")
+                .field("route", route)
+                .field("query", query)
+                .build()
+        };
+        assert_eq!(key("/users", "page=2"), key("/users", "page=2"));
+        // Moving bytes between fields changes the key.
+        assert_ne!(key("/users?", "page=2"), key("/users", "?page=2"));
+        assert_ne!(
+            key("/users", "page=2"),
+            CacheKey::new("other-service")
+                .field("route", "/users")
+                .field("query", "page=2")
+                .build()
+        );
+
+        let small = CacheKey::with_digest_size("my-service", DigestSize::Bit128)
+            .field_bytes("body", b"{}")
+            .field_u64("version", 3)
+            .build();
+        assert_eq!(small.digest_size(), DigestSize::Bit128);
+        assert_eq!(small.to_string().len(), 32);
+    }
+}
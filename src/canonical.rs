@@ -0,0 +1,554 @@
+//! Canonical serde-based object hashing.
+//!
+//! Values are serialized with a deterministic binary encoding before hashing:
+//! every value carries a one-byte type tag, integers use fixed widths
+//! (64-bit unless they need 128), strings and sequences are length-prefixed,
+//! and map entries (including struct fields) are sorted by their encoded key.
+//! Two values that serialize to the same logical data therefore hash the
+//! same regardless of map iteration order or integer width, which makes the
+//! digest suitable for config fingerprints and cache keys.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use serde::ser::{self, Serialize};
+use std::fmt;
+
+/// Error produced while canonically encoding a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalError(String);
+
+impl fmt::Display for CanonicalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "canonical encoding failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for CanonicalError {}
+
+impl ser::Error for CanonicalError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        CanonicalError(msg.to_string())
+    }
+}
+
+mod tag {
+    pub const UNIT: u8 = b'0';
+    pub const BOOL: u8 = b'?';
+    pub const UINT: u8 = b'u';
+    pub const UINT128: u8 = b'U';
+    pub const INT: u8 = b'i';
+    pub const INT128: u8 = b'I';
+    pub const FLOAT: u8 = b'f';
+    pub const CHAR: u8 = b'c';
+    pub const STR: u8 = b's';
+    pub const BYTES: u8 = b'b';
+    pub const NONE: u8 = b'n';
+    pub const SOME: u8 = b'S';
+    pub const SEQ: u8 = b'l';
+    pub const MAP: u8 = b'm';
+    pub const VARIANT: u8 = b'v';
+}
+
+/// Encodes `value` with the canonical encoding described in the module docs.
+///
+/// # Arguments
+///
+/// * `value` - Any serde-serializable value.
+///
+/// # Returns
+///
+/// The canonical byte encoding, or an error raised by the value's `Serialize` impl.
+pub fn to_canonical_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, CanonicalError> {
+    let mut out = Vec::new();
+    value.serialize(CanonicalSerializer { out: &mut out })?;
+    Ok(out)
+}
+
+/// Canonically encodes `value` and hashes the result.
+///
+/// # Arguments
+///
+/// * `digest_size` - The BlueHash variant to use.
+/// * `value` - Any serde-serializable value.
+///
+/// # Returns
+///
+/// The digest of the canonical encoding.
+pub fn hash_serde<T: Serialize + ?Sized>(
+    digest_size: DigestSize,
+    value: &T,
+) -> Result<Vec<u8>, CanonicalError> {
+    let encoded = to_canonical_bytes(value)?;
+    let mut hasher = BlueHashCore::new(digest_size);
+    hasher.update(&encoded);
+    Ok(hasher.finalize())
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    out.extend_from_slice(&(len as u64).to_be_bytes());
+}
+
+fn write_framed(out: &mut Vec<u8>, tag: u8, bytes: &[u8]) {
+    out.push(tag);
+    write_len(out, bytes.len());
+    out.extend_from_slice(bytes);
+}
+
+struct CanonicalSerializer<'a> {
+    out: &'a mut Vec<u8>,
+}
+
+impl CanonicalSerializer<'_> {
+    fn uint(self, v: u64) -> Result<(), CanonicalError> {
+        self.out.push(tag::UINT);
+        self.out.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn int(self, v: i64) -> Result<(), CanonicalError> {
+        if v >= 0 {
+            return self.uint(v as u64);
+        }
+        self.out.push(tag::INT);
+        self.out.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn variant(self, variant: &str) -> Self {
+        write_framed(self.out, tag::VARIANT, variant.as_bytes());
+        self
+    }
+}
+
+/// Collects sequence elements so the element count can be written first.
+struct SeqCollector<'a> {
+    out: &'a mut Vec<u8>,
+    count: usize,
+    items: Vec<u8>,
+}
+
+impl<'a> SeqCollector<'a> {
+    fn new(out: &'a mut Vec<u8>) -> Self {
+        Self {
+            out,
+            count: 0,
+            items: Vec::new(),
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CanonicalError> {
+        self.count += 1;
+        value.serialize(CanonicalSerializer {
+            out: &mut self.items,
+        })
+    }
+
+    fn finish(self) -> Result<(), CanonicalError> {
+        self.out.push(tag::SEQ);
+        write_len(self.out, self.count);
+        self.out.extend_from_slice(&self.items);
+        Ok(())
+    }
+}
+
+/// Collects encoded map entries so they can be sorted by key.
+struct MapCollector<'a> {
+    out: &'a mut Vec<u8>,
+    entries: Vec<(Vec<u8>, Vec<u8>)>,
+    pending_key: Option<Vec<u8>>,
+}
+
+impl<'a> MapCollector<'a> {
+    fn new(out: &'a mut Vec<u8>) -> Self {
+        Self {
+            out,
+            entries: Vec::new(),
+            pending_key: None,
+        }
+    }
+
+    fn finish(mut self) -> Result<(), CanonicalError> {
+        self.entries.sort();
+        self.out.push(tag::MAP);
+        write_len(self.out, self.entries.len());
+        for (key, value) in self.entries {
+            self.out.extend_from_slice(&key);
+            self.out.extend_from_slice(&value);
+        }
+        Ok(())
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), CanonicalError> {
+        let mut encoded_key = Vec::new();
+        write_framed(&mut encoded_key, tag::STR, key.as_bytes());
+        let encoded_value = to_canonical_bytes(value)?;
+        self.entries.push((encoded_key, encoded_value));
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for CanonicalSerializer<'a> {
+    type Ok = ();
+    type Error = CanonicalError;
+    type SerializeSeq = SeqCollector<'a>;
+    type SerializeTuple = SeqCollector<'a>;
+    type SerializeTupleStruct = SeqCollector<'a>;
+    type SerializeTupleVariant = SeqCollector<'a>;
+    type SerializeMap = MapCollector<'a>;
+    type SerializeStruct = MapCollector<'a>;
+    type SerializeStructVariant = MapCollector<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), CanonicalError> {
+        self.out.extend_from_slice(&[tag::BOOL, v as u8]);
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), CanonicalError> {
+        self.int(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), CanonicalError> {
+        self.int(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), CanonicalError> {
+        self.int(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), CanonicalError> {
+        self.int(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), CanonicalError> {
+        if let Ok(small) = i64::try_from(v) {
+            return self.int(small);
+        }
+        if v > 0 {
+            return self.serialize_u128(v as u128);
+        }
+        self.out.push(tag::INT128);
+        self.out.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), CanonicalError> {
+        self.uint(v as u64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), CanonicalError> {
+        self.uint(v as u64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), CanonicalError> {
+        self.uint(v as u64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), CanonicalError> {
+        self.uint(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), CanonicalError> {
+        if let Ok(small) = u64::try_from(v) {
+            return self.uint(small);
+        }
+        self.out.push(tag::UINT128);
+        self.out.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), CanonicalError> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), CanonicalError> {
+        // 统一 NaN 与负零的表示，保证编码确定
+        let bits = if v.is_nan() {
+            f64::NAN.to_bits()
+        } else if v == 0.0 {
+            0
+        } else {
+            v.to_bits()
+        };
+        self.out.push(tag::FLOAT);
+        self.out.extend_from_slice(&bits.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), CanonicalError> {
+        self.out.push(tag::CHAR);
+        self.out.extend_from_slice(&(v as u32).to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), CanonicalError> {
+        write_framed(self.out, tag::STR, v.as_bytes());
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), CanonicalError> {
+        write_framed(self.out, tag::BYTES, v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), CanonicalError> {
+        self.out.push(tag::NONE);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), CanonicalError> {
+        self.out.push(tag::SOME);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), CanonicalError> {
+        self.out.push(tag::UNIT);
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), CanonicalError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<(), CanonicalError> {
+        self.variant(variant).serialize_unit()
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), CanonicalError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), CanonicalError> {
+        value.serialize(self.variant(variant))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqCollector<'a>, CanonicalError> {
+        Ok(SeqCollector::new(self.out))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<SeqCollector<'a>, CanonicalError> {
+        Ok(SeqCollector::new(self.out))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<SeqCollector<'a>, CanonicalError> {
+        Ok(SeqCollector::new(self.out))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SeqCollector<'a>, CanonicalError> {
+        Ok(SeqCollector::new(self.variant(variant).out))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapCollector<'a>, CanonicalError> {
+        Ok(MapCollector::new(self.out))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<MapCollector<'a>, CanonicalError> {
+        Ok(MapCollector::new(self.out))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<MapCollector<'a>, CanonicalError> {
+        Ok(MapCollector::new(self.variant(variant).out))
+    }
+}
+
+impl ser::SerializeSeq for SeqCollector<'_> {
+    type Ok = ();
+    type Error = CanonicalError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), CanonicalError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<(), CanonicalError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqCollector<'_> {
+    type Ok = ();
+    type Error = CanonicalError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), CanonicalError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<(), CanonicalError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqCollector<'_> {
+    type Ok = ();
+    type Error = CanonicalError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CanonicalError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<(), CanonicalError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqCollector<'_> {
+    type Ok = ();
+    type Error = CanonicalError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CanonicalError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<(), CanonicalError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeMap for MapCollector<'_> {
+    type Ok = ();
+    type Error = CanonicalError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), CanonicalError> {
+        self.pending_key = Some(to_canonical_bytes(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CanonicalError> {
+        let key = self
+            .pending_key
+            .take()
+            .ok_or_else(|| CanonicalError("map value without key".to_string()))?;
+        self.entries.push((key, to_canonical_bytes(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), CanonicalError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for MapCollector<'_> {
+    type Ok = ();
+    type Error = CanonicalError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), CanonicalError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), CanonicalError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for MapCollector<'_> {
+    type Ok = ();
+    type Error = CanonicalError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), CanonicalError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), CanonicalError> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn test_map_order_does_not_matter() {
+        let mut a = HashMap::new();
+        let mut b = HashMap::new();
+        for i in 0..32u32 {
+            a.insert(format!("key{}", i), i);
+        }
+        for i in (0..32u32).rev() {
+            b.insert(format!("key{}", i), i);
+        }
+        let sorted: BTreeMap<_, _> = a.clone().into_iter().collect();
+        assert_eq!(
+            to_canonical_bytes(&a).unwrap(),
+            to_canonical_bytes(&b).unwrap()
+        );
+        assert_eq!(
+            to_canonical_bytes(&a).unwrap(),
+            to_canonical_bytes(&sorted).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_integer_width_does_not_matter() {
+        assert_eq!(
+            to_canonical_bytes(&7u8).unwrap(),
+            to_canonical_bytes(&7u64).unwrap()
+        );
+        assert_eq!(
+            to_canonical_bytes(&-7i16).unwrap(),
+            to_canonical_bytes(&-7i64).unwrap()
+        );
+        assert_ne!(
+            to_canonical_bytes(&"7").unwrap(),
+            to_canonical_bytes(&7u8).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_hash_serde() {
+        let config = vec![("threads", 4u32), ("retries", 3u32)];
+        let digest = hash_serde(DigestSize::Bit256, &config).unwrap();
+        assert_eq!(digest.len(), 32);
+        assert_ne!(
+            digest,
+            hash_serde(DigestSize::Bit256, &vec![("threads", 4u32)]).unwrap()
+        );
+    }
+}
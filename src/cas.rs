@@ -0,0 +1,221 @@
+//! Content addresses for object stores.
+//!
+//! A content address has the form `bluehash256:<hex>`, where the prefix names
+//! the digest size and the hex part is the lowercase digest of the content.
+//! Addresses can be mapped to sharded storage paths such as `ab/cd/abcd...`
+//! to keep directory sizes bounded in blob stores.
+
+use crate::output::decode_hex;
+use crate::utils::to_hex_string;
+use crate::{constant_time_eq, BlueHashCore, Digest, DigestSize, ParseError};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+/// Error returned when a content address cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CasError {
+    /// The address has no `algorithm:` prefix.
+    MissingPrefix,
+    /// The algorithm prefix is not a BlueHash variant.
+    UnknownAlgorithm(String),
+    /// The digest is not valid lowercase hex.
+    InvalidDigest(ParseError),
+    /// The digest length does not match the algorithm prefix.
+    LengthMismatch { expected: usize, actual: usize },
+}
+
+impl fmt::Display for CasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CasError::MissingPrefix => write!(f, "content address has no algorithm prefix"),
+            CasError::UnknownAlgorithm(name) => write!(f, "unknown algorithm {:?}", name),
+            CasError::InvalidDigest(err) => write!(f, "invalid digest: {}", err),
+            CasError::LengthMismatch { expected, actual } => write!(
+                f,
+                "digest is {} bytes but the algorithm produces {}",
+                actual, expected
+            ),
+        }
+    }
+}
+
+impl core::error::Error for CasError {}
+
+/// Returns the address prefix used for a digest size, e.g. `bluehash256`.
+pub fn algorithm_name(digest_size: DigestSize) -> &'static str {
+    match digest_size {
+        DigestSize::Bit128 => "bluehash128",
+        DigestSize::Bit256 => "bluehash256",
+        DigestSize::Bit512 => "bluehash512",
+    }
+}
+
+fn algorithm_from_name(name: &str) -> Option<DigestSize> {
+    match name {
+        "bluehash128" => Some(DigestSize::Bit128),
+        "bluehash256" => Some(DigestSize::Bit256),
+        "bluehash512" => Some(DigestSize::Bit512),
+        _ => None,
+    }
+}
+
+/// A BlueHash content address.
+#[derive(Debug, Clone, Eq)]
+pub struct ContentAddress {
+    digest_size: DigestSize,
+    digest: Vec<u8>,
+}
+
+impl ContentAddress {
+    /// Computes the content address of `data`.
+    pub fn of(digest_size: DigestSize, data: &[u8]) -> Self {
+        let mut hasher = BlueHashCore::new(digest_size);
+        hasher.update(data);
+        Self {
+            digest_size,
+            digest: hasher.finalize(),
+        }
+    }
+
+    /// Builds an address from an already computed digest.
+    ///
+    /// Returns `None` if the digest length does not match a BlueHash digest size.
+    pub fn from_digest(digest: &[u8]) -> Option<Self> {
+        let digest_size = DigestSize::from_digest_length(digest.len())?;
+        Some(Self {
+            digest_size,
+            digest: digest.to_vec(),
+        })
+    }
+
+    pub fn digest_size(&self) -> DigestSize {
+        self.digest_size
+    }
+
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// Lowercase hex of the digest, without the algorithm prefix.
+    pub fn hex(&self) -> String {
+        to_hex_string(&self.digest)
+    }
+
+    /// Checks in constant time whether `data` has this address.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        let actual = Self::of(self.digest_size, data);
+        constant_time_eq(&actual.digest, &self.digest)
+    }
+
+    /// Derives a sharded storage path: `levels` directories of two hex
+    /// characters each, followed by the full hex digest as the file name.
+    ///
+    /// # Arguments
+    ///
+    /// * `levels` - Number of shard directories (clamped so the shards never
+    ///   use more characters than the digest has).
+    ///
+    /// # Returns
+    ///
+    /// A relative path such as `ab/cd/abcd...` for `levels == 2`.
+    #[cfg(feature = "std")]
+    pub fn shard_path(&self, levels: usize) -> PathBuf {
+        let hex = self.hex();
+        let levels = levels.min(hex.len() / 2);
+        let mut path = PathBuf::new();
+        for level in 0..levels {
+            path.push(&hex[level * 2..level * 2 + 2]);
+        }
+        path.push(&hex);
+        path
+    }
+}
+
+impl PartialEq for ContentAddress {
+    fn eq(&self, other: &Self) -> bool {
+        self.digest_size == other.digest_size && constant_time_eq(&self.digest, &other.digest)
+    }
+}
+
+impl fmt::Display for ContentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", algorithm_name(self.digest_size), self.hex())
+    }
+}
+
+impl FromStr for ContentAddress {
+    type Err = CasError;
+
+    /// Parses `bluehashNNN:<hex>`. The hex part must be lowercase so every
+    /// object has exactly one textual address.
+    fn from_str(s: &str) -> Result<Self, CasError> {
+        let (name, hex) = s.split_once(':').ok_or(CasError::MissingPrefix)?;
+        let digest_size = algorithm_from_name(name)
+            .ok_or_else(|| CasError::UnknownAlgorithm(name.to_string()))?;
+        if let Some((index, character)) = hex.char_indices().find(|(_, c)| c.is_ascii_uppercase()) {
+            return Err(CasError::InvalidDigest(ParseError::InvalidCharacter {
+                index,
+                character,
+            }));
+        }
+        let digest = decode_hex(hex).map_err(CasError::InvalidDigest)?;
+        if digest.len() != digest_size.digest_length() {
+            return Err(CasError::LengthMismatch {
+                expected: digest_size.digest_length(),
+                actual: digest.len(),
+            });
+        }
+        Ok(Self {
+            digest_size,
+            digest,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_round_trip_and_shards() {
+        let address = ContentAddress::of(DigestSize::Bit256, b"blob");
+        let text = address.to_string();
+        assert!(text.starts_with("bluehash256:"));
+        assert_eq!(text.len(), "bluehash256:".len() + 64);
+        let parsed: ContentAddress = text.parse().unwrap();
+        assert_eq!(parsed, address);
+        assert!(parsed.matches(b"blob"));
+        assert!(!parsed.matches(b"blob2"));
+
+        let hex = address.hex();
+        let expected: PathBuf = [&hex[0..2], &hex[2..4], &hex[..]].iter().collect();
+        assert_eq!(address.shard_path(2), expected);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "deadbeef".parse::<ContentAddress>(),
+            Err(CasError::MissingPrefix)
+        );
+        assert_eq!(
+            "sha256:00".parse::<ContentAddress>(),
+            Err(CasError::UnknownAlgorithm("sha256".to_string()))
+        );
+        assert_eq!(
+            "bluehash128:00".parse::<ContentAddress>(),
+            Err(CasError::LengthMismatch {
+                expected: 16,
+                actual: 1
+            })
+        );
+        assert!(matches!(
+            "bluehash128:AB".parse::<ContentAddress>(),
+            Err(CasError::InvalidDigest(_))
+        ));
+    }
+}
@@ -0,0 +1,291 @@
+//! Out-of-order chunk verification for over-the-air updates.
+//!
+//! BlueHash is a sponge, so the image has to be absorbed in order.
+//! `ChunkedVerifier` absorbs the contiguous prefix of the image as soon as it
+//! arrives and holds chunks that arrive ahead of it until the gap before them
+//! is filled; a coverage bitmap (one bit per chunk) rejects duplicates, and the
+//! digest is checked once every chunk has arrived. With in-order delivery
+//! nothing is buffered. The held-back chunks are capped at
+//! `DEFAULT_MAX_PENDING_LEN` bytes (see `with_max_pending_len`); a chunk that
+//! would exceed the cap is rejected with `PendingFull` and can be requested
+//! again once the gap before it has been filled.
+
+use crate::{constant_time_eq, BlueHashCore, Digest, DigestSize, BLOCK_LEN};
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Default cap on the bytes held back by a `ChunkedVerifier`.
+pub const DEFAULT_MAX_PENDING_LEN: usize = 1 << 20;
+
+/// Error returned by `ChunkedVerifier`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChunkError {
+    /// The chunk size is zero or not a multiple of 8 bytes.
+    InvalidChunkSize(usize),
+    /// The expected digest has the wrong length for the digest size.
+    InvalidDigestLength,
+    /// The offset is not a multiple of the chunk size, or lies past the end.
+    InvalidOffset(u64),
+    /// The chunk at `offset` has the wrong length.
+    InvalidLength(u64),
+    /// The chunk at `offset` was already accepted.
+    Duplicate(u64),
+    /// Holding the chunk at `offset` back would exceed the pending cap.
+    PendingFull(u64),
+    /// Not every chunk has been received.
+    Incomplete,
+    /// The image is complete but its digest does not match.
+    DigestMismatch,
+}
+
+impl fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkError::InvalidChunkSize(size) => write!(f, "invalid chunk size {}", size),
+            ChunkError::InvalidDigestLength => write!(f, "expected digest has the wrong length"),
+            ChunkError::InvalidOffset(offset) => write!(f, "invalid chunk offset {}", offset),
+            ChunkError::InvalidLength(offset) => {
+                write!(f, "chunk at offset {} has the wrong length", offset)
+            }
+            ChunkError::Duplicate(offset) => write!(f, "duplicate chunk at offset {}", offset),
+            ChunkError::PendingFull(offset) => {
+                write!(f, "no room to hold back the chunk at offset {}", offset)
+            }
+            ChunkError::Incomplete => write!(f, "image is incomplete"),
+            ChunkError::DigestMismatch => write!(f, "image digest mismatch"),
+        }
+    }
+}
+
+impl core::error::Error for ChunkError {}
+
+/// Verifies an image delivered as fixed-size chunks in arbitrary order.
+#[derive(Debug, Clone)]
+pub struct ChunkedVerifier {
+    hasher: BlueHashCore,
+    total_len: u64,
+    chunk_size: usize,
+    expected: Vec<u8>,
+    received: Vec<u64>,
+    received_count: u64,
+    absorbed_len: u64,
+    pending: BTreeMap<u64, Vec<u8>>,
+    pending_len: usize,
+    max_pending_len: usize,
+}
+
+impl ChunkedVerifier {
+    /// Prepares verification of a `total_len`-byte image.
+    ///
+    /// # Arguments
+    ///
+    /// * `digest_size` - The digest size of `expected_digest`.
+    /// * `total_len` - The length of the complete image.
+    /// * `chunk_size` - The size of every chunk except possibly the last;
+    ///   must be a non-zero multiple of 8 bytes.
+    /// * `expected_digest` - The BlueHash digest of the complete image.
+    pub fn new(
+        digest_size: DigestSize,
+        total_len: u64,
+        chunk_size: usize,
+        expected_digest: &[u8],
+    ) -> Result<Self, ChunkError> {
+        if chunk_size == 0 || !chunk_size.is_multiple_of(BLOCK_LEN) {
+            return Err(ChunkError::InvalidChunkSize(chunk_size));
+        }
+        if expected_digest.len() != digest_size.digest_length() {
+            return Err(ChunkError::InvalidDigestLength);
+        }
+        let chunk_count = total_len.div_ceil(chunk_size as u64);
+        Ok(Self {
+            hasher: BlueHashCore::new(digest_size),
+            total_len,
+            chunk_size,
+            expected: expected_digest.to_vec(),
+            received: vec![0u64; chunk_count.div_ceil(64) as usize],
+            received_count: 0,
+            absorbed_len: 0,
+            pending: BTreeMap::new(),
+            pending_len: 0,
+            max_pending_len: DEFAULT_MAX_PENDING_LEN,
+        })
+    }
+
+    /// Sets the most bytes held back while waiting for earlier chunks; 0
+    /// accepts chunks in order only.
+    pub fn with_max_pending_len(mut self, max_pending_len: usize) -> Self {
+        self.max_pending_len = max_pending_len;
+        self
+    }
+
+    /// Number of chunks the image is split into.
+    pub fn chunk_count(&self) -> u64 {
+        self.total_len.div_ceil(self.chunk_size as u64)
+    }
+
+    /// Number of distinct chunks accepted so far.
+    pub fn received_count(&self) -> u64 {
+        self.received_count
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.received_count == self.chunk_count()
+    }
+
+    /// Returns whether the chunk starting at `offset` has been accepted.
+    pub fn has_chunk(&self, offset: u64) -> bool {
+        let index = offset / self.chunk_size as u64;
+        offset.is_multiple_of(self.chunk_size as u64)
+            && index < self.chunk_count()
+            && self.received[(index / 64) as usize] & (1 << (index % 64)) != 0
+    }
+
+    /// Accepts the chunk starting at `offset`; it is absorbed once every chunk
+    /// before it has arrived.
+    ///
+    /// Rejected chunks leave the verifier unchanged, so a client can simply
+    /// request them again.
+    pub fn accept(&mut self, offset: u64, chunk: &[u8]) -> Result<(), ChunkError> {
+        if !offset.is_multiple_of(self.chunk_size as u64) || offset >= self.total_len {
+            return Err(ChunkError::InvalidOffset(offset));
+        }
+        let expected_len = (self.total_len - offset).min(self.chunk_size as u64);
+        if chunk.len() as u64 != expected_len {
+            return Err(ChunkError::InvalidLength(offset));
+        }
+        let index = offset / self.chunk_size as u64;
+        let (word, bit) = ((index / 64) as usize, 1u64 << (index % 64));
+        if self.received[word] & bit != 0 {
+            return Err(ChunkError::Duplicate(offset));
+        }
+        if offset != self.absorbed_len && self.pending_len + chunk.len() > self.max_pending_len {
+            return Err(ChunkError::PendingFull(offset));
+        }
+        self.received[word] |= bit;
+        self.received_count += 1;
+
+        if offset == self.absorbed_len {
+            self.hasher.update(chunk);
+            self.absorbed_len += chunk.len() as u64;
+            while let Some(next) = self.pending.remove(&self.absorbed_len) {
+                self.hasher.update(&next);
+                self.absorbed_len += next.len() as u64;
+                self.pending_len -= next.len();
+            }
+        } else {
+            self.pending.insert(offset, chunk.to_vec());
+            self.pending_len += chunk.len();
+        }
+        Ok(())
+    }
+
+    /// Number of bytes held back until the chunks before them arrive.
+    pub fn pending_len(&self) -> usize {
+        self.pending_len
+    }
+
+    /// Checks the digest of the complete image in constant time.
+    pub fn finalize(mut self) -> Result<(), ChunkError> {
+        if !self.is_complete() {
+            return Err(ChunkError::Incomplete);
+        }
+        if constant_time_eq(&self.hasher.finalize(), &self.expected) {
+            Ok(())
+        } else {
+            Err(ChunkError::DigestMismatch)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(data: &[u8]) -> Vec<u8> {
+        let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    #[test]
+    fn test_out_of_order_chunks_match_sequential_digest() {
+        let image: Vec<u8> = (0..90u8).collect();
+        let expected = digest(&image);
+        let mut verifier = ChunkedVerifier::new(DigestSize::Bit128, 90, 16, &expected).unwrap();
+        assert_eq!(verifier.chunk_count(), 6);
+        for offset in [80u64, 16, 64, 0, 48] {
+            let end = (offset as usize + 16).min(90);
+            verifier
+                .accept(offset, &image[offset as usize..end])
+                .unwrap();
+        }
+        assert!(!verifier.is_complete());
+        assert_eq!(verifier.pending_len(), 10 + 16 + 16);
+        assert!(!verifier.has_chunk(32));
+        assert_eq!(verifier.clone().finalize(), Err(ChunkError::Incomplete));
+        assert_eq!(
+            verifier.accept(16, &image[16..32]),
+            Err(ChunkError::Duplicate(16))
+        );
+        assert_eq!(
+            verifier.accept(8, &image[8..24]),
+            Err(ChunkError::InvalidOffset(8))
+        );
+        assert_eq!(
+            verifier.accept(32, &image[32..40]),
+            Err(ChunkError::InvalidLength(32))
+        );
+        verifier.accept(32, &image[32..48]).unwrap();
+        assert!(verifier.has_chunk(32));
+        assert_eq!(verifier.finalize(), Ok(()));
+
+        let mut capped = ChunkedVerifier::new(DigestSize::Bit128, 90, 16, &expected)
+            .unwrap()
+            .with_max_pending_len(32);
+        capped.accept(16, &image[16..32]).unwrap();
+        capped.accept(32, &image[32..48]).unwrap();
+        assert_eq!(
+            capped.accept(48, &image[48..64]),
+            Err(ChunkError::PendingFull(48))
+        );
+        assert!(!capped.has_chunk(48));
+        capped.accept(0, &image[..16]).unwrap();
+        assert_eq!(capped.pending_len(), 0);
+        for offset in [64u64, 48, 80] {
+            let end = (offset as usize + 16).min(90);
+            capped.accept(offset, &image[offset as usize..end]).unwrap();
+        }
+        assert_eq!(capped.finalize(), Ok(()));
+    }
+
+    #[test]
+    fn test_chunked_verifier_detects_mismatch() {
+        let image = [0xa5u8; 36];
+        let expected = digest(&image);
+        let mut verifier = ChunkedVerifier::new(DigestSize::Bit128, 36, 8, &expected).unwrap();
+        for offset in [32u64, 24, 16, 8, 0] {
+            let end = (offset as usize + 8).min(36);
+            verifier
+                .accept(offset, &image[offset as usize..end])
+                .unwrap();
+        }
+        assert_eq!(verifier.finalize(), Ok(()));
+
+        let mut tampered = image;
+        tampered[35] ^= 1;
+        let mut verifier = ChunkedVerifier::new(DigestSize::Bit128, 36, 8, &expected).unwrap();
+        for offset in (0..36u64).step_by(8) {
+            let end = (offset as usize + 8).min(36);
+            verifier
+                .accept(offset, &tampered[offset as usize..end])
+                .unwrap();
+        }
+        assert_eq!(verifier.finalize(), Err(ChunkError::DigestMismatch));
+        assert_eq!(
+            ChunkedVerifier::new(DigestSize::Bit128, 36, 12, &expected).unwrap_err(),
+            ChunkError::InvalidChunkSize(12)
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! Multihash and CIDv1 helpers for IPLD experiments.
+//!
+//! BlueHash has no registered multicodec yet, so the multihash codes below
+//! live in the multicodec private-use range (`0x300000..=0x3fffff`) and must
+//! be treated as provisional. CIDs are rendered with the multibase
+//! base32-lowercase encoding (prefix `b`), the default for CIDv1.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+/// Provisional multihash code for BlueHash-128.
+pub const MULTIHASH_BLUEHASH_128: u64 = 0x30_b128;
+/// Provisional multihash code for BlueHash-256.
+pub const MULTIHASH_BLUEHASH_256: u64 = 0x30_b256;
+/// Provisional multihash code for BlueHash-512.
+pub const MULTIHASH_BLUEHASH_512: u64 = 0x30_b512;
+
+/// Multicodec for raw binary content.
+pub const CODEC_RAW: u64 = 0x55;
+/// Multicodec for MerkleDAG protobuf nodes.
+pub const CODEC_DAG_PB: u64 = 0x70;
+/// Multicodec for MerkleDAG CBOR nodes.
+pub const CODEC_DAG_CBOR: u64 = 0x71;
+
+const CID_VERSION: u64 = 1;
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// Error returned when a CID cannot be decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CidError {
+    /// The multibase prefix is not `b` (base32 lowercase).
+    UnsupportedMultibase(char),
+    /// The base32 payload contains an invalid character.
+    InvalidBase32(char),
+    /// A varint is truncated or longer than 9 bytes.
+    InvalidVarint,
+    /// The CID version is not 1.
+    UnsupportedVersion(u64),
+    /// The multihash code is not a BlueHash code.
+    UnknownMultihash(u64),
+    /// The multihash length does not match its code, or bytes are left over.
+    InvalidLength,
+}
+
+impl fmt::Display for CidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CidError::UnsupportedMultibase(c) => write!(f, "unsupported multibase prefix {:?}", c),
+            CidError::InvalidBase32(c) => write!(f, "invalid base32 character {:?}", c),
+            CidError::InvalidVarint => write!(f, "invalid varint"),
+            CidError::UnsupportedVersion(v) => write!(f, "unsupported CID version {}", v),
+            CidError::UnknownMultihash(code) => write!(f, "unknown multihash code {:#x}", code),
+            CidError::InvalidLength => write!(f, "invalid multihash length"),
+        }
+    }
+}
+
+impl core::error::Error for CidError {}
+
+/// Returns the provisional multihash code for a digest size.
+pub fn multihash_code(digest_size: DigestSize) -> u64 {
+    match digest_size {
+        DigestSize::Bit128 => MULTIHASH_BLUEHASH_128,
+        DigestSize::Bit256 => MULTIHASH_BLUEHASH_256,
+        DigestSize::Bit512 => MULTIHASH_BLUEHASH_512,
+    }
+}
+
+fn digest_size_from_code(code: u64) -> Option<DigestSize> {
+    match code {
+        MULTIHASH_BLUEHASH_128 => Some(DigestSize::Bit128),
+        MULTIHASH_BLUEHASH_256 => Some(DigestSize::Bit256),
+        MULTIHASH_BLUEHASH_512 => Some(DigestSize::Bit512),
+        _ => None,
+    }
+}
+
+/// Appends `value` as an unsigned LEB128 varint.
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Reads an unsigned varint, returning the value and the remaining bytes.
+fn read_varint(bytes: &[u8]) -> Result<(u64, &[u8]), CidError> {
+    let mut value = 0u64;
+    for (i, &byte) in bytes.iter().enumerate().take(9) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, &bytes[i + 1..]));
+        }
+    }
+    Err(CidError::InvalidVarint)
+}
+
+/// Encodes an existing BlueHash digest as a multihash.
+///
+/// Returns `None` if the digest length does not match a BlueHash digest size.
+pub fn multihash_from_digest(digest: &[u8]) -> Option<Vec<u8>> {
+    let digest_size = DigestSize::from_digest_length(digest.len())?;
+    let mut out = Vec::with_capacity(digest.len() + 6);
+    write_varint(&mut out, multihash_code(digest_size));
+    write_varint(&mut out, digest.len() as u64);
+    out.extend_from_slice(digest);
+    Some(out)
+}
+
+/// Hashes `data` and returns the multihash `<code><length><digest>`.
+pub fn multihash(digest_size: DigestSize, data: &[u8]) -> Vec<u8> {
+    let mut hasher = BlueHashCore::new(digest_size);
+    hasher.update(data);
+    multihash_from_digest(&hasher.finalize()).expect("finalize returns a valid digest length")
+}
+
+/// A version 1 content identifier carrying a BlueHash multihash.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cid {
+    codec: u64,
+    digest_size: DigestSize,
+    digest: Vec<u8>,
+}
+
+impl Cid {
+    /// Hashes `data` and wraps the result in a CIDv1 with the given content codec.
+    pub fn new_v1(codec: u64, digest_size: DigestSize, data: &[u8]) -> Self {
+        let mut hasher = BlueHashCore::new(digest_size);
+        hasher.update(data);
+        Self {
+            codec,
+            digest_size,
+            digest: hasher.finalize(),
+        }
+    }
+
+    pub fn codec(&self) -> u64 {
+        self.codec
+    }
+
+    pub fn digest_size(&self) -> DigestSize {
+        self.digest_size
+    }
+
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// Binary form: `<version><codec><multihash>`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_varint(&mut out, CID_VERSION);
+        write_varint(&mut out, self.codec);
+        out.extend_from_slice(
+            &multihash_from_digest(&self.digest).expect("Cid always holds a valid digest"),
+        );
+        out
+    }
+
+    /// Parses the binary form produced by [`Cid::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CidError> {
+        let (version, rest) = read_varint(bytes)?;
+        if version != CID_VERSION {
+            return Err(CidError::UnsupportedVersion(version));
+        }
+        let (codec, rest) = read_varint(rest)?;
+        let (code, rest) = read_varint(rest)?;
+        let digest_size = digest_size_from_code(code).ok_or(CidError::UnknownMultihash(code))?;
+        let (length, digest) = read_varint(rest)?;
+        if length as usize != digest_size.digest_length() || digest.len() != length as usize {
+            return Err(CidError::InvalidLength);
+        }
+        Ok(Self {
+            codec,
+            digest_size,
+            digest: digest.to_vec(),
+        })
+    }
+}
+
+impl fmt::Display for Cid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = self.to_bytes();
+        let mut text = String::with_capacity(1 + bytes.len() * 8 / 5 + 1);
+        text.push('b');
+        let mut buffer = 0u32;
+        let mut bits = 0;
+        for &byte in &bytes {
+            buffer = (buffer << 8) | byte as u32;
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                text.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+            }
+        }
+        if bits > 0 {
+            text.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+        }
+        f.write_str(&text)
+    }
+}
+
+impl FromStr for Cid {
+    type Err = CidError;
+
+    fn from_str(s: &str) -> Result<Self, CidError> {
+        let mut chars = s.chars();
+        match chars.next() {
+            Some('b') => {}
+            Some(other) => return Err(CidError::UnsupportedMultibase(other)),
+            None => return Err(CidError::InvalidVarint),
+        }
+        let mut bytes = Vec::with_capacity(s.len() * 5 / 8);
+        let mut buffer = 0u32;
+        let mut bits = 0;
+        for c in chars {
+            let value = BASE32_ALPHABET
+                .iter()
+                .position(|&a| a as char == c)
+                .ok_or(CidError::InvalidBase32(c))?;
+            buffer = (buffer << 5) | value as u32;
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                bytes.push((buffer >> bits) as u8);
+            }
+        }
+        Self::from_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint_round_trip() {
+        for value in [
+            0u64,
+            1,
+            127,
+            128,
+            300,
+            MULTIHASH_BLUEHASH_512,
+            u32::MAX as u64,
+        ] {
+            let mut out = Vec::new();
+            write_varint(&mut out, value);
+            assert_eq!(read_varint(&out).unwrap(), (value, &[][..]));
+        }
+    }
+
+    #[test]
+    fn test_cid_round_trip() {
+        let cid = Cid::new_v1(CODEC_RAW, DigestSize::Bit256, b"ipld block");
+        let text = cid.to_string();
+        assert!(text.starts_with('b'));
+        assert_eq!(text.parse::<Cid>().unwrap(), cid);
+        assert!(cid
+            .to_bytes()
+            .ends_with(&multihash(DigestSize::Bit256, b"ipld block")));
+        assert_eq!(
+            "zabc".parse::<Cid>(),
+            Err(CidError::UnsupportedMultibase('z'))
+        );
+    }
+}
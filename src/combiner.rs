@@ -0,0 +1,99 @@
+//! Dual-hash combiner (`digest` feature).
+//!
+//! `CombinedHasher` feeds the same input to BlueHash and to a second,
+//! user-supplied `digest::Digest` (e.g. SHA-256 or SHA3-256), hedging against
+//! a break in either primitive:
+//!
+//! ```text
+//! combined     = BlueHash(len(label) || label || len(b) || b || d)
+//! concatenated = b || d
+//! ```
+//!
+//! with `b = BlueHash(m)`, `d = D(m)` and `label = "bluehash combiner v1"`.
+//! `finalize` returns the fixed-size `combined` digest. Note that hashing the
+//! concatenation makes the result depend on the collision resistance of the
+//! outer BlueHash call; where collision resistance must survive a full break
+//! of BlueHash, use `finalize_concatenated`, which is collision-resistant as
+//! long as either hash is.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+
+const COMBINER_LABEL: &[u8] = b"bluehash combiner v1";
+
+/// Hashes its input with BlueHash and a second digest.
+#[derive(Debug, Clone)]
+pub struct CombinedHasher<D> {
+    blue: BlueHashCore,
+    other: D,
+}
+
+impl<D: digest::Digest> CombinedHasher<D> {
+    /// Creates a combiner with a fresh instance of `D`.
+    pub fn new(digest_size: DigestSize) -> Self {
+        Self::with_digest(digest_size, D::new())
+    }
+
+    /// Creates a combiner around an existing `D` instance.
+    pub fn with_digest(digest_size: DigestSize, other: D) -> Self {
+        Self {
+            blue: BlueHashCore::new(digest_size),
+            other,
+        }
+    }
+
+    /// Absorbs data into both hashes.
+    pub fn update(&mut self, data: &[u8]) {
+        self.blue.update(data);
+        digest::Digest::update(&mut self.other, data);
+    }
+
+    fn outputs(mut self) -> (Vec<u8>, Vec<u8>) {
+        let blue = self.blue.finalize();
+        (blue, self.other.finalize().to_vec())
+    }
+
+    /// Returns the BlueHash digest of both outputs, as long as a BlueHash
+    /// digest of the configured size.
+    pub fn finalize(self) -> Vec<u8> {
+        let digest_size = self.blue.digest_size;
+        let (blue, other) = self.outputs();
+        let mut outer = BlueHashCore::new(digest_size);
+        outer.update_framed(COMBINER_LABEL);
+        outer.update_framed(&blue);
+        outer.update(&other);
+        outer.finalize()
+    }
+
+    /// Returns the BlueHash digest followed by the digest of `D`.
+    pub fn finalize_concatenated(self) -> Vec<u8> {
+        let (mut blue, other) = self.outputs();
+        blue.extend_from_slice(&other);
+        blue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Prehasher;
+
+    #[test]
+    fn test_combined_hasher() {
+        let mut combined = CombinedHasher::<Prehasher>::new(DigestSize::Bit128);
+        combined.update(b"belt and ");
+        combined.update(b"suspenders");
+
+        let concatenated = combined.clone().finalize_concatenated();
+        let mut blue = BlueHashCore::new(DigestSize::Bit128);
+        blue.update(b"belt and suspenders");
+        let mut other = Prehasher::default();
+        other.update(b"belt and suspenders");
+        assert_eq!(concatenated[..16], blue.finalize()[..]);
+        assert_eq!(concatenated[16..], other.finalize());
+
+        let digest = combined.finalize();
+        assert_eq!(digest.len(), 16);
+        assert_ne!(digest[..], concatenated[..16]);
+    }
+}
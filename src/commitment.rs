@@ -0,0 +1,205 @@
+//! Key commitment for AEAD ciphertexts.
+//!
+//! Common AEADs such as AES-GCM and ChaCha20-Poly1305 are not key-committing:
+//! one ciphertext can be crafted to decrypt under many keys, which enables
+//! partitioning-oracle attacks on password-based encryption. This module
+//! applies the UtC transform (Bellare and Hoang, 2022) with BlueHash:
+//!
+//! ```text
+//! commitment     = BlueHash-256_keyed(key, len(label) || label || "commit"  || nonce)
+//! encryption key = BlueHash-256_keyed(key, len(label) || label || "encrypt" || nonce)
+//! sealed         = commitment || AEAD.seal(encryption key, nonce, aad, plaintext)
+//! ```
+//!
+//! with `label = "bluehash key commitment v1"`. The inner AEAD is supplied as
+//! a closure, so any implementation can be wrapped. `open_committing` checks
+//! the commitment in constant time before the inner AEAD runs, so a wrong
+//! key is rejected without ever attempting decryption.
+
+use crate::{constant_time_eq, BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+use core::fmt;
+
+const COMMITMENT_LABEL: &[u8] = b"bluehash key commitment v1";
+
+/// Length of the key commitment and of the derived encryption key.
+pub const KEY_COMMITMENT_LEN: usize = 32;
+
+/// Encryption key and commitment derived from a master key and nonce.
+/// Equality compares both in constant time.
+#[derive(Clone)]
+pub struct CommittedKey {
+    pub encryption_key: [u8; KEY_COMMITMENT_LEN],
+    pub commitment: [u8; KEY_COMMITMENT_LEN],
+}
+
+impl PartialEq for CommittedKey {
+    fn eq(&self, other: &Self) -> bool {
+        constant_time_eq(&self.encryption_key, &other.encryption_key)
+            & constant_time_eq(&self.commitment, &other.commitment)
+    }
+}
+
+impl Eq for CommittedKey {}
+
+impl fmt::Debug for CommittedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommittedKey")
+            .field("commitment", &self.commitment)
+            .finish_non_exhaustive()
+    }
+}
+
+fn derive(key: &[u8], purpose: &[u8], nonce: &[u8]) -> [u8; KEY_COMMITMENT_LEN] {
+    let mut hasher = BlueHashCore::new_keyed(DigestSize::Bit256, key);
+    hasher.update_framed(COMMITMENT_LABEL);
+    hasher.update(purpose);
+    hasher.update(nonce);
+    let mut out = [0u8; KEY_COMMITMENT_LEN];
+    hasher.finalize_into(&mut out);
+    out
+}
+
+/// Derives the encryption key and key commitment for one message.
+///
+/// # Arguments
+///
+/// * `key` - The master key (e.g. derived from a password).
+/// * `nonce` - The AEAD nonce of the message.
+///
+/// # Returns
+///
+/// The key to pass to the inner AEAD and the commitment to store with the
+/// ciphertext.
+pub fn derive_committed_key(key: &[u8], nonce: &[u8]) -> CommittedKey {
+    CommittedKey {
+        encryption_key: derive(key, b"encrypt", nonce),
+        commitment: derive(key, b"commit", nonce),
+    }
+}
+
+/// Checks a key commitment in constant time.
+pub fn verify_key_commitment(key: &[u8], nonce: &[u8], commitment: &[u8]) -> bool {
+    constant_time_eq(&derive(key, b"commit", nonce), commitment)
+}
+
+/// Error returned by `open_committing`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenError<E> {
+    /// The sealed message is shorter than the commitment.
+    Truncated,
+    /// The key does not match the commitment.
+    KeyMismatch,
+    /// The inner AEAD rejected the ciphertext.
+    Aead(E),
+}
+
+impl<E: fmt::Display> fmt::Display for OpenError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpenError::Truncated => write!(f, "sealed message is truncated"),
+            OpenError::KeyMismatch => write!(f, "key does not match the commitment"),
+            OpenError::Aead(err) => write!(f, "decryption failed: {}", err),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for OpenError<E> {}
+
+/// Encrypts with an inner AEAD and prepends the key commitment.
+///
+/// # Arguments
+///
+/// * `key` - The master key.
+/// * `nonce` - The nonce, passed unchanged to the inner AEAD.
+/// * `seal` - The inner AEAD encryption, called as `seal(encryption_key, nonce)`
+///   and returning the ciphertext with its tag.
+///
+/// # Returns
+///
+/// `commitment || ciphertext`.
+pub fn seal_committing<F>(key: &[u8], nonce: &[u8], seal: F) -> Vec<u8>
+where
+    F: FnOnce(&[u8; KEY_COMMITMENT_LEN], &[u8]) -> Vec<u8>,
+{
+    let committed = derive_committed_key(key, nonce);
+    let ciphertext = seal(&committed.encryption_key, nonce);
+    let mut sealed = Vec::with_capacity(KEY_COMMITMENT_LEN + ciphertext.len());
+    sealed.extend_from_slice(&committed.commitment);
+    sealed.extend_from_slice(&ciphertext);
+    sealed
+}
+
+/// Checks the key commitment and decrypts with the inner AEAD.
+///
+/// # Arguments
+///
+/// * `key` - The master key.
+/// * `nonce` - The nonce used when sealing.
+/// * `sealed` - The output of `seal_committing`.
+/// * `open` - The inner AEAD decryption, called as
+///   `open(encryption_key, nonce, ciphertext)` only if the commitment matches.
+///
+/// # Returns
+///
+/// The plaintext, or an error if the commitment or the inner AEAD fails.
+pub fn open_committing<F, E>(
+    key: &[u8],
+    nonce: &[u8],
+    sealed: &[u8],
+    open: F,
+) -> Result<Vec<u8>, OpenError<E>>
+where
+    F: FnOnce(&[u8; KEY_COMMITMENT_LEN], &[u8], &[u8]) -> Result<Vec<u8>, E>,
+{
+    if sealed.len() < KEY_COMMITMENT_LEN {
+        return Err(OpenError::Truncated);
+    }
+    let (commitment, ciphertext) = sealed.split_at(KEY_COMMITMENT_LEN);
+    let committed = derive_committed_key(key, nonce);
+    if !constant_time_eq(&committed.commitment, commitment) {
+        return Err(OpenError::KeyMismatch);
+    }
+    open(&committed.encryption_key, nonce, ciphertext).map_err(OpenError::Aead)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A toy inner AEAD that XORs with a constant key byte; enough to show the
+    // wrapper passes the derived key through.
+    fn toy_seal(key: &[u8; KEY_COMMITMENT_LEN], plaintext: &[u8]) -> Vec<u8> {
+        plaintext.iter().map(|b| b ^ key[0]).collect()
+    }
+
+    #[test]
+    fn test_round_trip_and_wrong_key() {
+        let nonce = [7u8; 12];
+        let sealed = seal_committing(b"correct horse", &nonce, |k, _| toy_seal(k, b"secret"));
+        assert_eq!(sealed.len(), KEY_COMMITMENT_LEN + 6);
+        assert!(verify_key_commitment(
+            b"correct horse",
+            &nonce,
+            &sealed[..KEY_COMMITMENT_LEN]
+        ));
+
+        let opened = open_committing(b"correct horse", &nonce, &sealed, |k, _, c| {
+            Ok::<_, ()>(toy_seal(k, c))
+        });
+        assert_eq!(opened.unwrap(), b"secret");
+        let derived = derive_committed_key(b"correct horse", &nonce);
+        assert!(derived == derive_committed_key(b"correct horse", &nonce));
+        assert!(derived != derive_committed_key(b"correct horse", &[8u8; 12]));
+
+        // A wrong key is rejected before the inner AEAD is called.
+        let result = open_committing(b"battery staple", &nonce, &sealed, |_, _, _| {
+            panic!("inner AEAD must not run")
+        });
+        assert_eq!(result, Err(OpenError::<()>::KeyMismatch));
+        assert_eq!(
+            open_committing(b"correct horse", &nonce, &sealed[..4], |_, _, _| Err("x")),
+            Err(OpenError::Truncated)
+        );
+    }
+}
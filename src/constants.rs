@@ -1,18 +1,18 @@
-/// Generates a constant value for the round using input data, round number, hash length,
-/// and a secret key. This constant is used for permutations in the hash algorithm.
-/// The generation process is designed to introduce enough randomness and complexity,
-/// making it resistant to both classical and quantum attacks.
+//! Generates a constant value for the round using input data, round number, hash length,
+//! and a secret key. This constant is used for permutations in the hash algorithm.
+//! The generation process is designed to introduce enough randomness and complexity,
+//! making it resistant to both classical and quantum attacks.
 // <Author: BlueOkanna>
 // <Email: blueokanna@gmail.com>
-/// # Arguments
-/// * `round` - The current round number in the hash algorithm.
-/// * `input_data` - The input data used to generate the noise.
-/// * `hash_length` - The length of the hash output, used to adjust the constant.
-///
-/// # Returns
-/// A 64-bit unsigned integer representing the generated constant.
+//! # Arguments
+//! * `round` - The current round number in the hash algorithm.
+//! * `input_data` - The input data used to generate the noise.
+//! * `hash_length` - The length of the hash output, used to adjust the constant.
+//!
+//! # Returns
+//! A 64-bit unsigned integer representing the generated constant.
 
-use crate::noise::generate_lwe_noise;
+use crate::noise::generate_lwe_noise_from_seed;
 
 pub const SBOX: [u8; 256] = [
     0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5,
@@ -49,25 +49,37 @@ pub const SBOX: [u8; 256] = [
     0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
 ];
 
+/// 轮常量与 LWE 噪声共用的素数（黄金分割常数）
+pub(crate) const NOISE_PRIME: u64 = 0x9E3779B97F4A7C15;
+
 /// 根据轮次、输入数据和哈希长度生成混合常量，使用预计算旋转和噪声预处理增强非线性
 pub fn generate_constants<T>(round: usize, input_data: &[T], _hash_length: usize) -> u64
 where
     T: Copy
     + Into<u64>
-    + std::ops::BitXor<Output = T>
-    + std::ops::Shl<u32, Output = T>
-    + std::ops::BitAnd<Output = T>,
+    + core::ops::BitXor<Output = T>
+    + core::ops::Shl<u32, Output = T>
+    + core::ops::BitAnd<Output = T>,
 {
+    let seed_base: u64 = input_data
+        .iter()
+        .fold(0u64, |acc, &x| acc.wrapping_add(x.into()));
+    generate_constants_from_seed(round, seed_base)
+}
+
+/// 与 `generate_constants` 相同，但直接使用已累加的输入种子（输入数据的回绕和）
+pub fn generate_constants_from_seed(round: usize, seed_base: u64) -> u64 {
     fn precompute_rotation(value: u64, shift_left: u32, shift_right: u32) -> (u64, u64) {
         (value.rotate_left(shift_left), value.rotate_right(shift_right))
     }
-    let prime = 0x9E3779B97F4A7C15u64;
-    let round_factor = round.wrapping_add(0xABCDEF1234567890);
+    let prime = NOISE_PRIME;
+    // 以 u64 计算，保证 32 位目标与 64 位目标得到相同的轮常量
+    let round_factor = (round as u64).wrapping_add(0xABCDEF1234567890);
     let extra_prime = 0x7FFFFFFFFFFFFFFFu64;
-    let (round_factor_rot_left, round_factor_rot_right) = precompute_rotation(round_factor as u64, 32, 16);
+    let (round_factor_rot_left, round_factor_rot_right) = precompute_rotation(round_factor, 32, 16);
     let (rotated_prime, _) = precompute_rotation(prime, (round % 64) as u32, 0);
     let (extra_prime_rot_left, _) = precompute_rotation(extra_prime, (round % 32) as u32, 0);
-    let noise = generate_lwe_noise(input_data, round, prime).rotate_left(8);
+    let noise = generate_lwe_noise_from_seed(seed_base, round, prime).rotate_left(8);
     let noise_sub = SBOX[(noise as u8) as usize] as u64;
     let combined = rotated_prime
         .wrapping_mul(round_factor_rot_left)
@@ -0,0 +1,239 @@
+//! COSE_Hash structures carrying BlueHash digests.
+//!
+//! RFC 9054 defines `COSE_Hash = [hashAlg: int / tstr, hashValue: bstr]`,
+//! used e.g. by SUIT manifests and firmware attestation evidence to name the
+//! digest of a payload. This module encodes and decodes that structure with
+//! the provisional COSE algorithm values from [`crate::interop`], using
+//! deterministic CBOR (RFC 8949, section 4.2): shortest-form integers and
+//! definite lengths. Decoding rejects any other encoding.
+
+use crate::interop::{from_cose, identifier};
+use crate::{constant_time_eq, BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+use core::fmt;
+
+const MAJOR_UNSIGNED: u8 = 0;
+const MAJOR_NEGATIVE: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_ARRAY: u8 = 4;
+
+/// Error returned when a COSE_Hash cannot be decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoseError {
+    /// The input ended in the middle of an item.
+    Truncated,
+    /// An item has the wrong CBOR type, or is not in deterministic form.
+    Malformed,
+    /// The algorithm is not a BlueHash COSE algorithm value.
+    UnknownAlgorithm(i64),
+    /// The hash value length does not match the algorithm.
+    InvalidLength(usize),
+    /// Bytes remain after the structure.
+    TrailingBytes,
+}
+
+impl fmt::Display for CoseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoseError::Truncated => write!(f, "truncated CBOR input"),
+            CoseError::Malformed => write!(f, "malformed COSE_Hash structure"),
+            CoseError::UnknownAlgorithm(alg) => write!(f, "unknown COSE algorithm {}", alg),
+            CoseError::InvalidLength(len) => write!(f, "invalid hash value length {}", len),
+            CoseError::TrailingBytes => write!(f, "trailing bytes after COSE_Hash"),
+        }
+    }
+}
+
+impl core::error::Error for CoseError {}
+
+/// A `COSE_Hash` naming a BlueHash digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoseHash {
+    digest_size: DigestSize,
+    value: Vec<u8>,
+}
+
+impl CoseHash {
+    /// Hashes `data` and wraps the digest.
+    pub fn of(digest_size: DigestSize, data: &[u8]) -> Self {
+        let mut hasher = BlueHashCore::new(digest_size);
+        hasher.update(data);
+        Self {
+            digest_size,
+            value: hasher.finalize(),
+        }
+    }
+
+    pub fn digest_size(&self) -> DigestSize {
+        self.digest_size
+    }
+
+    /// The COSE algorithm value written as `hashAlg`.
+    pub fn algorithm(&self) -> i64 {
+        identifier(self.digest_size).cose_hash
+    }
+
+    pub fn value(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// Recomputes the digest of `data` and compares it in constant time.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        constant_time_eq(&Self::of(self.digest_size, data).value, &self.value)
+    }
+
+    /// Encodes the structure as deterministic CBOR.
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.value.len() + 8);
+        write_head(&mut out, MAJOR_ARRAY, 2);
+        let alg = self.algorithm();
+        if alg < 0 {
+            write_head(&mut out, MAJOR_NEGATIVE, (-1 - alg) as u64);
+        } else {
+            write_head(&mut out, MAJOR_UNSIGNED, alg as u64);
+        }
+        write_head(&mut out, MAJOR_BYTES, self.value.len() as u64);
+        out.extend_from_slice(&self.value);
+        out
+    }
+
+    /// Decodes a structure produced by [`CoseHash::to_cbor`].
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The CBOR encoding, with nothing after it.
+    ///
+    /// # Returns
+    ///
+    /// The decoded structure, or an error if the encoding is not deterministic
+    /// CBOR, names another algorithm, or has a digest of the wrong length.
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, CoseError> {
+        let mut input = bytes;
+        if read_head(&mut input)? != (MAJOR_ARRAY, 2) {
+            return Err(CoseError::Malformed);
+        }
+        let alg = match read_head(&mut input)? {
+            (MAJOR_UNSIGNED, n) => i64::try_from(n).map_err(|_| CoseError::Malformed)?,
+            (MAJOR_NEGATIVE, n) => -1 - i64::try_from(n).map_err(|_| CoseError::Malformed)?,
+            _ => return Err(CoseError::Malformed),
+        };
+        let digest_size = from_cose(alg)
+            .filter(|id| id.cose_hash == alg)
+            .ok_or(CoseError::UnknownAlgorithm(alg))?
+            .digest_size;
+        let len = match read_head(&mut input)? {
+            (MAJOR_BYTES, len) => usize::try_from(len).map_err(|_| CoseError::Truncated)?,
+            _ => return Err(CoseError::Malformed),
+        };
+        if input.len() < len {
+            return Err(CoseError::Truncated);
+        }
+        if len != digest_size.digest_length() {
+            return Err(CoseError::InvalidLength(len));
+        }
+        let (value, rest) = input.split_at(len);
+        if !rest.is_empty() {
+            return Err(CoseError::TrailingBytes);
+        }
+        Ok(Self {
+            digest_size,
+            value: value.to_vec(),
+        })
+    }
+}
+
+/// Writes a CBOR item head with the shortest argument encoding.
+fn write_head(out: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    if value < 24 {
+        out.push(major | value as u8);
+    } else if value <= u8::MAX as u64 {
+        out.extend_from_slice(&[major | 24, value as u8]);
+    } else if value <= u16::MAX as u64 {
+        out.push(major | 25);
+        out.extend_from_slice(&(value as u16).to_be_bytes());
+    } else if value <= u32::MAX as u64 {
+        out.push(major | 26);
+        out.extend_from_slice(&(value as u32).to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+/// Reads a CBOR item head, rejecting indefinite and non-shortest encodings.
+fn read_head(input: &mut &[u8]) -> Result<(u8, u64), CoseError> {
+    let (&initial, rest) = input.split_first().ok_or(CoseError::Truncated)?;
+    let (major, info) = (initial >> 5, initial & 0x1f);
+    let width = match info {
+        0..=23 => 0,
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        _ => return Err(CoseError::Malformed),
+    };
+    if rest.len() < width {
+        return Err(CoseError::Truncated);
+    }
+    let (arg, rest) = rest.split_at(width);
+    let value = if width == 0 {
+        info as u64
+    } else {
+        arg.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64)
+    };
+    let minimum = match width {
+        0 => 0,
+        1 => 24,
+        w => 1u64 << (4 * w),
+    };
+    if value < minimum {
+        return Err(CoseError::Malformed);
+    }
+    *input = rest;
+    Ok((major, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cose_hash_round_trip() {
+        let hash = CoseHash::of(DigestSize::Bit256, b"firmware v1.2.3");
+        let encoded = hash.to_cbor();
+        // [-65602, h'...'] : array(2), nint 0x3a 0x0001_0041, bstr(32)
+        assert_eq!(
+            &encoded[..8],
+            &[0x82, 0x3a, 0x00, 0x01, 0x00, 0x41, 0x58, 0x20]
+        );
+        let decoded = CoseHash::from_cbor(&encoded).unwrap();
+        assert_eq!(decoded, hash);
+        assert!(decoded.matches(b"firmware v1.2.3"));
+        assert!(!decoded.matches(b"firmware v1.2.4"));
+    }
+
+    #[test]
+    fn test_from_cbor_rejects_invalid_input() {
+        let encoded = CoseHash::of(DigestSize::Bit128, b"x").to_cbor();
+        assert_eq!(
+            CoseHash::from_cbor(&encoded[..encoded.len() - 1]),
+            Err(CoseError::Truncated)
+        );
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert_eq!(
+            CoseHash::from_cbor(&trailing),
+            Err(CoseError::TrailingBytes)
+        );
+        // SHA-256 (-16) is not a BlueHash algorithm.
+        assert_eq!(
+            CoseHash::from_cbor(&[0x82, 0x2f, 0x40]),
+            Err(CoseError::UnknownAlgorithm(-16))
+        );
+        // Non-shortest array head.
+        let mut long_head = vec![0x98, 0x02];
+        long_head.extend_from_slice(&encoded[1..]);
+        assert_eq!(CoseHash::from_cbor(&long_head), Err(CoseError::Malformed));
+    }
+}
@@ -0,0 +1,223 @@
+//! Count-Min sketches for frequency estimation.
+//!
+//! A Count-Min sketch keeps `depth` rows of `width` counters. Adding a key
+//! increments one counter per row, and the estimate of a key is the smallest
+//! of its counters: never below the true count, and above it by at most
+//! `e / width` times the total with probability `1 - exp(-depth)`. Row `i`
+//! uses its own keyed BlueHash instance:
+//!
+//! ```text
+//! key_i   = BlueHash-256(len(label) || label || seed || i)
+//! index_i = u64_be(BlueHash-128-keyed(key_i, key)[0..8]) mod width
+//! ```
+//!
+//! with `label = "bluehash count-min row v1"` and the seed and row as 8-byte
+//! big-endian integers. Sketches with the same shape and seed merge by
+//! adding counters. `with_heavy_hitters(k)` also tracks the `k` keys with the
+//! largest estimates seen so far, so the most frequent keys can be listed
+//! without keeping every key.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+const ROW_LABEL: &[u8] = b"bluehash count-min row v1";
+
+/// Error returned when merging sketches of different shapes or seeds.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SketchMismatch;
+
+impl fmt::Display for SketchMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sketches use different shapes or seeds")
+    }
+}
+
+impl core::error::Error for SketchMismatch {}
+
+/// Count-Min sketch; see the module docs.
+#[derive(Debug, Clone)]
+pub struct CountMinSketch {
+    width: usize,
+    seed: u64,
+    rows: Vec<BlueHashCore>,
+    counters: Vec<u64>,
+    total: u64,
+    heavy_capacity: usize,
+    heavy: Vec<(Vec<u8>, u64)>,
+}
+
+impl CountMinSketch {
+    /// Creates an empty sketch.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Counters per row; the error bound shrinks as `1 / width`.
+    /// * `depth` - Number of rows; the failure probability is `exp(-depth)`.
+    /// * `seed` - Selects the row hash functions; merged sketches must share it.
+    ///
+    /// # Panics
+    /// Panics if `width` or `depth` is 0.
+    pub fn new(width: usize, depth: usize, seed: u64) -> Self {
+        assert!(width > 0 && depth > 0, "sketch dimensions must be nonzero");
+        let rows = (0..depth as u64)
+            .map(|row| {
+                let mut hasher = BlueHashCore::new(DigestSize::Bit256);
+                hasher.update_framed(ROW_LABEL);
+                hasher.update_u64_be(seed);
+                hasher.update_u64_be(row);
+                BlueHashCore::new_keyed(DigestSize::Bit128, &hasher.finalize())
+            })
+            .collect();
+        Self {
+            width,
+            seed,
+            rows,
+            counters: vec![0; width * depth],
+            total: 0,
+            heavy_capacity: 0,
+            heavy: Vec::new(),
+        }
+    }
+
+    /// Tracks the `k` keys with the largest estimates.
+    pub fn with_heavy_hitters(mut self, k: usize) -> Self {
+        self.heavy_capacity = k;
+        self.heavy.truncate(k);
+        self
+    }
+
+    /// Adds one occurrence of `key`.
+    pub fn insert(&mut self, key: &[u8]) {
+        self.add(key, 1);
+    }
+
+    /// Adds `count` occurrences of `key`.
+    pub fn add(&mut self, key: &[u8], count: u64) {
+        for (row, index) in self.indices(key).into_iter().enumerate() {
+            let counter = &mut self.counters[row * self.width + index];
+            *counter = counter.saturating_add(count);
+        }
+        self.total = self.total.saturating_add(count);
+        if self.heavy_capacity > 0 {
+            let estimate = self.estimate(key);
+            self.track(key, estimate);
+        }
+    }
+
+    /// Returns an upper bound on the number of occurrences of `key`.
+    pub fn estimate(&self, key: &[u8]) -> u64 {
+        self.indices(key)
+            .into_iter()
+            .enumerate()
+            .map(|(row, index)| self.counters[row * self.width + index])
+            .min()
+            .expect("sketch has at least one row")
+    }
+
+    /// Total of all counts added.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Returns the tracked keys and their estimates, largest first.
+    pub fn heavy_hitters(&self) -> Vec<(&[u8], u64)> {
+        let mut hitters: Vec<(&[u8], u64)> = self
+            .heavy
+            .iter()
+            .map(|(key, _)| (key.as_slice(), self.estimate(key)))
+            .collect();
+        hitters.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        hitters
+    }
+
+    /// Adds the counts of another sketch; the tracked keys of both are
+    /// re-estimated and the largest kept.
+    pub fn merge(&mut self, other: &CountMinSketch) -> Result<(), SketchMismatch> {
+        if self.width != other.width
+            || self.rows.len() != other.rows.len()
+            || self.seed != other.seed
+        {
+            return Err(SketchMismatch);
+        }
+        for (mine, theirs) in self.counters.iter_mut().zip(&other.counters) {
+            *mine = mine.saturating_add(*theirs);
+        }
+        self.total = self.total.saturating_add(other.total);
+        let keys: Vec<Vec<u8>> = self
+            .heavy
+            .drain(..)
+            .chain(other.heavy.iter().cloned())
+            .map(|(key, _)| key)
+            .collect();
+        for key in keys {
+            let estimate = self.estimate(&key);
+            self.track(&key, estimate);
+        }
+        Ok(())
+    }
+
+    fn indices(&self, key: &[u8]) -> Vec<usize> {
+        self.rows
+            .iter()
+            .map(|template| {
+                let mut hasher = template.clone();
+                hasher.update(key);
+                let digest = hasher.finalize();
+                let word = u64::from_be_bytes(digest[..8].try_into().expect("BlueHash-128 digest"));
+                (word % self.width as u64) as usize
+            })
+            .collect()
+    }
+
+    fn track(&mut self, key: &[u8], estimate: u64) {
+        if let Some(entry) = self.heavy.iter_mut().find(|(tracked, _)| tracked == key) {
+            entry.1 = estimate;
+            return;
+        }
+        if self.heavy.len() < self.heavy_capacity {
+            self.heavy.push((key.to_vec(), estimate));
+            return;
+        }
+        if let Some(smallest) = self.heavy.iter_mut().min_by_key(|(_, count)| *count) {
+            if estimate > smallest.1 {
+                *smallest = (key.to_vec(), estimate);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn test_estimates_and_heavy_hitters() {
+        let mut left = CountMinSketch::new(512, 4, 1).with_heavy_hitters(3);
+        let mut right = CountMinSketch::new(512, 4, 1).with_heavy_hitters(3);
+        for i in 0..2000 {
+            let sketch = if i % 2 == 0 { &mut left } else { &mut right };
+            sketch.insert(format!("user-{}", i % 500).as_bytes());
+            if i % 10 == 0 {
+                sketch.add(b"hot-a", 20);
+            }
+            if i % 20 == 0 {
+                sketch.add(b"hot-b", 20);
+            }
+        }
+        right.add(b"hot-c", 900);
+        left.merge(&right).unwrap();
+
+        assert_eq!(left.total(), 2000 + 200 * 20 + 100 * 20 + 900);
+        assert!(left.estimate(b"user-7") >= 4);
+        assert!(left.estimate(b"hot-a") >= 4000);
+        let hitters: Vec<&[u8]> = left.heavy_hitters().into_iter().map(|(k, _)| k).collect();
+        assert_eq!(hitters, [&b"hot-a"[..], b"hot-b", b"hot-c"]);
+        assert_eq!(
+            left.merge(&CountMinSketch::new(512, 4, 2)),
+            Err(SketchMismatch)
+        );
+    }
+}
@@ -0,0 +1,404 @@
+//! Append-only transparency log in the style of Certificate Transparency.
+//!
+//! `MerkleLog` keeps the leaf hashes of an RFC 6962 Merkle tree (built on
+//! [`crate::merkle`], with the `0x00` / `0x01` domain-separation prefixes) and
+//! produces audit paths and consistency proofs as specified in RFC 9162,
+//! section 2.1. Verification needs only the proof, the leaf hash and the
+//! signed tree heads, so clients do not have to store the log.
+//!
+//! Proofs serialize to a compact binary form: the two 8-byte big-endian tree
+//! parameters, a one-byte hash count, and the concatenated hashes. The digest
+//! length is implied by the `DigestSize` used to parse them.
+
+use crate::merkle::{hash_leaf, hash_node, split_point};
+use crate::{constant_time_eq, BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Error returned by log operations and proof parsing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LogError {
+    /// The leaf index is not smaller than the tree size.
+    IndexOutOfRange { index: u64, size: u64 },
+    /// The requested tree size is zero or larger than the log.
+    InvalidTreeSize(u64),
+    /// A serialized proof is truncated or has trailing bytes.
+    MalformedProof,
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogError::IndexOutOfRange { index, size } => {
+                write!(
+                    f,
+                    "leaf index {} out of range for tree size {}",
+                    index, size
+                )
+            }
+            LogError::InvalidTreeSize(size) => write!(f, "invalid tree size {}", size),
+            LogError::MalformedProof => write!(f, "malformed proof"),
+        }
+    }
+}
+
+impl core::error::Error for LogError {}
+
+/// Audit path proving that a leaf is included in a tree of a given size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionProof {
+    pub leaf_index: u64,
+    pub tree_size: u64,
+    pub path: Vec<Vec<u8>>,
+}
+
+/// Proof that a tree of `old_size` is a prefix of a tree of `new_size`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsistencyProof {
+    pub old_size: u64,
+    pub new_size: u64,
+    pub path: Vec<Vec<u8>>,
+}
+
+fn encode_proof(a: u64, b: u64, path: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(17 + path.iter().map(Vec::len).sum::<usize>());
+    out.extend_from_slice(&a.to_be_bytes());
+    out.extend_from_slice(&b.to_be_bytes());
+    out.push(path.len() as u8);
+    for hash in path {
+        out.extend_from_slice(hash);
+    }
+    out
+}
+
+fn decode_proof(
+    digest_size: DigestSize,
+    bytes: &[u8],
+) -> Result<(u64, u64, Vec<Vec<u8>>), LogError> {
+    if bytes.len() < 17 {
+        return Err(LogError::MalformedProof);
+    }
+    let a = u64::from_be_bytes(bytes[..8].try_into().unwrap());
+    let b = u64::from_be_bytes(bytes[8..16].try_into().unwrap());
+    let count = bytes[16] as usize;
+    let hashes = &bytes[17..];
+    let digest_length = digest_size.digest_length();
+    if hashes.len() != count * digest_length {
+        return Err(LogError::MalformedProof);
+    }
+    let path = hashes
+        .chunks_exact(digest_length)
+        .map(<[u8]>::to_vec)
+        .collect();
+    Ok((a, b, path))
+}
+
+impl InclusionProof {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode_proof(self.leaf_index, self.tree_size, &self.path)
+    }
+
+    pub fn from_bytes(digest_size: DigestSize, bytes: &[u8]) -> Result<Self, LogError> {
+        let (leaf_index, tree_size, path) = decode_proof(digest_size, bytes)?;
+        Ok(Self {
+            leaf_index,
+            tree_size,
+            path,
+        })
+    }
+
+    /// Checks the proof against a leaf hash and the root of `tree_size` leaves
+    /// (RFC 9162, section 2.1.3.2).
+    pub fn verify(&self, digest_size: DigestSize, leaf_hash: &[u8], root: &[u8]) -> bool {
+        if self.leaf_index >= self.tree_size {
+            return false;
+        }
+        let (mut fnode, mut snode) = (self.leaf_index, self.tree_size - 1);
+        let mut r = leaf_hash.to_vec();
+        for p in &self.path {
+            if snode == 0 {
+                return false;
+            }
+            if fnode & 1 == 1 || fnode == snode {
+                r = hash_node(digest_size, p, &r);
+                while fnode & 1 == 0 && fnode != 0 {
+                    fnode >>= 1;
+                    snode >>= 1;
+                }
+            } else {
+                r = hash_node(digest_size, &r, p);
+            }
+            fnode >>= 1;
+            snode >>= 1;
+        }
+        snode == 0 && constant_time_eq(&r, root)
+    }
+}
+
+impl ConsistencyProof {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode_proof(self.old_size, self.new_size, &self.path)
+    }
+
+    pub fn from_bytes(digest_size: DigestSize, bytes: &[u8]) -> Result<Self, LogError> {
+        let (old_size, new_size, path) = decode_proof(digest_size, bytes)?;
+        Ok(Self {
+            old_size,
+            new_size,
+            path,
+        })
+    }
+
+    /// Checks that `old_root` and `new_root` describe consistent trees
+    /// (RFC 9162, section 2.1.4.2).
+    pub fn verify(&self, digest_size: DigestSize, old_root: &[u8], new_root: &[u8]) -> bool {
+        if self.old_size > self.new_size {
+            return false;
+        }
+        if self.old_size == self.new_size {
+            return self.path.is_empty() && constant_time_eq(old_root, new_root);
+        }
+        if self.old_size == 0 {
+            return self.path.is_empty();
+        }
+        let mut path: Vec<&[u8]> = self.path.iter().map(Vec::as_slice).collect();
+        if self.old_size.is_power_of_two() {
+            path.insert(0, old_root);
+        }
+        let Some((first, rest)) = path.split_first() else {
+            return false;
+        };
+        let (mut fnode, mut snode) = (self.old_size - 1, self.new_size - 1);
+        while fnode & 1 == 1 {
+            fnode >>= 1;
+            snode >>= 1;
+        }
+        let (mut fr, mut sr) = (first.to_vec(), first.to_vec());
+        for c in rest {
+            if snode == 0 {
+                return false;
+            }
+            if fnode & 1 == 1 || fnode == snode {
+                fr = hash_node(digest_size, c, &fr);
+                sr = hash_node(digest_size, c, &sr);
+                while fnode & 1 == 0 && fnode != 0 {
+                    fnode >>= 1;
+                    snode >>= 1;
+                }
+            } else {
+                sr = hash_node(digest_size, &sr, c);
+            }
+            fnode >>= 1;
+            snode >>= 1;
+        }
+        snode == 0 && constant_time_eq(&fr, old_root) & constant_time_eq(&sr, new_root)
+    }
+}
+
+/// Append-only Merkle tree log.
+#[derive(Debug, Clone)]
+pub struct MerkleLog {
+    digest_size: DigestSize,
+    leaf_hashes: Vec<Vec<u8>>,
+}
+
+impl MerkleLog {
+    pub fn new(digest_size: DigestSize) -> Self {
+        Self {
+            digest_size,
+            leaf_hashes: Vec::new(),
+        }
+    }
+
+    /// Number of leaves in the log.
+    pub fn size(&self) -> u64 {
+        self.leaf_hashes.len() as u64
+    }
+
+    /// Appends an entry and returns its leaf index.
+    pub fn append(&mut self, entry: &[u8]) -> u64 {
+        self.leaf_hashes.push(hash_leaf(self.digest_size, entry));
+        self.size() - 1
+    }
+
+    /// Returns the leaf hash of the entry at `index`.
+    pub fn leaf_hash(&self, index: u64) -> Option<&[u8]> {
+        self.leaf_hashes.get(index as usize).map(Vec::as_slice)
+    }
+
+    /// Root hash of the current tree.
+    pub fn root(&self) -> Vec<u8> {
+        self.subtree_root(&self.leaf_hashes)
+    }
+
+    /// Root hash of the tree formed by the first `size` leaves.
+    pub fn root_at(&self, size: u64) -> Result<Vec<u8>, LogError> {
+        if size > self.size() {
+            return Err(LogError::InvalidTreeSize(size));
+        }
+        Ok(self.subtree_root(&self.leaf_hashes[..size as usize]))
+    }
+
+    /// Audit path for leaf `index` in the tree of the first `tree_size` leaves.
+    pub fn inclusion_proof(&self, index: u64, tree_size: u64) -> Result<InclusionProof, LogError> {
+        if tree_size == 0 || tree_size > self.size() {
+            return Err(LogError::InvalidTreeSize(tree_size));
+        }
+        if index >= tree_size {
+            return Err(LogError::IndexOutOfRange {
+                index,
+                size: tree_size,
+            });
+        }
+        let mut path = Vec::new();
+        self.audit_path(
+            index as usize,
+            &self.leaf_hashes[..tree_size as usize],
+            &mut path,
+        );
+        Ok(InclusionProof {
+            leaf_index: index,
+            tree_size,
+            path,
+        })
+    }
+
+    /// Proof that the tree of `old_size` leaves is a prefix of the tree of
+    /// `new_size` leaves.
+    pub fn consistency_proof(
+        &self,
+        old_size: u64,
+        new_size: u64,
+    ) -> Result<ConsistencyProof, LogError> {
+        if new_size > self.size() {
+            return Err(LogError::InvalidTreeSize(new_size));
+        }
+        if old_size > new_size {
+            return Err(LogError::InvalidTreeSize(old_size));
+        }
+        let mut path = Vec::new();
+        if old_size > 0 && old_size < new_size {
+            self.subproof(
+                old_size as usize,
+                &self.leaf_hashes[..new_size as usize],
+                true,
+                &mut path,
+            );
+        }
+        Ok(ConsistencyProof {
+            old_size,
+            new_size,
+            path,
+        })
+    }
+
+    fn subtree_root(&self, leaves: &[Vec<u8>]) -> Vec<u8> {
+        match leaves.len() {
+            0 => BlueHashCore::new(self.digest_size).finalize(),
+            1 => leaves[0].clone(),
+            n => {
+                let k = split_point(n);
+                hash_node(
+                    self.digest_size,
+                    &self.subtree_root(&leaves[..k]),
+                    &self.subtree_root(&leaves[k..]),
+                )
+            }
+        }
+    }
+
+    /// PATH(m, D[n]) from RFC 9162, section 2.1.3.1.
+    fn audit_path(&self, m: usize, leaves: &[Vec<u8>], path: &mut Vec<Vec<u8>>) {
+        let n = leaves.len();
+        if n <= 1 {
+            return;
+        }
+        let k = split_point(n);
+        if m < k {
+            self.audit_path(m, &leaves[..k], path);
+            path.push(self.subtree_root(&leaves[k..]));
+        } else {
+            self.audit_path(m - k, &leaves[k..], path);
+            path.push(self.subtree_root(&leaves[..k]));
+        }
+    }
+
+    /// SUBPROOF(m, D[n], b) from RFC 9162, section 2.1.4.1.
+    fn subproof(&self, m: usize, leaves: &[Vec<u8>], complete: bool, path: &mut Vec<Vec<u8>>) {
+        let n = leaves.len();
+        if m == n {
+            if !complete {
+                path.push(self.subtree_root(leaves));
+            }
+            return;
+        }
+        let k = split_point(n);
+        if m <= k {
+            self.subproof(m, &leaves[..k], complete, path);
+            path.push(self.subtree_root(&leaves[k..]));
+        } else {
+            self.subproof(m - k, &leaves[k..], false, path);
+            path.push(self.subtree_root(&leaves[..k]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: DigestSize = DigestSize::Bit128;
+
+    fn log_of(n: u8) -> MerkleLog {
+        let mut log = MerkleLog::new(SIZE);
+        for i in 0..n {
+            assert_eq!(log.append(&[i]), i as u64);
+        }
+        log
+    }
+
+    #[test]
+    fn test_inclusion_proofs() {
+        let log = log_of(7);
+        let root = log.root();
+        for index in 0..7 {
+            let proof = log.inclusion_proof(index, 7).unwrap();
+            let leaf = log.leaf_hash(index).unwrap();
+            assert!(proof.verify(SIZE, leaf, &root));
+            let parsed = InclusionProof::from_bytes(SIZE, &proof.to_bytes()).unwrap();
+            assert_eq!(parsed, proof);
+            assert!(!proof.verify(SIZE, log.leaf_hash((index + 1) % 7).unwrap(), &root));
+        }
+        let proof = log.inclusion_proof(2, 3).unwrap();
+        assert!(proof.verify(SIZE, log.leaf_hash(2).unwrap(), &log.root_at(3).unwrap()));
+        assert_eq!(
+            log.inclusion_proof(3, 3),
+            Err(LogError::IndexOutOfRange { index: 3, size: 3 })
+        );
+    }
+
+    #[test]
+    fn test_consistency_proofs() {
+        let log = log_of(7);
+        let new_root = log.root();
+        for old_size in 1..=7 {
+            let proof = log.consistency_proof(old_size, 7).unwrap();
+            let old_root = log.root_at(old_size).unwrap();
+            assert!(
+                proof.verify(SIZE, &old_root, &new_root),
+                "old size {}",
+                old_size
+            );
+            if old_size < 7 {
+                assert!(!proof.verify(SIZE, &new_root, &new_root));
+            }
+        }
+        let proof = log.consistency_proof(3, 5).unwrap();
+        let parsed = ConsistencyProof::from_bytes(SIZE, &proof.to_bytes()).unwrap();
+        assert!(parsed.verify(SIZE, &log.root_at(3).unwrap(), &log.root_at(5).unwrap()));
+        assert_eq!(
+            ConsistencyProof::from_bytes(SIZE, &proof.to_bytes()[1..]),
+            Err(LogError::MalformedProof)
+        );
+    }
+}
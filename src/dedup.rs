@@ -0,0 +1,174 @@
+//! Content-defined chunking with an in-memory deduplicating store.
+//!
+//! Backup tools split data at boundaries chosen by the content itself, so an
+//! insertion only changes the chunks around it and the rest deduplicate
+//! against earlier backups. `DedupWriter` uses a gear rolling hash:
+//!
+//! ```text
+//! h = (h << 1) + GEAR[byte]
+//! cut after the byte when len >= DEDUP_MIN_CHUNK_SIZE and h & MASK == 0,
+//! or when len == DEDUP_MAX_CHUNK_SIZE
+//! ```
+//!
+//! where `MASK` has 13 bits set, giving chunks of about 8 KiB on average.
+//! Each chunk is stored once under its BlueHash digest, and every stream
+//! written between two `finish` calls yields a recipe: the list of chunk
+//! digests that `restore` turns back into the stream.
+
+use crate::{BlueHashCore, Digest, DigestOutput, DigestSize};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// Smallest chunk cut by content; only the end of a stream is shorter.
+pub const DEDUP_MIN_CHUNK_SIZE: usize = 2 * 1024;
+/// Largest chunk; longer runs without a boundary are cut here.
+pub const DEDUP_MAX_CHUNK_SIZE: usize = 64 * 1024;
+
+const BOUNDARY_MASK: u64 = (1 << 13) - 1;
+
+/// 256 pseudo-random words from SplitMix64 seeded with 0.
+pub(crate) const GEAR: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut state = 0u64;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+};
+
+/// Writer splitting its input into chunks and storing each unique chunk once.
+#[derive(Debug, Clone)]
+pub struct DedupWriter {
+    digest_size: DigestSize,
+    chunks: BTreeMap<Vec<u8>, Vec<u8>>,
+    pending: Vec<u8>,
+    rolling: u64,
+    recipe: Vec<DigestOutput>,
+}
+
+impl DedupWriter {
+    /// Creates an empty store keyed by digests of the given size.
+    pub fn new(digest_size: DigestSize) -> Self {
+        Self {
+            digest_size,
+            chunks: BTreeMap::new(),
+            pending: Vec::new(),
+            rolling: 0,
+            recipe: Vec::new(),
+        }
+    }
+
+    /// Absorbs the next part of the current stream.
+    pub fn write(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.pending.push(byte);
+            self.rolling = (self.rolling << 1).wrapping_add(GEAR[byte as usize]);
+            let len = self.pending.len();
+            if (len >= DEDUP_MIN_CHUNK_SIZE && self.rolling & BOUNDARY_MASK == 0)
+                || len >= DEDUP_MAX_CHUNK_SIZE
+            {
+                self.cut();
+            }
+        }
+    }
+
+    /// Ends the current stream and returns its recipe; the next `write`
+    /// starts a new stream sharing the same chunk store.
+    pub fn finish(&mut self) -> Vec<DigestOutput> {
+        if !self.pending.is_empty() {
+            self.cut();
+        }
+        core::mem::take(&mut self.recipe)
+    }
+
+    /// Returns the stored chunk with the given digest.
+    pub fn chunk(&self, digest: &DigestOutput) -> Option<&[u8]> {
+        self.chunks.get(digest.as_bytes()).map(Vec::as_slice)
+    }
+
+    /// Reassembles a stream from its recipe, or returns `None` if a chunk is
+    /// not in the store.
+    pub fn restore(&self, recipe: &[DigestOutput]) -> Option<Vec<u8>> {
+        let mut out = Vec::new();
+        for digest in recipe {
+            out.extend_from_slice(self.chunk(digest)?);
+        }
+        Some(out)
+    }
+
+    /// Number of unique chunks stored.
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Total size of the unique chunks stored.
+    pub fn stored_bytes(&self) -> usize {
+        self.chunks.values().map(Vec::len).sum()
+    }
+
+    fn cut(&mut self) {
+        let mut hasher = BlueHashCore::new(self.digest_size);
+        hasher.update(&self.pending);
+        let digest = hasher.finalize();
+        self.recipe
+            .push(DigestOutput::from_bytes(&digest).expect("BlueHash digest length"));
+        let chunk = core::mem::take(&mut self.pending);
+        self.chunks.entry(digest).or_insert(chunk);
+        self.rolling = 0;
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for DedupWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        DedupWriter::write(self, buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_content_is_stored_once() {
+        let mut state = 1u64;
+        let data: Vec<u8> = (0..200_000)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 56) as u8
+            })
+            .collect();
+        let mut edited = data.clone();
+        edited.splice(100_000..100_000, *b"inserted");
+
+        let mut writer = DedupWriter::new(DigestSize::Bit256);
+        writer.write(&data[..1234]);
+        writer.write(&data[1234..]);
+        let first = writer.finish();
+        let after_first = writer.stored_bytes();
+        assert_eq!(after_first, data.len());
+        assert!(first.len() > 1);
+
+        writer.write(&edited);
+        let second = writer.finish();
+        assert_eq!(writer.restore(&first).unwrap(), data);
+        assert_eq!(writer.restore(&second).unwrap(), edited);
+        // Only the chunks around the insertion are new.
+        assert!(writer.stored_bytes() - after_first <= 2 * DEDUP_MAX_CHUNK_SIZE);
+        assert!(writer.stored_bytes() < data.len() + edited.len() / 2);
+        assert!(writer.finish().is_empty());
+    }
+}
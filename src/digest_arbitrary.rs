@@ -0,0 +1,129 @@
+//! `arbitrary` support for fuzzing code that consumes BlueHash types
+//! (`arbitrary` feature).
+//!
+//! Every impl produces a value the rest of the crate accepts, so a fuzz
+//! target spends its time in the caller's code rather than on inputs that
+//! are rejected up front:
+//!
+//! | type | generated values |
+//! |------|------------------|
+//! | `DigestSize`, `SecurityProfile` | any variant |
+//! | `DigestOutput`, `ContentAddress` | a digest of 16, 32 or 64 bytes |
+//! | `BlueHashBuilder` | any size and profile, with or without a key |
+//! | `TotpConfig` | 6 to 9 digits and a nonzero step |
+//! | `ErasureParams` | at least one data chunk |
+
+use crate::cas::ContentAddress;
+use crate::erasure::ErasureParams;
+use crate::otp::TotpConfig;
+use crate::{BlueHashBuilder, DigestOutput, DigestSize, SecurityProfile};
+use alloc::vec;
+use alloc::vec::Vec;
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+fn digest_bytes(u: &mut Unstructured<'_>) -> Result<Vec<u8>> {
+    let mut bytes = vec![0u8; DigestSize::arbitrary(u)?.digest_length()];
+    u.fill_buffer(&mut bytes)?;
+    Ok(bytes)
+}
+
+impl<'a> Arbitrary<'a> for DigestSize {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[DigestSize::Bit128, DigestSize::Bit256, DigestSize::Bit512])?)
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (1, Some(4))
+    }
+}
+
+impl<'a> Arbitrary<'a> for SecurityProfile {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[
+            SecurityProfile::Fast,
+            SecurityProfile::Standard,
+            SecurityProfile::Paranoid,
+        ])?)
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (1, Some(4))
+    }
+}
+
+impl<'a> Arbitrary<'a> for DigestOutput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(DigestOutput::from_bytes(&digest_bytes(u)?).expect("digest length"))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ContentAddress {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(ContentAddress::from_digest(&digest_bytes(u)?).expect("digest length"))
+    }
+}
+
+impl<'a> Arbitrary<'a> for BlueHashBuilder {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let builder =
+            BlueHashBuilder::new(DigestSize::arbitrary(u)?).profile(SecurityProfile::arbitrary(u)?);
+        Ok(match Option::<Vec<u8>>::arbitrary(u)? {
+            Some(key) => builder.key(&key),
+            None => builder,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for TotpConfig {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(TotpConfig {
+            digits: u.int_in_range(6..=9)?,
+            step: u.int_in_range(1..=u64::MAX)?,
+            skew: u64::arbitrary(u)?,
+            t0: u64::arbitrary(u)?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ErasureParams {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(ErasureParams {
+            data_chunks: u.int_in_range(1..=u16::MAX)?,
+            parity_chunks: u16::arbitrary(u)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlueHashCore, Digest};
+
+    #[test]
+    fn test_generated_values_are_accepted() {
+        let data: Vec<u8> = (0..=255u8).cycle().take(4096).collect();
+        let mut u = Unstructured::new(&data);
+        for _ in 0..16 {
+            let digest = DigestOutput::arbitrary(&mut u).unwrap();
+            assert_eq!(digest.to_hex().parse::<DigestOutput>(), Ok(digest));
+            let address = ContentAddress::arbitrary(&mut u).unwrap();
+            assert_eq!(
+                address.digest().len(),
+                address.digest_size().digest_length()
+            );
+
+            let builder = BlueHashBuilder::arbitrary(&mut u).unwrap();
+            let mut hasher = builder.build();
+            hasher.update(b"fuzz");
+            assert!(!hasher.finalize().is_empty());
+
+            let config = TotpConfig::arbitrary(&mut u).unwrap();
+            assert!((6..=9).contains(&config.digits) && config.step > 0);
+            assert!(ErasureParams::arbitrary(&mut u).unwrap().data_chunks > 0);
+        }
+        // An exhausted input still yields valid values.
+        let mut empty = Unstructured::new(&[]);
+        assert!(DigestOutput::arbitrary(&mut empty).is_ok());
+        let _: BlueHashCore = BlueHashBuilder::arbitrary(&mut empty).unwrap().build();
+    }
+}
@@ -0,0 +1,145 @@
+//! `serde` support for digest types (`serde` feature).
+//!
+//! Following the convention of `uuid` and `chrono`, `DigestOutput` and
+//! `cas::ContentAddress` serialize as text for human-readable formats (JSON,
+//! TOML, YAML) and as raw digest bytes for binary formats (bincode, postcard,
+//! CBOR), so compact encodings do not pay for hex:
+//!
+//! | type | human-readable | binary |
+//! |------|----------------|--------|
+//! | `DigestOutput` | `"<hex>"` | the 16, 32 or 64 digest bytes |
+//! | `ContentAddress` | `"bluehashNNN:<hex>"` | the digest bytes (the length implies the size) |
+//!
+//! Deserialization accepts text, bytes and sequences of bytes from any
+//! format, and rejects lengths that are not a BlueHash digest size.
+
+use crate::cas::ContentAddress;
+use crate::DigestOutput;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+use core::str::FromStr;
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+/// The conversions shared by both digest types.
+trait DigestRepr: Sized + fmt::Display + FromStr<Err: fmt::Display> {
+    const EXPECTING: &'static str;
+    fn bytes(&self) -> &[u8];
+    fn from_digest_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+impl DigestRepr for DigestOutput {
+    const EXPECTING: &'static str = "a BlueHash digest as hex or bytes";
+
+    fn bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+
+    fn from_digest_bytes(bytes: &[u8]) -> Option<Self> {
+        DigestOutput::from_bytes(bytes).ok()
+    }
+}
+
+impl DigestRepr for ContentAddress {
+    const EXPECTING: &'static str = "a BlueHash content address or digest bytes";
+
+    fn bytes(&self) -> &[u8] {
+        self.digest()
+    }
+
+    fn from_digest_bytes(bytes: &[u8]) -> Option<Self> {
+        ContentAddress::from_digest(bytes)
+    }
+}
+
+fn serialize<T: DigestRepr, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.collect_str(value)
+    } else {
+        serializer.serialize_bytes(value.bytes())
+    }
+}
+
+struct DigestVisitor<T>(PhantomData<T>);
+
+impl<'de, T: DigestRepr> Visitor<'de> for DigestVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(T::EXPECTING)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<T, E> {
+        T::from_digest_bytes(v).ok_or_else(|| E::invalid_length(v.len(), &self))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(64));
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+        self.visit_bytes(&bytes)
+    }
+}
+
+fn deserialize<'de, T: DigestRepr, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_str(DigestVisitor(PhantomData))
+    } else {
+        deserializer.deserialize_bytes(DigestVisitor(PhantomData))
+    }
+}
+
+macro_rules! impl_serde {
+    ($($ty:ty),*) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serialize(self, serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserialize(deserializer)
+                }
+            }
+        )*
+    };
+}
+
+impl_serde!(DigestOutput, ContentAddress);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DigestSize;
+    use serde::de::value::{BytesDeserializer, Error as ValueError};
+
+    #[test]
+    fn test_human_readable_and_compact_forms() {
+        let address = ContentAddress::of(DigestSize::Bit128, b"serde");
+        let json = serde_json::to_string(&address).unwrap();
+        assert_eq!(json, format!("\"{}\"", address));
+        assert_eq!(
+            serde_json::from_str::<ContentAddress>(&json).unwrap(),
+            address
+        );
+
+        let digest = DigestOutput::from_bytes(address.digest()).unwrap();
+        let json = serde_json::to_string(&digest).unwrap();
+        assert_eq!(json, format!("\"{}\"", digest.to_hex()));
+        assert_eq!(serde_json::from_str::<DigestOutput>(&json).unwrap(), digest);
+
+        // Binary formats hand over the raw bytes.
+        let compact = BytesDeserializer::<ValueError>::new(digest.as_bytes());
+        assert_eq!(DigestOutput::deserialize(compact).unwrap(), digest);
+        let short = BytesDeserializer::<ValueError>::new(&digest.as_bytes()[..15]);
+        assert!(ContentAddress::deserialize(short).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! Fortuna-style entropy accumulator.
+//!
+//! `EntropyPool` follows the design of Fortuna (Ferguson and Schneier,
+//! *Practical Cryptography*, chapter 10) with BlueHash in place of SHA-256
+//! and AES:
+//!
+//! * Events from each source are spread round-robin over 32 pools, each of
+//!   which is a running BlueHash-256 of `source || len(data) || data`.
+//! * The `r`-th reseed drains pool `i` only when `2^i` divides `r`, so even if
+//!   an attacker controls most sources, some pool eventually accumulates
+//!   enough entropy to recover from a state compromise (catastrophic
+//!   reseeding). A reseed happens on a request once pool 0 holds at least
+//!   `MIN_POOL_SIZE` bytes and, with the `std` feature, the minimum reseed
+//!   interval (100 ms by default) has passed.
+//! * Reseeding sets `key = BlueHash-256_keyed(key, label || r || pool digests)`.
+//! * The generator squeezes output from the keyed XOF over a 128-bit counter
+//!   and then replaces its key with `BlueHash-256_keyed(key, rekey label ||
+//!   counter)`, a separate domain from the output stream, so earlier outputs
+//!   cannot be recovered from a later state and the new key is never part of
+//!   any output. Large requests are split so that
+//!   no more than 1 MiB is produced under one key.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+const RESEED_LABEL: &[u8] = b"bluehash fortuna reseed";
+const GENERATE_LABEL: &[u8] = b"bluehash fortuna generate";
+const REKEY_LABEL: &[u8] = b"bluehash fortuna rekey";
+
+/// Number of entropy pools.
+pub const POOL_COUNT: usize = 32;
+
+/// Bytes of events pool 0 must receive before a reseed.
+pub const MIN_POOL_SIZE: usize = 64;
+
+/// Largest amount of output produced under one generator key.
+const MAX_REQUEST: usize = 1 << 20;
+
+const KEY_LEN: usize = 32;
+
+/// Error returned by `EntropyPool::fill_bytes`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EntropyError {
+    /// The pool has not yet gathered enough entropy to seed the generator.
+    NotSeeded,
+}
+
+impl fmt::Display for EntropyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntropyError::NotSeeded => write!(f, "entropy pool is not seeded"),
+        }
+    }
+}
+
+impl core::error::Error for EntropyError {}
+
+/// Entropy accumulator with a reseeding generator.
+#[derive(Clone)]
+pub struct EntropyPool {
+    pools: Vec<BlueHashCore>,
+    pool0_len: usize,
+    next_pool: [u8; 256],
+    key: [u8; KEY_LEN],
+    counter: u128,
+    reseed_count: u64,
+    #[cfg(feature = "std")]
+    last_reseed: Option<Instant>,
+    #[cfg(feature = "std")]
+    min_reseed_interval: Duration,
+}
+
+impl Default for EntropyPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EntropyPool {
+    /// Creates an unseeded pool.
+    pub fn new() -> Self {
+        Self {
+            pools: (0..POOL_COUNT)
+                .map(|_| BlueHashCore::new(DigestSize::Bit256))
+                .collect(),
+            pool0_len: 0,
+            next_pool: [0; 256],
+            key: [0; KEY_LEN],
+            counter: 0,
+            reseed_count: 0,
+            #[cfg(feature = "std")]
+            last_reseed: None,
+            #[cfg(feature = "std")]
+            min_reseed_interval: Duration::from_millis(100),
+        }
+    }
+
+    /// Sets the minimum time between two reseeds.
+    #[cfg(feature = "std")]
+    pub fn with_min_reseed_interval(mut self, interval: Duration) -> Self {
+        self.min_reseed_interval = interval;
+        self
+    }
+
+    /// Adds an entropy event from `source`.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Identifies the entropy source; each source cycles through
+    ///   the pools independently.
+    /// * `data` - The event data, e.g. a timestamp or sensor reading.
+    pub fn add_event(&mut self, source: u8, data: &[u8]) {
+        let pool = self.next_pool[source as usize] as usize;
+        self.next_pool[source as usize] = ((pool + 1) % POOL_COUNT) as u8;
+        let hasher = &mut self.pools[pool];
+        hasher.update(&[source]);
+        hasher.update_framed(data);
+        if pool == 0 {
+            self.pool0_len += data.len() + 9;
+        }
+    }
+
+    /// Number of reseeds performed so far.
+    pub fn reseed_count(&self) -> u64 {
+        self.reseed_count
+    }
+
+    /// Fills `out` with random bytes, reseeding first if enough entropy has
+    /// accumulated.
+    pub fn fill_bytes(&mut self, out: &mut [u8]) -> Result<(), EntropyError> {
+        if self.pool0_len >= MIN_POOL_SIZE && self.reseed_due() {
+            self.reseed();
+        }
+        if self.reseed_count == 0 {
+            return Err(EntropyError::NotSeeded);
+        }
+        for chunk in out.chunks_mut(MAX_REQUEST) {
+            self.generate(chunk);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    fn reseed_due(&self) -> bool {
+        self.last_reseed
+            .is_none_or(|last| last.elapsed() >= self.min_reseed_interval)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn reseed_due(&self) -> bool {
+        true
+    }
+
+    fn reseed(&mut self) {
+        self.reseed_count += 1;
+        let mut hasher = BlueHashCore::new_keyed(DigestSize::Bit256, &self.key);
+        hasher.update_framed(RESEED_LABEL);
+        hasher.update(&self.reseed_count.to_be_bytes());
+        for (i, pool) in self.pools.iter_mut().enumerate() {
+            if !self.reseed_count.is_multiple_of(1u64 << i) {
+                break;
+            }
+            hasher.update(&pool.finalize());
+            pool.reset();
+        }
+        self.pool0_len = 0;
+        hasher.finalize_into(&mut self.key);
+        #[cfg(feature = "std")]
+        {
+            self.last_reseed = Some(Instant::now());
+        }
+    }
+
+    fn generate(&mut self, out: &mut [u8]) {
+        let mut hasher = BlueHashCore::new_keyed(DigestSize::Bit256, &self.key);
+        hasher.update_framed(GENERATE_LABEL);
+        hasher.update(&self.counter.to_be_bytes());
+        hasher.finalize_xof().squeeze(out);
+
+        let mut rekey = BlueHashCore::new_keyed(DigestSize::Bit256, &self.key);
+        rekey.update_framed(REKEY_LABEL);
+        rekey.update(&self.counter.to_be_bytes());
+        rekey.finalize_into(&mut self.key);
+        self.counter = self.counter.wrapping_add(1);
+    }
+}
+
+impl fmt::Debug for EntropyPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EntropyPool")
+            .field("reseed_count", &self.reseed_count)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_pool_digest() -> Vec<u8> {
+        BlueHashCore::new(DigestSize::Bit256).finalize()
+    }
+
+    /// Sends enough events from one source for pool 0 to trigger a reseed.
+    fn add_events(pool: &mut EntropyPool) {
+        for i in 0..4 * POOL_COUNT as u64 {
+            pool.add_event(1, &i.to_be_bytes());
+        }
+    }
+
+    fn seeded_pool() -> EntropyPool {
+        let mut pool = EntropyPool::new().with_min_reseed_interval(Duration::ZERO);
+        add_events(&mut pool);
+        pool
+    }
+
+    #[test]
+    fn test_fill_requires_seed() {
+        let mut pool = EntropyPool::new();
+        let mut out = [0u8; 16];
+        assert_eq!(pool.fill_bytes(&mut out), Err(EntropyError::NotSeeded));
+        pool.add_event(0, &[0u8; 8]);
+        assert_eq!(pool.fill_bytes(&mut out), Err(EntropyError::NotSeeded));
+
+        let mut pool = seeded_pool();
+        pool.fill_bytes(&mut out).unwrap();
+        let mut again = [0u8; 16];
+        pool.fill_bytes(&mut again).unwrap();
+        assert_ne!(out, again);
+        assert_eq!(pool.reseed_count(), 1);
+
+        // The next key is never part of the output handed out.
+        let mut long = [0u8; 4096];
+        pool.fill_bytes(&mut long).unwrap();
+        assert!(long.windows(8).all(|window| window != &pool.key[..8]));
+    }
+
+    #[test]
+    fn test_catastrophic_reseed_schedule() {
+        let mut pool = seeded_pool();
+        let mut out = [0u8; 8];
+        pool.fill_bytes(&mut out).unwrap();
+        // Reseed 1 drains only pool 0, so pool 1 still holds its events.
+        assert_ne!(pool.pools[1].clone().finalize(), empty_pool_digest());
+        add_events(&mut pool);
+        pool.fill_bytes(&mut out).unwrap();
+        assert_eq!(pool.reseed_count(), 2);
+        // Reseed 2 drains pools 0 and 1 but not pool 2.
+        assert_eq!(pool.pools[1].clone().finalize(), empty_pool_digest());
+        assert_ne!(pool.pools[2].clone().finalize(), empty_pool_digest());
+    }
+}
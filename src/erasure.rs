@@ -0,0 +1,243 @@
+//! Commitments to erasure-coded chunks.
+//!
+//! A blob stored with a Reed–Solomon code is split into `data_chunks` data
+//! shards and `parity_chunks` parity shards of equal length, and each shard is
+//! kept by a different storage node. This module commits to the coded shards
+//! (produced by any RS encoder) so that a node can later prove custody of its
+//! shard against one combined commitment:
+//!
+//! ```text
+//! chunk_commitment(i) = BlueHash(len(label) || label || u16(k) || u16(m) || u32(i) || chunk_i)
+//! combined            = MTH(chunk_commitment(0), ..., chunk_commitment(k + m - 1))
+//! ```
+//!
+//! `label` is `"bluehash erasure chunk v1"`, integers are big-endian, and
+//! `MTH` is the RFC 6962 tree of the [`crate::ctlog`] module, whose inclusion
+//! proofs serve as custody proofs. Because the code parameters and the shard
+//! index are inside every leaf, the combined commitment also binds them.
+
+use crate::ctlog::{InclusionProof, MerkleLog};
+use crate::merkle::hash_leaf;
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+use core::fmt;
+
+const CHUNK_LABEL: &[u8] = b"bluehash erasure chunk v1";
+
+/// Reed–Solomon code parameters.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ErasureParams {
+    pub data_chunks: u16,
+    pub parity_chunks: u16,
+}
+
+impl ErasureParams {
+    /// Total number of coded chunks.
+    pub fn total_chunks(&self) -> usize {
+        self.data_chunks as usize + self.parity_chunks as usize
+    }
+}
+
+/// Error returned when committing to coded chunks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ErasureError {
+    /// There must be at least one data chunk.
+    NoDataChunks,
+    /// The number of chunks does not match the code parameters.
+    WrongChunkCount { expected: usize, actual: usize },
+    /// Coded chunks must all have the same length.
+    UnequalChunkLengths,
+}
+
+impl fmt::Display for ErasureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErasureError::NoDataChunks => write!(f, "no data chunks"),
+            ErasureError::WrongChunkCount { expected, actual } => {
+                write!(f, "expected {} chunks, got {}", expected, actual)
+            }
+            ErasureError::UnequalChunkLengths => write!(f, "chunks have different lengths"),
+        }
+    }
+}
+
+impl core::error::Error for ErasureError {}
+
+/// Commits to a single coded chunk.
+///
+/// # Arguments
+///
+/// * `digest_size` - The BlueHash variant to use.
+/// * `params` - The code parameters.
+/// * `index` - The chunk index (data chunks first, then parity chunks).
+/// * `chunk` - The chunk bytes.
+///
+/// # Returns
+///
+/// The chunk commitment.
+pub fn chunk_commitment(
+    digest_size: DigestSize,
+    params: ErasureParams,
+    index: u32,
+    chunk: &[u8],
+) -> Vec<u8> {
+    let mut hasher = BlueHashCore::new(digest_size);
+    hasher.update_framed(CHUNK_LABEL);
+    hasher.update(&params.data_chunks.to_be_bytes());
+    hasher.update(&params.parity_chunks.to_be_bytes());
+    hasher.update(&index.to_be_bytes());
+    hasher.update(chunk);
+    hasher.finalize()
+}
+
+/// Per-chunk commitments and the combined commitment of a coded blob.
+#[derive(Debug, Clone)]
+pub struct ErasureCommitment {
+    params: ErasureParams,
+    chunk_commitments: Vec<Vec<u8>>,
+    tree: MerkleLog,
+}
+
+impl ErasureCommitment {
+    /// Commits to all coded chunks of a blob.
+    ///
+    /// # Arguments
+    ///
+    /// * `digest_size` - The BlueHash variant to use.
+    /// * `params` - The code parameters.
+    /// * `chunks` - The data chunks followed by the parity chunks.
+    ///
+    /// # Returns
+    ///
+    /// The commitments, or an error if the chunks do not fit the parameters.
+    pub fn new(
+        digest_size: DigestSize,
+        params: ErasureParams,
+        chunks: &[&[u8]],
+    ) -> Result<Self, ErasureError> {
+        if params.data_chunks == 0 {
+            return Err(ErasureError::NoDataChunks);
+        }
+        if chunks.len() != params.total_chunks() {
+            return Err(ErasureError::WrongChunkCount {
+                expected: params.total_chunks(),
+                actual: chunks.len(),
+            });
+        }
+        if chunks.iter().any(|chunk| chunk.len() != chunks[0].len()) {
+            return Err(ErasureError::UnequalChunkLengths);
+        }
+        let mut tree = MerkleLog::new(digest_size);
+        let chunk_commitments: Vec<Vec<u8>> = chunks
+            .iter()
+            .zip(0u32..)
+            .map(|(chunk, index)| chunk_commitment(digest_size, params, index, chunk))
+            .collect();
+        for commitment in &chunk_commitments {
+            tree.append(commitment);
+        }
+        Ok(Self {
+            params,
+            chunk_commitments,
+            tree,
+        })
+    }
+
+    pub fn params(&self) -> ErasureParams {
+        self.params
+    }
+
+    /// Commitment to the chunk at `index`.
+    pub fn chunk_commitment(&self, index: u32) -> Option<&[u8]> {
+        self.chunk_commitments
+            .get(index as usize)
+            .map(Vec::as_slice)
+    }
+
+    /// The combined commitment over all chunks.
+    pub fn combined(&self) -> Vec<u8> {
+        self.tree.root()
+    }
+
+    /// Custody proof for the chunk at `index`, or `None` if out of range.
+    pub fn custody_proof(&self, index: u32) -> Option<InclusionProof> {
+        self.tree
+            .inclusion_proof(index as u64, self.tree.size())
+            .ok()
+    }
+}
+
+/// Checks that `chunk` is the coded chunk at `proof.leaf_index` of the blob
+/// with the given combined commitment.
+///
+/// # Arguments
+///
+/// * `digest_size` - The BlueHash variant used to commit.
+/// * `params` - The code parameters.
+/// * `combined` - The combined commitment.
+/// * `chunk` - The chunk bytes held by the prover.
+/// * `proof` - The custody proof for the chunk.
+///
+/// # Returns
+///
+/// `true` if the proof is valid for this chunk.
+pub fn verify_chunk(
+    digest_size: DigestSize,
+    params: ErasureParams,
+    combined: &[u8],
+    chunk: &[u8],
+    proof: &InclusionProof,
+) -> bool {
+    if proof.tree_size != params.total_chunks() as u64 || proof.leaf_index > u32::MAX as u64 {
+        return false;
+    }
+    let commitment = chunk_commitment(digest_size, params, proof.leaf_index as u32, chunk);
+    proof.verify(digest_size, &hash_leaf(digest_size, &commitment), combined)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: DigestSize = DigestSize::Bit128;
+    const PARAMS: ErasureParams = ErasureParams {
+        data_chunks: 3,
+        parity_chunks: 2,
+    };
+
+    #[test]
+    fn test_custody_proofs() {
+        let chunks: [&[u8]; 5] = [b"d0d0", b"d1d1", b"d2d2", b"p0p0", b"p1p1"];
+        let commitment = ErasureCommitment::new(SIZE, PARAMS, &chunks).unwrap();
+        let combined = commitment.combined();
+        for (index, chunk) in (0u32..).zip(chunks) {
+            let proof = commitment.custody_proof(index).unwrap();
+            assert!(verify_chunk(SIZE, PARAMS, &combined, chunk, &proof));
+        }
+        let proof = commitment.custody_proof(3).unwrap();
+        assert!(!verify_chunk(SIZE, PARAMS, &combined, b"d0d0", &proof));
+
+        // The same shards under different code parameters do not verify.
+        let other = ErasureParams {
+            data_chunks: 4,
+            parity_chunks: 1,
+        };
+        assert!(!verify_chunk(SIZE, other, &combined, b"p0p0", &proof));
+        assert!(commitment.custody_proof(5).is_none());
+    }
+
+    #[test]
+    fn test_commitment_errors() {
+        assert_eq!(
+            ErasureCommitment::new(SIZE, PARAMS, &[b"a", b"b"]).unwrap_err(),
+            ErasureError::WrongChunkCount {
+                expected: 5,
+                actual: 2
+            }
+        );
+        assert_eq!(
+            ErasureCommitment::new(SIZE, PARAMS, &[b"a", b"b", b"c", b"d", b"ee"]).unwrap_err(),
+            ErasureError::UnequalChunkLengths
+        );
+    }
+}
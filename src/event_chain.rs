@@ -0,0 +1,353 @@
+//! Hash chains over event logs.
+//!
+//! An event store that keeps a running digest of its log can prove that a
+//! replayed log is the one it wrote: changing, dropping, reordering or
+//! inserting any event changes every later digest. `EventChain` computes
+//!
+//! ```text
+//! h_0 = BlueHash(len(label) || label)
+//! h_i = BlueHash(h_{i-1} || len(event_i) || event_i)
+//! ```
+//!
+//! with `label = "bluehash event chain v1"` and an 8-byte big-endian length.
+//! The current `ChainHead` (event count and `h_n`) is small enough to persist
+//! after every append; `verify_log` replays events from one head and checks
+//! that they lead to another.
+//!
+//! A head kept next to the log proves nothing against someone who can
+//! rewrite both, so audit logs periodically export an anchor: a head sent to
+//! a write-once store, a transparency log or a timestamping service. With
+//! `with_anchor_interval(n)` the chain records its head after every `n`
+//! events for `take_anchors` to collect. An anchor can be signed externally
+//! over `ChainHead::anchor_message`, or sealed with a BlueHash MAC as an
+//! `Anchor`:
+//!
+//! ```text
+//! message = len(anchor_label) || anchor_label || count || h_count
+//! tag     = BlueHash-256-keyed(key, message)
+//! ```
+//!
+//! with `anchor_label = "bluehash event chain anchor v1"`. `verify_segment`
+//! checks both anchors' tags and that the events between them replay from the
+//! first to the second, so a segment of the log can be audited on its own.
+
+use crate::{constant_time_eq, BlueHashCore, Digest, DigestSize, Mac};
+use alloc::vec::Vec;
+use core::fmt;
+
+const EVENT_CHAIN_LABEL: &[u8] = b"bluehash event chain v1";
+const ANCHOR_LABEL: &[u8] = b"bluehash event chain anchor v1";
+
+/// Error returned when a chain head cannot be decoded or a log does not
+/// verify.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainError {
+    /// An encoded head has the wrong length.
+    InvalidHead(usize),
+    /// The replayed log has a different number of events.
+    LengthMismatch { expected: u64, actual: u64 },
+    /// The replayed log leads to a different digest.
+    DigestMismatch,
+    /// An anchor's tag does not verify under the given key.
+    InvalidAnchor,
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::InvalidHead(len) => write!(f, "invalid chain head length {}", len),
+            ChainError::LengthMismatch { expected, actual } => write!(
+                f,
+                "log has {} events but {} were expected",
+                actual, expected
+            ),
+            ChainError::DigestMismatch => write!(f, "log does not match the chain head"),
+            ChainError::InvalidAnchor => write!(f, "anchor tag does not verify"),
+        }
+    }
+}
+
+impl core::error::Error for ChainError {}
+
+/// The state of a chain after some number of events.
+#[derive(Debug, Clone, Eq)]
+pub struct ChainHead {
+    /// Number of events absorbed.
+    pub len: u64,
+    /// `h_len`.
+    pub digest: Vec<u8>,
+}
+
+impl ChainHead {
+    /// Returns `h_0`, the head of an empty chain.
+    pub fn genesis(digest_size: DigestSize) -> Self {
+        let mut hasher = BlueHashCore::new(digest_size);
+        hasher.update_framed(EVENT_CHAIN_LABEL);
+        Self {
+            len: 0,
+            digest: hasher.finalize(),
+        }
+    }
+
+    pub fn digest_size(&self) -> DigestSize {
+        DigestSize::from_digest_length(self.digest.len())
+            .expect("ChainHead always holds a valid digest length")
+    }
+
+    /// Encodes the head as the 8-byte big-endian event count followed by the
+    /// digest.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(8 + self.digest.len());
+        out.extend_from_slice(&self.len.to_be_bytes());
+        out.extend_from_slice(&self.digest);
+        out
+    }
+
+    /// Returns the message an external signer should sign to anchor this
+    /// head; see the module docs.
+    pub fn anchor_message(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(8 + ANCHOR_LABEL.len() + 8 + self.digest.len());
+        message.extend_from_slice(&(ANCHOR_LABEL.len() as u64).to_be_bytes());
+        message.extend_from_slice(ANCHOR_LABEL);
+        message.extend_from_slice(&self.to_bytes());
+        message
+    }
+
+    /// Decodes the output of `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ChainError> {
+        let (len, digest) = bytes
+            .split_first_chunk::<8>()
+            .filter(|(_, digest)| DigestSize::from_digest_length(digest.len()).is_some())
+            .ok_or(ChainError::InvalidHead(bytes.len()))?;
+        Ok(Self {
+            len: u64::from_be_bytes(*len),
+            digest: digest.to_vec(),
+        })
+    }
+}
+
+impl PartialEq for ChainHead {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && constant_time_eq(&self.digest, &other.digest)
+    }
+}
+
+/// A chain head sealed with a MAC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anchor {
+    pub head: ChainHead,
+    /// BlueHash-256-keyed tag over `head.anchor_message()`.
+    pub tag: Vec<u8>,
+}
+
+impl Anchor {
+    /// Seals `head` under `key`.
+    pub fn seal(head: ChainHead, key: &[u8]) -> Self {
+        let mut mac = Mac::new(DigestSize::Bit256, key);
+        mac.update(&head.anchor_message());
+        Self {
+            tag: mac.finalize(),
+            head,
+        }
+    }
+
+    /// Checks the tag in constant time.
+    pub fn verify(&self, key: &[u8]) -> bool {
+        let mut mac = Mac::new(DigestSize::Bit256, key);
+        mac.update(&self.head.anchor_message());
+        mac.verify(&self.tag)
+    }
+}
+
+/// A running hash chain; see the module docs.
+#[derive(Debug, Clone)]
+pub struct EventChain {
+    head: ChainHead,
+    anchor_interval: u64,
+    anchors: Vec<ChainHead>,
+}
+
+impl EventChain {
+    /// Starts an empty chain.
+    pub fn new(digest_size: DigestSize) -> Self {
+        Self::resume(ChainHead::genesis(digest_size))
+    }
+
+    /// Continues a chain from a persisted head.
+    pub fn resume(head: ChainHead) -> Self {
+        Self {
+            head,
+            anchor_interval: 0,
+            anchors: Vec::new(),
+        }
+    }
+
+    /// Records the head after every `interval` events (counted from the
+    /// start of the chain) for `take_anchors`; 0 disables anchoring.
+    pub fn with_anchor_interval(mut self, interval: u64) -> Self {
+        self.anchor_interval = interval;
+        self
+    }
+
+    /// Returns the anchors recorded since the last call.
+    pub fn take_anchors(&mut self) -> Vec<ChainHead> {
+        core::mem::take(&mut self.anchors)
+    }
+
+    /// Appends an event and returns the new head digest.
+    pub fn append(&mut self, event: &[u8]) -> &[u8] {
+        let mut hasher = BlueHashCore::new(self.head.digest_size());
+        hasher.update(&self.head.digest);
+        hasher.update_framed(event);
+        self.head.digest = hasher.finalize();
+        self.head.len += 1;
+        if self.anchor_interval > 0 && self.head.len.is_multiple_of(self.anchor_interval) {
+            self.anchors.push(self.head.clone());
+        }
+        &self.head.digest
+    }
+
+    pub fn head(&self) -> &ChainHead {
+        &self.head
+    }
+
+    pub fn len(&self) -> u64 {
+        self.head.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.len == 0
+    }
+}
+
+/// Replays `events` from `start` and checks that they lead to `end`.
+///
+/// # Arguments
+///
+/// * `start` - The head before the first event, e.g. `ChainHead::genesis`.
+/// * `events` - The events in log order.
+/// * `end` - The head persisted after the last event.
+///
+/// # Returns
+///
+/// `Ok(())` if the log matches, or the first discrepancy found.
+pub fn verify_log<I>(start: &ChainHead, events: I, end: &ChainHead) -> Result<(), ChainError>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let mut chain = EventChain::resume(start.clone());
+    for event in events {
+        chain.append(event.as_ref());
+    }
+    if chain.len() != end.len {
+        return Err(ChainError::LengthMismatch {
+            expected: end.len,
+            actual: chain.len(),
+        });
+    }
+    if chain.head() != end {
+        return Err(ChainError::DigestMismatch);
+    }
+    Ok(())
+}
+
+/// Checks a log segment against the sealed anchors around it.
+///
+/// # Arguments
+///
+/// * `key` - The key the anchors were sealed with.
+/// * `start` - The anchor before the first event of the segment.
+/// * `events` - The events of the segment in log order.
+/// * `end` - The anchor after the last event of the segment.
+///
+/// # Returns
+///
+/// `Ok(())` if both anchors verify and the events lead from one to the
+/// other, or the first problem found.
+pub fn verify_segment<I>(
+    key: &[u8],
+    start: &Anchor,
+    events: I,
+    end: &Anchor,
+) -> Result<(), ChainError>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    if !start.verify(key) || !end.verify(key) {
+        return Err(ChainError::InvalidAnchor);
+    }
+    verify_log(&start.head, events, &end.head)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_detects_tampering() {
+        let events: [&[u8]; 3] = [b"created", b"renamed", b"deleted"];
+        let mut chain = EventChain::new(DigestSize::Bit256);
+        for event in events {
+            chain.append(event);
+        }
+        let saved = chain.head().to_bytes();
+        let end = ChainHead::from_bytes(&saved).unwrap();
+        assert_eq!(end.len, 3);
+
+        let genesis = ChainHead::genesis(DigestSize::Bit256);
+        assert_eq!(verify_log(&genesis, events, &end), Ok(()));
+        assert_eq!(
+            verify_log(&genesis, [events[0], events[2], events[1]], &end),
+            Err(ChainError::DigestMismatch)
+        );
+        assert_eq!(
+            verify_log(&genesis, &events[..2], &end),
+            Err(ChainError::LengthMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
+
+        // Resuming from a persisted head continues the same chain.
+        let mut first = EventChain::new(DigestSize::Bit256);
+        first.append(events[0]);
+        let mut resumed = EventChain::resume(first.head().clone());
+        resumed.append(events[1]);
+        resumed.append(events[2]);
+        assert_eq!(resumed.head(), &end);
+        assert_eq!(
+            ChainHead::from_bytes(&saved[..20]),
+            Err(ChainError::InvalidHead(20))
+        );
+    }
+
+    #[test]
+    fn test_segments_verify_between_anchors() {
+        let events: Vec<Vec<u8>> = (0..10u8).map(|i| alloc::vec![b'e', i]).collect();
+        let mut chain = EventChain::new(DigestSize::Bit256).with_anchor_interval(4);
+        for event in &events {
+            chain.append(event);
+        }
+        let heads = chain.take_anchors();
+        assert_eq!(heads.iter().map(|h| h.len).collect::<Vec<_>>(), [4, 8]);
+        assert!(chain.take_anchors().is_empty());
+
+        let key = b"audit sealing key";
+        let first = Anchor::seal(heads[0].clone(), key);
+        let second = Anchor::seal(heads[1].clone(), key);
+        assert_eq!(verify_segment(key, &first, &events[4..8], &second), Ok(()));
+
+        let mut tampered = events[4..8].to_vec();
+        tampered[2][1] ^= 1;
+        assert_eq!(
+            verify_segment(key, &first, &tampered, &second),
+            Err(ChainError::DigestMismatch)
+        );
+        assert_eq!(
+            verify_segment(b"wrong key", &first, &events[4..8], &second),
+            Err(ChainError::InvalidAnchor)
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! Transcript binding and exporters for TLS-like protocols.
+//!
+//! Modelled on the TLS 1.3 key schedule (RFC 8446, section 7.5): handshake
+//! messages are appended to a `Transcript`, which is finalized into an
+//! `ExporterSecret`. Exporter values are then derived per label, mirroring
+//! `TLS-Exporter(label, context, length)`:
+//!
+//! 1. `label_secret = MAC(secret, "bluehash exporter" || label)`
+//! 2. `output = XOF(MAC(label_secret, "exporter" || H(context) || length))`
+//!
+//! Every field is length-prefixed and the requested length is bound into the
+//! derivation, so a shorter output is never a prefix of a longer one.
+
+use crate::{constant_time_eq, BlueHashCore, Digest, DigestSize};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+const TRANSCRIPT_LABEL: &[u8] = b"bluehash transcript";
+const EXPORTER_LABEL: &[u8] = b"bluehash exporter";
+
+/// Running hash over the messages of a protocol session.
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    hasher: BlueHashCore,
+}
+
+impl Transcript {
+    /// Starts an empty transcript.
+    pub fn new(digest_size: DigestSize) -> Self {
+        let mut hasher = BlueHashCore::new(digest_size);
+        hasher.update_framed(TRANSCRIPT_LABEL);
+        Self { hasher }
+    }
+
+    /// Appends one labeled protocol message.
+    pub fn append(&mut self, label: &[u8], message: &[u8]) {
+        self.hasher.update_framed(label);
+        self.hasher.update_framed(message);
+    }
+
+    /// Returns the transcript hash at this point without ending the transcript.
+    pub fn current_hash(&self) -> Vec<u8> {
+        self.hasher.clone().finalize()
+    }
+
+    /// Ends the transcript and returns the secret exporters are derived from.
+    pub fn finalize(mut self) -> ExporterSecret {
+        let digest_size = self.hasher.digest_size;
+        ExporterSecret::new(digest_size, &self.hasher.finalize())
+    }
+}
+
+/// Master value from which labeled exporter outputs are derived.
+///
+/// Equality compares the secrets in constant time.
+#[derive(Clone)]
+pub struct ExporterSecret {
+    digest_size: DigestSize,
+    secret: Vec<u8>,
+}
+
+impl ExporterSecret {
+    /// Wraps an existing secret, e.g. one agreed by a key exchange.
+    pub fn new(digest_size: DigestSize, secret: &[u8]) -> Self {
+        Self {
+            digest_size,
+            secret: secret.to_vec(),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.secret
+    }
+
+    /// Derives `length` bytes of keying material for `label` and `context`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - Identifies the use of the output, e.g. `b"EXPORTER-my-protocol"`.
+    /// * `context` - Application context; an empty slice is a valid context.
+    /// * `length` - Number of bytes to derive.
+    ///
+    /// # Returns
+    ///
+    /// The exporter output. Different labels, contexts and lengths give
+    /// independent outputs.
+    pub fn derive_exporter(&self, label: &[u8], context: &[u8], length: usize) -> Vec<u8> {
+        let mut hasher = BlueHashCore::new_keyed(self.digest_size, &self.secret);
+        hasher.update_framed(EXPORTER_LABEL);
+        hasher.update_framed(label);
+        let label_secret = hasher.finalize();
+
+        let mut hasher = BlueHashCore::new(self.digest_size);
+        hasher.update(context);
+        let context_hash = hasher.finalize();
+
+        let mut hasher = BlueHashCore::new_keyed(self.digest_size, &label_secret);
+        hasher.update_framed(b"exporter");
+        hasher.update_framed(&context_hash);
+        hasher.update(&(length as u64).to_be_bytes());
+        let mut out = vec![0u8; length];
+        hasher.finalize_xof().squeeze(&mut out);
+        out
+    }
+}
+
+impl PartialEq for ExporterSecret {
+    fn eq(&self, other: &Self) -> bool {
+        self.digest_size == other.digest_size && constant_time_eq(&self.secret, &other.secret)
+    }
+}
+
+impl Eq for ExporterSecret {}
+
+impl fmt::Debug for ExporterSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExporterSecret")
+            .field("digest_size", &self.digest_size)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcript_binds_message_boundaries() {
+        let mut a = Transcript::new(DigestSize::Bit128);
+        a.append(b"ClientHello", b"ab");
+        a.append(b"ServerHello", b"c");
+        let mut b = Transcript::new(DigestSize::Bit128);
+        b.append(b"ClientHello", b"a");
+        b.append(b"ServerHello", b"bc");
+        assert_ne!(a.current_hash(), b.current_hash());
+        assert_ne!(a.finalize(), b.finalize());
+    }
+
+    #[test]
+    fn test_exporters_are_independent() {
+        let secret = ExporterSecret::new(DigestSize::Bit256, &[7u8; 32]);
+        let base = secret.derive_exporter(b"EXPORTER-test", b"ctx", 48);
+        assert_eq!(base.len(), 48);
+        assert_eq!(base, secret.derive_exporter(b"EXPORTER-test", b"ctx", 48));
+        assert_ne!(base, secret.derive_exporter(b"EXPORTER-other", b"ctx", 48));
+        assert_ne!(base, secret.derive_exporter(b"EXPORTER-test", b"", 48));
+        let short = secret.derive_exporter(b"EXPORTER-test", b"ctx", 16);
+        assert_ne!(short[..], base[..16]);
+        // Outputs longer than one XOF block keep changing past the first block.
+        let long = secret.derive_exporter(b"EXPORTER-test", b"ctx", 1024);
+        let blocks: alloc::collections::BTreeSet<&[u8]> = long.chunks_exact(32).collect();
+        assert_eq!(blocks.len(), 32);
+        assert!(!format!("{:?}", secret).contains("secret"));
+    }
+}
@@ -0,0 +1,134 @@
+//! Indexed families of derived outputs.
+//!
+//! Protocols often need many outputs from one key and context, e.g. one
+//! key per chunk or per epoch. Rather than rehashing the key and context for
+//! every index, `DeriveMany` absorbs them once and derives member `i` from a
+//! copy of that mid-state:
+//!
+//! ```text
+//! prefix = BlueHash-256-keyed(key, len(label) || label || len(context) || context
+//!                                  || each_len)
+//! out_i  = XOF(prefix || i)[..each_len]
+//! ```
+//!
+//! with `label = "bluehash derive many v1"` and 8-byte big-endian integers.
+//! Binding `each_len` makes families of different output lengths independent,
+//! so a shorter member is never a prefix of a longer one.
+
+use crate::{BlueHashCore, DigestSize};
+use alloc::vec;
+use alloc::vec::Vec;
+
+const DERIVE_MANY_LABEL: &[u8] = b"bluehash derive many v1";
+
+/// Derives `count` outputs of `each_len` bytes from `key` and `context`.
+///
+/// # Arguments
+///
+/// * `key` - The secret the family is derived from.
+/// * `context` - Distinguishes families derived from the same key.
+/// * `count` - Number of outputs, indexed `0..count`.
+/// * `each_len` - Length of every output in bytes.
+///
+/// # Returns
+///
+/// The outputs in index order; the same as the first `count` items of
+/// `DeriveMany::new(key, context, each_len)`.
+pub fn derive_many(key: &[u8], context: &[u8], count: usize, each_len: usize) -> Vec<Vec<u8>> {
+    DeriveMany::new(key, context, each_len)
+        .take(count)
+        .collect()
+}
+
+/// An endless iterator over an indexed output family; see the module docs.
+#[derive(Debug, Clone)]
+pub struct DeriveMany {
+    prefix: BlueHashCore,
+    each_len: usize,
+    next_index: u64,
+}
+
+impl DeriveMany {
+    /// Absorbs `key` and `context` once; members are derived from this state.
+    pub fn new(key: &[u8], context: &[u8], each_len: usize) -> Self {
+        let mut prefix = BlueHashCore::new_keyed(DigestSize::Bit256, key);
+        prefix.update_framed(DERIVE_MANY_LABEL);
+        prefix.update_framed(context);
+        prefix.update_u64_be(each_len as u64);
+        Self {
+            prefix,
+            each_len,
+            next_index: 0,
+        }
+    }
+
+    /// Writes member `index` into `out`, which must be `each_len` bytes long.
+    /// Never allocates.
+    ///
+    /// # Panics
+    /// Panics if `out.len()` differs from `each_len`.
+    pub fn derive_into(&self, index: u64, out: &mut [u8]) {
+        assert_eq!(out.len(), self.each_len, "output must be each_len bytes");
+        let mut hasher = self.prefix.clone();
+        hasher.update_u64_be(index);
+        hasher.finalize_xof().squeeze(out);
+    }
+
+    /// Returns member `index`, independently of the iterator position.
+    pub fn derive(&self, index: u64) -> Vec<u8> {
+        let mut out = vec![0u8; self.each_len];
+        self.derive_into(index, &mut out);
+        out
+    }
+}
+
+impl Iterator for DeriveMany {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Vec<u8>> {
+        let out = self.derive(self.next_index);
+        self.next_index = self.next_index.wrapping_add(1);
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_many_matches_rehashing() {
+        let outputs = derive_many(b"key", b"chunk keys", 3, 20);
+        assert_eq!(outputs.len(), 3);
+        for (index, output) in outputs.iter().enumerate() {
+            let mut hasher = BlueHashCore::new_keyed(DigestSize::Bit256, b"key");
+            hasher.update_framed(DERIVE_MANY_LABEL);
+            hasher.update_framed(b"chunk keys");
+            hasher.update_u64_be(20);
+            hasher.update_u64_be(index as u64);
+            let mut expected = [0u8; 20];
+            hasher.finalize_xof().squeeze(&mut expected);
+            assert_eq!(output[..], expected);
+        }
+        assert_ne!(outputs[0], outputs[1]);
+
+        let family = DeriveMany::new(b"key", b"chunk keys", 20);
+        assert_eq!(family.derive(2), outputs[2]);
+        assert_ne!(
+            derive_many(b"key", b"chunk keys", 1, 32)[0][..20],
+            outputs[0][..]
+        );
+    }
+
+    #[test]
+    fn test_members_longer_than_one_block() {
+        // 400 bytes spans four 128-byte XOF blocks of BlueHash-256.
+        let outputs = derive_many(b"key", b"long members", 4, 400);
+        let mut words = alloc::collections::BTreeSet::new();
+        for output in &outputs {
+            assert_eq!(output.len(), 400);
+            words.extend(output.chunks_exact(16));
+        }
+        assert_eq!(words.len(), 4 * 400 / 16);
+    }
+}
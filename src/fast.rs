@@ -0,0 +1,116 @@
+//! Reduced-round checksum mode.
+//!
+//! `BlueHashFast` runs the BlueHash-128 construction with `FAST_ROUNDS`
+//! permutation rounds instead of 112 and outputs 64 or 128 bits:
+//!
+//! ```text
+//! checksum = BlueHash128_truncated[FAST_ROUNDS](len(label) || label || data)
+//! ```
+//!
+//! with `label = "bluehash fast v1"`.
+//!
+//! # Security level
+//!
+//! This is an integrity checksum, a replacement for CRC-32C or xxHash, and
+//! **not** a cryptographic hash. It detects accidental corruption (a random
+//! change goes unnoticed with probability about 2^-64 or 2^-128), but with so
+//! few rounds no collision or preimage resistance is claimed: an adversary who
+//! can choose the data should be assumed able to forge matching checksums.
+//! Use `BlueHashCore` or `BlueHashChecksum` with a seed where data may be
+//! hostile.
+
+use crate::checksum::{Checksum128, Checksum64};
+use crate::{BlueHashCore, Digest, DigestSize};
+
+const FAST_LABEL: &[u8] = b"bluehash fast v1";
+
+/// Number of permutation rounds used by `BlueHashFast`.
+pub const FAST_ROUNDS: usize = 8;
+
+/// Streaming reduced-round checksum; see the module documentation for its
+/// (non-cryptographic) security level.
+#[derive(Debug, Clone)]
+pub struct BlueHashFast {
+    hasher: BlueHashCore,
+}
+
+impl BlueHashFast {
+    /// Creates a new checksum state.
+    pub fn new() -> Self {
+        let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+        hasher.round_count = FAST_ROUNDS;
+        hasher.update_framed(FAST_LABEL);
+        Self { hasher }
+    }
+
+    /// Absorbs data.
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+    }
+
+    /// Returns the 64-bit checksum of the data absorbed so far.
+    pub fn finalize64(&self) -> u64 {
+        let mut out = [0u8; 8];
+        self.hasher.clone().finalize_truncated_into(&mut out);
+        u64::from_be_bytes(out)
+    }
+
+    /// Returns the 128-bit checksum of the data absorbed so far.
+    pub fn finalize128(&self) -> u128 {
+        let mut out = [0u8; 16];
+        self.hasher.clone().finalize_truncated_into(&mut out);
+        u128::from_be_bytes(out)
+    }
+
+    /// One-shot 64-bit checksum.
+    pub fn hash64(data: &[u8]) -> u64 {
+        Self::new().checksum64(data)
+    }
+
+    /// One-shot 128-bit checksum.
+    pub fn hash128(data: &[u8]) -> u128 {
+        Self::new().checksum128(data)
+    }
+}
+
+impl Default for BlueHashFast {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Checksums `data` appended to the state absorbed so far.
+impl Checksum64 for BlueHashFast {
+    fn checksum64(&self, data: &[u8]) -> u64 {
+        let mut state = self.clone();
+        state.update(data);
+        state.finalize64()
+    }
+}
+
+/// Checksums `data` appended to the state absorbed so far.
+impl Checksum128 for BlueHashFast {
+    fn checksum128(&self, data: &[u8]) -> u128 {
+        let mut state = self.clone();
+        state.update(data);
+        state.finalize128()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum::BlueHashChecksum;
+
+    #[test]
+    fn test_fast_checksum() {
+        let mut streaming = BlueHashFast::new();
+        streaming.update(b"log ");
+        streaming.update(b"record");
+        let sum = streaming.finalize64();
+        assert_eq!(sum, BlueHashFast::hash64(b"log record"));
+        assert_ne!(sum, BlueHashFast::hash64(b"log recorc"));
+        assert_ne!(sum, BlueHashChecksum::new().checksum64(b"log record"));
+        assert_ne!((BlueHashFast::hash128(b"log record") >> 64) as u64, sum);
+    }
+}
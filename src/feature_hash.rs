@@ -0,0 +1,118 @@
+//! Feature hashing (the hashing trick) for machine-learning pipelines.
+//!
+//! Instead of keeping a vocabulary, a vectorizer maps every token straight
+//! to one of `dims` slots and adds a signed count there; the random sign
+//! makes collisions cancel out in expectation. Slots and signs come from
+//! keyed BlueHash-128:
+//!
+//! ```text
+//! h     = BlueHash-128-keyed(key, len(label) || label || feature)
+//! index = u64_be(h[0..8]) mod dims
+//! sign  = +1 if h[8] is even, -1 otherwise
+//! ```
+//!
+//! with `label = "bluehash feature hash v1"`. A secret key keeps an adversary
+//! from crafting tokens that collide on purpose; `feature_hash` uses the
+//! empty key for pipelines that only need stable slots.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec;
+use alloc::vec::Vec;
+
+const FEATURE_LABEL: &[u8] = b"bluehash feature hash v1";
+
+/// Returns the slot and sign of `feature` in a `dims`-dimensional vector,
+/// using the empty key.
+///
+/// # Panics
+/// Panics if `dims` is 0.
+pub fn feature_hash(feature: &[u8], dims: usize) -> (usize, f32) {
+    HashingVectorizer::new(&[], dims).feature_hash(feature)
+}
+
+/// Folds token streams into fixed-size vectors; see the module docs.
+#[derive(Debug, Clone)]
+pub struct HashingVectorizer {
+    template: BlueHashCore,
+    dims: usize,
+}
+
+impl HashingVectorizer {
+    /// Creates a vectorizer producing `dims`-dimensional vectors.
+    ///
+    /// # Panics
+    /// Panics if `dims` is 0.
+    pub fn new(key: &[u8], dims: usize) -> Self {
+        assert!(dims > 0, "feature vectors need at least one dimension");
+        let mut template = BlueHashCore::new_keyed(DigestSize::Bit128, key);
+        template.update_framed(FEATURE_LABEL);
+        Self { template, dims }
+    }
+
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    /// Returns the slot and sign of `feature`.
+    pub fn feature_hash(&self, feature: &[u8]) -> (usize, f32) {
+        let mut hasher = self.template.clone();
+        hasher.update(feature);
+        let digest = hasher.finalize();
+        let slot = u64::from_be_bytes(digest[..8].try_into().expect("BlueHash-128 digest"));
+        let sign = if digest[8] & 1 == 0 { 1.0 } else { -1.0 };
+        ((slot % self.dims as u64) as usize, sign)
+    }
+
+    /// Adds `weight` times the signed unit vector of `feature` to `vector`.
+    ///
+    /// # Panics
+    /// Panics if `vector` is shorter than `dims`.
+    pub fn accumulate(&self, feature: &[u8], weight: f32, vector: &mut [f32]) {
+        let (index, sign) = self.feature_hash(feature);
+        vector[index] += sign * weight;
+    }
+
+    /// Returns the vector of a token stream, each token counting once.
+    pub fn transform<I>(&self, tokens: I) -> Vec<f32>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut vector = vec![0.0; self.dims];
+        for token in tokens {
+            self.accumulate(token.as_ref(), 1.0, &mut vector);
+        }
+        vector
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_fold_into_fixed_dims() {
+        let (index, sign) = feature_hash(b"token", 16);
+        assert!(index < 16);
+        assert_eq!(feature_hash(b"token", 16), (index, sign));
+
+        let vectorizer = HashingVectorizer::new(b"", 16);
+        assert_eq!(vectorizer.feature_hash(b"token"), (index, sign));
+        let vector = vectorizer.transform(["be", "be"]);
+        let (be, be_sign) = vectorizer.feature_hash(b"be");
+        assert_eq!(vector[be], 2.0 * be_sign);
+        assert_eq!(vector.iter().filter(|v| **v != 0.0).count(), 1);
+        let sentence = vectorizer.transform("to be or not to be".split(' '));
+        assert_eq!(sentence.len(), 16);
+        assert!(sentence.iter().map(|v| v.abs()).sum::<f32>() <= 6.0);
+
+        // Different keys place the same features differently.
+        let keyed = HashingVectorizer::new(b"secret", 1 << 20);
+        let unkeyed = HashingVectorizer::new(b"", 1 << 20);
+        let moved = ["a", "b", "c", "d"]
+            .iter()
+            .filter(|t| keyed.feature_hash(t.as_bytes()) != unkeyed.feature_hash(t.as_bytes()))
+            .count();
+        assert!(moved >= 3);
+    }
+}
@@ -0,0 +1,151 @@
+//! ssh/TLS-style fingerprint formatting for BlueHash digests.
+//!
+//! Fingerprints mirror OpenSSH conventions with a BlueHash prefix:
+//! `BLUEHASH256:<unpadded base64>` (like `SHA256:...`), the legacy
+//! colon-separated hex form, and the "drunken bishop" randomart picture.
+
+use crate::utils::to_base64;
+use crate::{DigestOutput, DigestSize};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const ART_WIDTH: usize = 17;
+const ART_HEIGHT: usize = 9;
+const ART_SYMBOLS: &[u8] = b" .o+=*BOX@%&#/^";
+
+/// Returns the fingerprint prefix for a digest size, e.g. `BLUEHASH256`.
+pub fn fingerprint_prefix(digest_size: DigestSize) -> &'static str {
+    match digest_size {
+        DigestSize::Bit128 => "BLUEHASH128",
+        DigestSize::Bit256 => "BLUEHASH256",
+        DigestSize::Bit512 => "BLUEHASH512",
+    }
+}
+
+impl DigestOutput {
+    /// 生成 OpenSSH 风格指纹：`BLUEHASH256:<无填充 base64>`
+    pub fn fingerprint(&self) -> String {
+        format!(
+            "{}:{}",
+            fingerprint_prefix(self.digest_size()),
+            to_base64(self.as_bytes(), false)
+        )
+    }
+
+    /// 生成冒号分隔的 16 进制指纹：`BLUEHASH256:ab:cd:...`
+    pub fn fingerprint_hex(&self) -> String {
+        let hex = self.to_hex();
+        let pairs: Vec<&str> = (0..hex.len()).step_by(2).map(|i| &hex[i..i + 2]).collect();
+        format!(
+            "{}:{}",
+            fingerprint_prefix(self.digest_size()),
+            pairs.join(":")
+        )
+    }
+
+    /// 生成 "drunken bishop" 随机图（与 ssh-keygen -lv 的布局一致）
+    ///
+    /// `title` 显示在上边框中（例如密钥类型），下边框显示摘要算法。
+    pub fn randomart(&self, title: &str) -> String {
+        let mut field = [[0usize; ART_WIDTH]; ART_HEIGHT];
+        let (start_x, start_y) = (ART_WIDTH / 2, ART_HEIGHT / 2);
+        let (mut x, mut y) = (start_x, start_y);
+        for &byte in self.as_bytes() {
+            let mut bits = byte;
+            for _ in 0..4 {
+                x = if bits & 1 == 1 {
+                    (x + 1).min(ART_WIDTH - 1)
+                } else {
+                    x.saturating_sub(1)
+                };
+                y = if bits & 2 == 2 {
+                    (y + 1).min(ART_HEIGHT - 1)
+                } else {
+                    y.saturating_sub(1)
+                };
+                field[y][x] += 1;
+                bits >>= 2;
+            }
+        }
+
+        let mut art = frame_line(title);
+        art.push('\n');
+        for (row_index, row) in field.iter().enumerate() {
+            art.push('|');
+            for (col_index, &count) in row.iter().enumerate() {
+                let symbol = if (col_index, row_index) == (start_x, start_y) {
+                    'S'
+                } else if (col_index, row_index) == (x, y) {
+                    'E'
+                } else {
+                    ART_SYMBOLS[count.min(ART_SYMBOLS.len() - 1)] as char
+                };
+                art.push(symbol);
+            }
+            art.push_str("|\n");
+        }
+        art.push_str(&frame_line(fingerprint_prefix(self.digest_size())));
+        art
+    }
+}
+
+/// Builds a border line with `label` centered in brackets.
+fn frame_line(label: &str) -> String {
+    let label = if label.is_empty() {
+        String::new()
+    } else {
+        let max = ART_WIDTH - 2;
+        let truncated: String = label.chars().take(max).collect();
+        format!("[{}]", truncated)
+    };
+    let dashes = ART_WIDTH - label.chars().count();
+    let left = dashes / 2;
+    format!(
+        "+{}{}{}+",
+        "-".repeat(left),
+        label,
+        "-".repeat(dashes - left)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::to_base64;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(to_base64(b"foobar", true), "Zm9vYmFy");
+        assert_eq!(to_base64(b"fooba", true), "Zm9vYmE=");
+        assert_eq!(to_base64(b"foob", false), "Zm9vYg");
+    }
+
+    #[test]
+    fn test_fingerprint_formats() {
+        let digest = DigestOutput::from_hex(&"0f".repeat(32)).unwrap();
+        assert_eq!(
+            digest.fingerprint(),
+            format!("BLUEHASH256:{}", to_base64(&[0x0f; 32], false))
+        );
+        assert!(digest.fingerprint_hex().starts_with("BLUEHASH256:0f:0f:"));
+        assert_eq!(
+            digest.fingerprint_hex().len(),
+            "BLUEHASH256:".len() + 32 * 3 - 1
+        );
+    }
+
+    #[test]
+    fn test_randomart_layout() {
+        let digest = DigestOutput::from_hex(&"a5".repeat(32)).unwrap();
+        let art = digest.randomart("ED25519 256");
+        let lines: Vec<&str> = art.lines().collect();
+        assert_eq!(lines.len(), ART_HEIGHT + 2);
+        assert!(lines
+            .iter()
+            .all(|line| line.chars().count() == ART_WIDTH + 2));
+        assert_eq!(lines[0], "+--[ED25519 256]--+");
+        assert_eq!(lines[ART_HEIGHT + 1], "+--[BLUEHASH256]--+");
+        assert!(art.contains('S'));
+    }
+}
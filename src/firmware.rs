@@ -0,0 +1,164 @@
+//! Firmware image verification for bootloaders.
+//!
+//! A manifest lists the digest of every section of an image (bootloader
+//! stage, application, data) and the digest of the whole image. Manifests
+//! borrow their digests, so they can be parsed in place from flash, and
+//! verification hashes into stack buffers with `finalize_into`, so it needs
+//! neither `std` nor a heap allocator.
+//!
+//! All digests are compared in constant time and every section is checked
+//! even after a mismatch, so timing does not reveal which section failed.
+
+use crate::{constant_time_eq, BlueHashCore, Digest, DigestSize};
+use core::fmt;
+
+/// Largest digest length of any `DigestSize`.
+const MAX_DIGEST_LEN: usize = 64;
+
+/// One section of an image: a byte range and its expected digest.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImageSection<'a> {
+    pub offset: usize,
+    pub len: usize,
+    pub digest: &'a [u8],
+}
+
+/// Expected digests of an image and its sections.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImageManifest<'a> {
+    pub digest_size: DigestSize,
+    pub sections: &'a [ImageSection<'a>],
+    /// Digest of the complete image.
+    pub image_digest: &'a [u8],
+}
+
+/// Reason an image was rejected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImageError {
+    /// A digest in the manifest has the wrong length for its digest size.
+    InvalidDigestLength,
+    /// Section `index` extends past the end of the image.
+    SectionOutOfBounds(usize),
+    /// At least one digest does not match.
+    DigestMismatch,
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::InvalidDigestLength => write!(f, "manifest digest has the wrong length"),
+            ImageError::SectionOutOfBounds(i) => write!(f, "section {} is out of bounds", i),
+            ImageError::DigestMismatch => write!(f, "image digest mismatch"),
+        }
+    }
+}
+
+impl core::error::Error for ImageError {}
+
+/// Checks an image against its manifest.
+///
+/// # Arguments
+///
+/// * `image` - The complete image as stored in flash.
+/// * `manifest` - The expected section and image digests.
+///
+/// # Returns
+///
+/// `Ok(())` if every section digest and the image digest match. Structural
+/// problems in the manifest are reported before any hashing takes place.
+pub fn verify_image(image: &[u8], manifest: &ImageManifest) -> Result<(), ImageError> {
+    let digest_length = manifest.digest_size.digest_length();
+    if manifest.image_digest.len() != digest_length {
+        return Err(ImageError::InvalidDigestLength);
+    }
+    for (i, section) in manifest.sections.iter().enumerate() {
+        if section.digest.len() != digest_length {
+            return Err(ImageError::InvalidDigestLength);
+        }
+        match section.offset.checked_add(section.len) {
+            Some(end) if end <= image.len() => {}
+            _ => return Err(ImageError::SectionOutOfBounds(i)),
+        }
+    }
+
+    let mut buffer = [0u8; MAX_DIGEST_LEN];
+    let computed = &mut buffer[..digest_length];
+    let mut matches = true;
+    for section in manifest.sections {
+        digest_into(
+            manifest.digest_size,
+            &image[section.offset..section.offset + section.len],
+            computed,
+        );
+        matches &= constant_time_eq(computed, section.digest);
+    }
+    digest_into(manifest.digest_size, image, computed);
+    matches &= constant_time_eq(computed, manifest.image_digest);
+
+    if matches {
+        Ok(())
+    } else {
+        Err(ImageError::DigestMismatch)
+    }
+}
+
+fn digest_into(digest_size: DigestSize, data: &[u8], out: &mut [u8]) {
+    let mut hasher = BlueHashCore::new(digest_size);
+    hasher.update(data);
+    hasher.finalize_into(out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(data: &[u8]) -> Vec<u8> {
+        let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    #[test]
+    fn test_verify_image() {
+        let mut image = [0u8; 64];
+        image[..16].copy_from_slice(b"stage one loader");
+        image[16..].fill(0xa5);
+        let (boot, app, whole) = (digest(&image[..16]), digest(&image[16..]), digest(&image));
+        let sections = [
+            ImageSection {
+                offset: 0,
+                len: 16,
+                digest: &boot,
+            },
+            ImageSection {
+                offset: 16,
+                len: 48,
+                digest: &app,
+            },
+        ];
+        let manifest = ImageManifest {
+            digest_size: DigestSize::Bit128,
+            sections: &sections,
+            image_digest: &whole,
+        };
+        assert_eq!(verify_image(&image, &manifest), Ok(()));
+
+        image[40] ^= 1;
+        assert_eq!(
+            verify_image(&image, &manifest),
+            Err(ImageError::DigestMismatch)
+        );
+        assert_eq!(
+            verify_image(&image[..32], &manifest),
+            Err(ImageError::SectionOutOfBounds(1))
+        );
+        let truncated = ImageManifest {
+            image_digest: &whole[..8],
+            ..manifest
+        };
+        assert_eq!(
+            verify_image(&image, &truncated),
+            Err(ImageError::InvalidDigestLength)
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Length-prefixed framing for hashing structured records.
+//!
+//! Plain concatenation is ambiguous: `("ab", "c")` and `("a", "bc")` feed the
+//! same bytes to the hasher. Framing every field with its length makes the
+//! encoding injective, so different field splits always hash differently.
+//! `update_u64_be`, `update_u64_le`, `update_u128` and `update_str` fix the
+//! encoding of common primitives (the same as `BlueHashable`, plus an explicit
+//! little-endian form), so protocol code need not convert them by hand.
+//!
+//! Injectivity is checked twice: `test_small_splits_are_injective`
+//! enumerates every split of short inputs, and the Kani harness in `proofs`
+//! (`cargo kani --harness framing_is_injective`) proves it for all records of
+//! up to three fields within its bounds. Both drive `write_framed`, the exact
+//! byte stream `hash_fields` absorbs.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+
+impl BlueHashCore {
+    /// 吸收一个带长度前缀的字段：先写入 8 字节大端长度，再写入字段内容
+    pub fn update_framed(&mut self, field: &[u8]) {
+        self.update(&(field.len() as u64).to_be_bytes());
+        self.update(field);
+    }
+
+    /// 吸收 8 字节大端序整数（与 `BlueHashable for u64` 的编码一致）
+    pub fn update_u64_be(&mut self, value: u64) {
+        self.update(&value.to_be_bytes());
+    }
+
+    /// 吸收 8 字节小端序整数，用于与小端序协议字段逐字节对齐
+    pub fn update_u64_le(&mut self, value: u64) {
+        self.update(&value.to_le_bytes());
+    }
+
+    /// 吸收 16 字节大端序整数（与 `BlueHashable for u128` 的编码一致）
+    pub fn update_u128(&mut self, value: u128) {
+        self.update(&value.to_be_bytes());
+    }
+
+    /// 吸收带长度前缀的 UTF-8 字符串，等同于 `update_framed(value.as_bytes())`
+    /// （与 `BlueHashable for str` 的编码一致），相邻字符串不会因拼接产生歧义
+    pub fn update_str(&mut self, value: &str) {
+        self.update_framed(value.as_bytes());
+    }
+}
+
+/// Hashes a list of fields, framing each one with its length.
+///
+/// # Arguments
+///
+/// * `digest_size` - The BlueHash variant to use.
+/// * `fields` - The fields of the record, in order.
+///
+/// # Returns
+///
+/// The digest of the framed record.
+pub fn hash_fields(digest_size: DigestSize, fields: &[&[u8]]) -> Vec<u8> {
+    let mut hasher = BlueHashCore::new(digest_size);
+    write_framed(fields, |bytes| hasher.update(bytes));
+    hasher.finalize()
+}
+
+/// Passes the framed encoding of `fields` to `sink`, piece by piece: the field
+/// count, then each field as `update_framed` writes it.
+fn write_framed<F: FnMut(&[u8])>(fields: &[&[u8]], mut sink: F) {
+    sink(&(fields.len() as u64).to_be_bytes());
+    for field in fields {
+        sink(&(field.len() as u64).to_be_bytes());
+        sink(field);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_splits_do_not_collide() {
+        let a = hash_fields(DigestSize::Bit128, &[b"ab", b"c"]);
+        let b = hash_fields(DigestSize::Bit128, &[b"a", b"bc"]);
+        let c = hash_fields(DigestSize::Bit128, &[b"abc"]);
+        assert_ne!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(b, c);
+    }
+
+    fn encode(fields: &[&[u8]]) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_framed(fields, |bytes| out.extend_from_slice(bytes));
+        out
+    }
+
+    #[test]
+    fn test_small_splits_are_injective() {
+        // 所有长度不超过 4 的输入按任意位置切分（每个位置切或不切），编码两两不同
+        let data = b"abcd";
+        let mut encodings = Vec::new();
+        for len in 0..=data.len() {
+            for cuts in 0u32..(1 << len.saturating_sub(1)) {
+                let mut fields: Vec<&[u8]> = Vec::new();
+                let mut start = 0;
+                for i in 1..len {
+                    if cuts & (1 << (i - 1)) != 0 {
+                        fields.push(&data[start..i]);
+                        start = i;
+                    }
+                }
+                if len > 0 {
+                    fields.push(&data[start..len]);
+                }
+                encodings.push(encode(&fields));
+            }
+        }
+        encodings.push(encode(&[b""]));
+        encodings.push(encode(&[b"", b""]));
+        let count = encodings.len();
+        encodings.sort();
+        encodings.dedup();
+        assert_eq!(encodings.len(), count);
+    }
+
+    #[test]
+    fn test_primitive_updates_match_canonical_encoding() {
+        use crate::BlueHashable;
+
+        let mut typed = BlueHashCore::new(DigestSize::Bit128);
+        typed.update_u64_be(7);
+        typed.update_u128(u128::MAX - 1);
+        typed.update_str("ab");
+        typed.update_u64_le(0x0102);
+        let mut manual = BlueHashCore::new(DigestSize::Bit128);
+        7u64.hash_into(&mut manual);
+        (u128::MAX - 1).hash_into(&mut manual);
+        "ab".hash_into(&mut manual);
+        manual.update(&[2, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(typed.finalize(), manual.finalize());
+    }
+
+    #[test]
+    fn test_hash_fields_matches_update_framed() {
+        let mut hasher = BlueHashCore::new(DigestSize::Bit256);
+        hasher.update(&2u64.to_be_bytes());
+        hasher.update_framed(b"name");
+        hasher.update_framed(b"value");
+        assert_eq!(
+            hasher.finalize(),
+            hash_fields(DigestSize::Bit256, &[b"name", b"value"])
+        );
+    }
+}
+
+#[cfg(kani)]
+mod proofs {
+    use super::*;
+
+    const MAX_FIELD_LEN: usize = 2;
+
+    fn any_record<'a>(storage: &'a [[u8; MAX_FIELD_LEN]; 3]) -> Vec<&'a [u8]> {
+        let count: usize = kani::any();
+        kani::assume(count <= 3);
+        (0..count)
+            .map(|i| {
+                let len: usize = kani::any();
+                kani::assume(len <= MAX_FIELD_LEN);
+                &storage[i][..len]
+            })
+            .collect()
+    }
+
+    fn encode(fields: &[&[u8]]) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_framed(fields, |bytes| out.extend_from_slice(bytes));
+        out
+    }
+
+    /// Two records with the same framed encoding have the same fields.
+    #[kani::proof]
+    #[kani::unwind(40)]
+    fn framing_is_injective() {
+        let storage_a: [[u8; MAX_FIELD_LEN]; 3] = kani::any();
+        let storage_b: [[u8; MAX_FIELD_LEN]; 3] = kani::any();
+        let a = any_record(&storage_a);
+        let b = any_record(&storage_b);
+        if encode(&a) == encode(&b) {
+            assert_eq!(a, b);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Consistent sampling keyed by BlueHash.
+//!
+//! Telemetry pipelines sample by ID rather than at random, so every service
+//! keeps or drops the same traces and users. Each key is mapped to a number
+//! uniform in `[0, 1)`:
+//!
+//! ```text
+//! u(key) = (u64_be(BlueHash-128(len(label) || label || key)[0..8]) >> 11) / 2^53
+//! ```
+//!
+//! with `label = "bluehash sampling v1"`. `sample_if` keeps a key when
+//! `u(key) < rate`, so raising the rate only ever adds keys. `BottomK` keeps
+//! the `k` keys of smallest rank: `u(key)` for a uniform sample of distinct
+//! keys (sketches of different streams merge into the sample of their
+//! union), or `u(key) / weight` for priority sampling, where heavier keys are
+//! more likely to be kept.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+
+const SAMPLING_LABEL: &[u8] = b"bluehash sampling v1";
+
+/// Returns `u(key)`, uniform in `[0, 1)`; see the module docs.
+pub fn sampling_value(key: &[u8]) -> f64 {
+    let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+    hasher.update_framed(SAMPLING_LABEL);
+    hasher.update(key);
+    let digest = hasher.finalize();
+    let word = u64::from_be_bytes(digest[..8].try_into().expect("BlueHash-128 digest"));
+    (word >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Decides whether `key` is in the sample.
+///
+/// # Arguments
+///
+/// * `key` - The ID to sample on, e.g. a trace or user ID.
+/// * `rate` - Fraction of keys to keep; 0.0 keeps none and 1.0 keeps all.
+///
+/// # Returns
+///
+/// `true` if `key` is kept; the answer is the same for the same key and rate
+/// everywhere.
+pub fn sample_if(key: &[u8], rate: f64) -> bool {
+    sampling_value(key) < rate
+}
+
+/// Bottom-k sample of distinct keys; see the module docs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BottomK {
+    k: usize,
+    entries: Vec<(f64, Vec<u8>)>,
+}
+
+impl BottomK {
+    /// Creates an empty sample holding at most `k` keys.
+    ///
+    /// # Panics
+    /// Panics if `k` is 0.
+    pub fn new(k: usize) -> Self {
+        assert!(k > 0, "a bottom-k sample needs k >= 1");
+        Self {
+            k,
+            entries: Vec::with_capacity(k),
+        }
+    }
+
+    /// Offers a key with rank `u(key)`.
+    pub fn insert(&mut self, key: &[u8]) {
+        self.insert_ranked(sampling_value(key), key);
+    }
+
+    /// Offers a key with rank `u(key) / weight`.
+    ///
+    /// # Panics
+    /// Panics unless `weight` is positive and finite.
+    pub fn insert_weighted(&mut self, key: &[u8], weight: f64) {
+        assert!(
+            weight > 0.0 && weight.is_finite(),
+            "sampling weights must be positive and finite"
+        );
+        self.insert_ranked(sampling_value(key) / weight, key);
+    }
+
+    /// Adds the keys of another sample, giving the sample of both streams.
+    pub fn merge(&mut self, other: &BottomK) {
+        for (rank, key) in &other.entries {
+            self.insert_ranked(*rank, key);
+        }
+    }
+
+    /// Returns the sampled keys in order of rank.
+    pub fn keys(&self) -> impl Iterator<Item = &[u8]> {
+        self.entries.iter().map(|(_, key)| key.as_slice())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Estimates the number of distinct keys offered with `insert`: exact
+    /// until the sample is full, `(k - 1) / (k-th smallest rank)` after.
+    pub fn estimate_distinct(&self) -> f64 {
+        match self.entries.last() {
+            Some((rank, _)) if self.entries.len() == self.k && *rank > 0.0 => {
+                (self.k - 1) as f64 / rank
+            }
+            _ => self.entries.len() as f64,
+        }
+    }
+
+    fn insert_ranked(&mut self, rank: f64, key: &[u8]) {
+        if self.entries.len() == self.k
+            && self.entries.last().is_some_and(|(last, _)| rank >= *last)
+        {
+            return;
+        }
+        if self.entries.iter().any(|(_, existing)| existing == key) {
+            return;
+        }
+        let at = self.entries.partition_point(|(r, _)| *r <= rank);
+        self.entries.insert(at, (rank, key.to_vec()));
+        self.entries.truncate(self.k);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn test_sampling_is_consistent() {
+        let ids: Vec<Vec<u8>> = (0..4000)
+            .map(|i| format!("trace-{}", i).into_bytes())
+            .collect();
+        let kept = ids.iter().filter(|id| sample_if(id, 0.25)).count();
+        assert!((800..1200).contains(&kept), "{}", kept);
+        // Raising the rate only adds keys.
+        assert!(ids
+            .iter()
+            .filter(|id| sample_if(id, 0.25))
+            .all(|id| sample_if(id, 0.5)));
+        assert!(!sample_if(b"trace-0", 0.0) && sample_if(b"trace-0", 1.0));
+
+        let mut left = BottomK::new(64);
+        let mut right = BottomK::new(64);
+        let mut all = BottomK::new(64);
+        for (i, id) in ids.iter().enumerate() {
+            if i % 2 == 0 {
+                left.insert(id);
+            } else {
+                right.insert(id);
+            }
+            all.insert(id);
+            all.insert(id);
+        }
+        left.merge(&right);
+        assert_eq!(left, all);
+        let estimate = all.estimate_distinct();
+        assert!((2800.0..5200.0).contains(&estimate), "{}", estimate);
+
+        let mut weighted = BottomK::new(1);
+        weighted.insert_weighted(b"light", 1.0);
+        weighted.insert_weighted(b"heavy", 1e12);
+        assert_eq!(weighted.keys().next(), Some(&b"heavy"[..]));
+    }
+}
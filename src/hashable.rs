@@ -0,0 +1,161 @@
+//! Canonical, injective hashing of Rust values.
+//!
+//! Implementations encode integers as fixed-width big-endian values and frame
+//! every variable-length value (strings, slices, vectors) with its length, so
+//! two different values of the same type never produce the same byte stream.
+//! With the `derive` feature, `#[derive(BlueHashable)]` extends this to
+//! structs and enums, binding type, field and variant names as well.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Types that can be fed to a BlueHash hasher in a canonical form.
+pub trait BlueHashable {
+    /// Feeds the canonical encoding of `self` into `hasher`.
+    fn hash_into(&self, hasher: &mut BlueHashCore);
+
+    /// Hashes `self` on its own and returns the digest.
+    fn blue_hash(&self, digest_size: DigestSize) -> Vec<u8> {
+        let mut hasher = BlueHashCore::new(digest_size);
+        self.hash_into(&mut hasher);
+        hasher.finalize()
+    }
+}
+
+macro_rules! impl_int {
+    ($($ty:ty),*) => {
+        $(
+            impl BlueHashable for $ty {
+                fn hash_into(&self, hasher: &mut BlueHashCore) {
+                    hasher.update(&self.to_be_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl BlueHashable for usize {
+    fn hash_into(&self, hasher: &mut BlueHashCore) {
+        (*self as u64).hash_into(hasher);
+    }
+}
+
+impl BlueHashable for isize {
+    fn hash_into(&self, hasher: &mut BlueHashCore) {
+        (*self as i64).hash_into(hasher);
+    }
+}
+
+impl BlueHashable for bool {
+    fn hash_into(&self, hasher: &mut BlueHashCore) {
+        hasher.update(&[*self as u8]);
+    }
+}
+
+impl BlueHashable for char {
+    fn hash_into(&self, hasher: &mut BlueHashCore) {
+        (*self as u32).hash_into(hasher);
+    }
+}
+
+impl BlueHashable for str {
+    fn hash_into(&self, hasher: &mut BlueHashCore) {
+        hasher.update_framed(self.as_bytes());
+    }
+}
+
+impl BlueHashable for String {
+    fn hash_into(&self, hasher: &mut BlueHashCore) {
+        self.as_str().hash_into(hasher);
+    }
+}
+
+impl<T: BlueHashable> BlueHashable for [T] {
+    fn hash_into(&self, hasher: &mut BlueHashCore) {
+        (self.len() as u64).hash_into(hasher);
+        for item in self {
+            item.hash_into(hasher);
+        }
+    }
+}
+
+impl<T: BlueHashable, const N: usize> BlueHashable for [T; N] {
+    fn hash_into(&self, hasher: &mut BlueHashCore) {
+        self[..].hash_into(hasher);
+    }
+}
+
+impl<T: BlueHashable> BlueHashable for Vec<T> {
+    fn hash_into(&self, hasher: &mut BlueHashCore) {
+        self[..].hash_into(hasher);
+    }
+}
+
+impl<T: BlueHashable> BlueHashable for Option<T> {
+    fn hash_into(&self, hasher: &mut BlueHashCore) {
+        match self {
+            None => hasher.update(&[0]),
+            Some(value) => {
+                hasher.update(&[1]);
+                value.hash_into(hasher);
+            }
+        }
+    }
+}
+
+impl<T: BlueHashable + ?Sized> BlueHashable for &T {
+    fn hash_into(&self, hasher: &mut BlueHashCore) {
+        (**self).hash_into(hasher);
+    }
+}
+
+impl<T: BlueHashable + ?Sized> BlueHashable for Box<T> {
+    fn hash_into(&self, hasher: &mut BlueHashCore) {
+        (**self).hash_into(hasher);
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: BlueHashable),+> BlueHashable for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn hash_into(&self, hasher: &mut BlueHashCore) {
+                let ($($name,)+) = self;
+                $($name.hash_into(hasher);)+
+            }
+        }
+    };
+}
+
+impl_tuple!(A);
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strings_are_framed() {
+        let a = ("ab", "c").blue_hash(DigestSize::Bit128);
+        let b = ("a", "bc").blue_hash(DigestSize::Bit128);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_option_and_vec_encoding() {
+        assert_ne!(
+            Some(0u8).blue_hash(DigestSize::Bit128),
+            None::<u8>.blue_hash(DigestSize::Bit128)
+        );
+        assert_ne!(
+            vec![vec![1u8], vec![]].blue_hash(DigestSize::Bit128),
+            vec![vec![], vec![1u8]].blue_hash(DigestSize::Bit128)
+        );
+    }
+}
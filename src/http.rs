@@ -0,0 +1,196 @@
+//! ETags and HTTP integrity fields.
+//!
+//! Web middleware needs two representations of a body digest:
+//!
+//! * a strong `ETag`, the BlueHash-256 digest in unpadded base64url between
+//!   double quotes, e.g. `"3q2-7w..."`;
+//! * an RFC 9530 `Content-Digest` or `Repr-Digest` field value, a structured
+//!   field dictionary member whose value is a byte sequence:
+//!   `bluehash-256=:<padded base64>:`.
+//!
+//! `bluehash-256` is not in the IANA Hash Algorithms for HTTP Digest Fields
+//! registry; it is a provisional token, so peers must agree on it out of band
+//! and fields carrying it should sit next to a registered algorithm rather
+//! than replace it. `verify_field` ignores the other members of a field, so
+//! it accepts values like `sha-256=:...:, bluehash-256=:...:`.
+//!
+//! A streamed response only knows its digest after the last chunk, so it
+//! goes in a trailer. `DigestedBody` wraps any iterator of body chunks,
+//! passes them through unchanged and hashes them on the way; once the body
+//! is exhausted, `digest` returns the value for the `Content-Digest` trailer.
+//!
+//! There is no `tower` feature and no ready-made middleware layer. Wrapping
+//! an `http_body::Body` means projecting a pinned field, which takes
+//! `unsafe` code or a macro expanding to it, and the crate forbids both.
+//! Middleware for a particular framework (a tower `Layer` mapping the
+//! response body, say) adapts its body type to an iterator for
+//! `DigestedBody`, or feeds a `BlueHashCore` itself and calls
+//! `HttpDigest::from_hasher`.
+
+use crate::utils::{to_base64, to_base64url};
+use crate::{constant_time_eq, BlueHashCore, Digest, DigestSize};
+use alloc::format;
+use alloc::string::String;
+
+/// The provisional RFC 9530 algorithm token for BlueHash-256.
+pub const HTTP_DIGEST_ALGORITHM: &str = "bluehash-256";
+
+/// The BlueHash-256 digest of an HTTP body.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HttpDigest {
+    digest: [u8; 32],
+}
+
+impl HttpDigest {
+    /// Hashes a complete body.
+    pub fn of(body: &[u8]) -> Self {
+        let mut hasher = BlueHashCore::new(DigestSize::Bit256);
+        hasher.update(body);
+        Self::from_hasher(hasher)
+    }
+
+    /// Finishes a BlueHash-256 hasher that has absorbed a streamed body.
+    ///
+    /// # Panics
+    /// Panics if `hasher` is not a BlueHash-256 hasher.
+    pub fn from_hasher(mut hasher: BlueHashCore) -> Self {
+        let mut digest = [0u8; 32];
+        hasher.finalize_into(&mut digest);
+        Self { digest }
+    }
+
+    /// Hashes everything `reader` yields.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: std::io::Read>(reader: R) -> std::io::Result<Self> {
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(&crate::reader::hash_reader(reader, DigestSize::Bit256)?);
+        Ok(Self { digest })
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.digest
+    }
+
+    /// Returns the strong `ETag` value, quotes included.
+    pub fn etag(&self) -> String {
+        format!("\"{}\"", to_base64url(&self.digest))
+    }
+
+    /// Returns the `Content-Digest` / `Repr-Digest` field value.
+    pub fn field_value(&self) -> String {
+        format!(
+            "{}=:{}:",
+            HTTP_DIGEST_ALGORITHM,
+            to_base64(&self.digest, true)
+        )
+    }
+
+    /// Checks a received `Content-Digest` / `Repr-Digest` field value.
+    ///
+    /// # Returns
+    ///
+    /// `Some(true)` if its `bluehash-256` member matches, `Some(false)` if it
+    /// does not, and `None` if the field has no `bluehash-256` member.
+    pub fn verify_field(&self, field: &str) -> Option<bool> {
+        let expected = to_base64(&self.digest, true);
+        field
+            .split(',')
+            .filter_map(|member| member.trim().split_once('='))
+            .find(|(name, _)| *name == HTTP_DIGEST_ALGORITHM)
+            .map(|(_, value)| {
+                let value = value.split(';').next().unwrap_or("");
+                value
+                    .strip_prefix(':')
+                    .and_then(|value| value.strip_suffix(':'))
+                    .is_some_and(|value| constant_time_eq(value.as_bytes(), expected.as_bytes()))
+            })
+    }
+}
+
+/// Iterator adapter hashing body chunks as they pass through.
+#[derive(Debug, Clone)]
+pub struct DigestedBody<I> {
+    chunks: I,
+    hasher: BlueHashCore,
+    digest: Option<HttpDigest>,
+}
+
+impl<I> DigestedBody<I> {
+    pub fn new(chunks: I) -> Self {
+        Self {
+            chunks,
+            hasher: BlueHashCore::new(DigestSize::Bit256),
+            digest: None,
+        }
+    }
+
+    /// Returns the digest of the whole body once the last chunk has been
+    /// yielded, or `None` while chunks remain.
+    pub fn digest(&self) -> Option<HttpDigest> {
+        self.digest
+    }
+}
+
+impl<I: Iterator> Iterator for DigestedBody<I>
+where
+    I::Item: AsRef<[u8]>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        match self.chunks.next() {
+            Some(chunk) => {
+                self.hasher.update(chunk.as_ref());
+                Some(chunk)
+            }
+            None => {
+                if self.digest.is_none() {
+                    self.digest = Some(HttpDigest::from_hasher(self.hasher.clone()));
+                }
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_values() {
+        let digest = HttpDigest::of(b"{\"hello\": \"world\"}");
+        let etag = digest.etag();
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+        assert_eq!(etag.len(), 43 + 2);
+
+        let field = digest.field_value();
+        assert!(field.starts_with("bluehash-256=:") && field.ends_with("=:"));
+        assert_eq!(digest.verify_field(&field), Some(true));
+        let combined = format!(
+            "sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:, {}",
+            field
+        );
+        assert_eq!(digest.verify_field(&combined), Some(true));
+        assert_eq!(HttpDigest::of(b"other").verify_field(&field), Some(false));
+        assert_eq!(digest.verify_field("sha-256=:AAAA:"), None);
+
+        let mut streamed = BlueHashCore::new(DigestSize::Bit256);
+        streamed.update(b"{\"hello\": ");
+        streamed.update(b"\"world\"}");
+        assert_eq!(HttpDigest::from_hasher(streamed), digest);
+    }
+
+    #[test]
+    fn test_digested_body_passes_chunks_through() {
+        let chunks = [&b"{\"hello\": "[..], b"\"world\"}"];
+        let mut body = DigestedBody::new(chunks.iter());
+        assert_eq!(body.next(), Some(&chunks[0]));
+        assert_eq!(body.digest(), None);
+        assert_eq!(body.by_ref().count(), 1);
+        assert_eq!(
+            body.digest(),
+            Some(HttpDigest::of(b"{\"hello\": \"world\"}"))
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! Deterministic, name-based UUIDs derived with BlueHash.
+//!
+//! Works like RFC 4122 version 5 (hash of namespace UUID followed by the
+//! name) but uses BlueHash-128 instead of SHA-1. The result is tagged as a
+//! version 8 ("custom") UUID with the RFC 4122 variant bits, so it stays
+//! distinguishable from genuine v5 identifiers.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use uuid::Uuid;
+
+/// Derives a deterministic UUID from a namespace and a name.
+///
+/// # Arguments
+///
+/// * `namespace` - The namespace UUID, e.g. `Uuid::NAMESPACE_DNS`.
+/// * `name` - The name within that namespace.
+///
+/// # Returns
+///
+/// A version 8, RFC 4122 variant UUID; equal inputs always give equal UUIDs.
+pub fn uuid_from(namespace: &Uuid, name: &[u8]) -> Uuid {
+    let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+    hasher.update(namespace.as_bytes());
+    hasher.update(name);
+    let digest = hasher.finalize();
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest);
+    bytes[6] = (bytes[6] & 0x0f) | 0x80;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    Uuid::from_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uuid_is_deterministic_v8() {
+        let a = uuid_from(&Uuid::NAMESPACE_DNS, b"example.com");
+        let b = uuid_from(&Uuid::NAMESPACE_DNS, b"example.com");
+        let c = uuid_from(&Uuid::NAMESPACE_URL, b"example.com");
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a.get_version_num(), 8);
+        assert_eq!(a.get_variant(), uuid::Variant::RFC4122);
+    }
+}
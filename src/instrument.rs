@@ -0,0 +1,124 @@
+//! Instrumentation of whole-input hashing (`tracing` feature).
+//!
+//! `hash_reader`, `hash_file` and their pipelined and sparse variants run
+//! inside a `bluehash.hash` span at DEBUG level, so services can find their
+//! hashing hotspots without wrapping every call site:
+//!
+//! | field | value |
+//! |-------|-------|
+//! | `operation` | `"hash_reader"` (also for `hash_file`), `"hash_reader_pipelined"` or `"hash_file_sparse"` |
+//! | `digest_size` | the BlueHash variant |
+//! | `bytes` | bytes absorbed, recorded when the operation ends |
+//! | `elapsed_us` | wall-clock time in microseconds, recorded at the end |
+//!
+//! An I/O error ends the operation with a WARN event inside the span. The
+//! crate has no runtime self-test (the known-answer tests live in
+//! `BlueHash_portability`), so there are no self-test events. Without the
+//! feature the wrapper only creates the hasher and finalizes it.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+use std::io;
+
+/// Runs `absorb` on a fresh hasher inside the span described in the module
+/// docs and returns the digest.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn hash_with<F>(
+    operation: &'static str,
+    digest_size: DigestSize,
+    absorb: F,
+) -> io::Result<Vec<u8>>
+where
+    F: FnOnce(&mut BlueHashCore) -> io::Result<()>,
+{
+    let mut hasher = BlueHashCore::new(digest_size);
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::debug_span!(
+            "bluehash.hash",
+            operation,
+            digest_size = ?digest_size,
+            bytes = tracing::field::Empty,
+            elapsed_us = tracing::field::Empty,
+        );
+        let _entered = span.enter();
+        let start = std::time::Instant::now();
+        let result = absorb(&mut hasher);
+        span.record("bytes", hasher.total_len as u64);
+        span.record("elapsed_us", start.elapsed().as_micros() as u64);
+        if let Err(err) = &result {
+            tracing::warn!(error = %err, "hashing failed");
+        }
+        result?;
+    }
+    #[cfg(not(feature = "tracing"))]
+    absorb(&mut hasher)?;
+    Ok(hasher.finalize())
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+    use crate::reader::hash_reader;
+    use std::fmt::Debug;
+    use std::string::{String, ToString};
+    use std::sync::{Arc, Mutex};
+    use std::vec;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Collects `name=value` for every span field and event.
+    struct Fields(Arc<Mutex<Vec<String>>>);
+
+    impl Visit for &Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            let entry = format!("{}={:?}", field.name(), value);
+            self.0.lock().unwrap().push(entry);
+        }
+    }
+
+    impl Subscriber for Fields {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            span.record(&mut &*self);
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, values: &Record<'_>) {
+            values.record(&mut &*self);
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut &*self);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn test_span_records_workload() {
+        let fields = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = Fields(fields.clone());
+        let data = vec![7u8; 5000];
+        let digest = tracing::subscriber::with_default(subscriber, || {
+            hash_reader(&data[..], DigestSize::Bit256).unwrap()
+        });
+        let mut plain = BlueHashCore::new(DigestSize::Bit256);
+        plain.update(&data);
+        assert_eq!(digest, plain.finalize());
+
+        let fields = fields.lock().unwrap();
+        assert!(fields.contains(&"operation=\"hash_reader\"".to_string()));
+        assert!(fields.contains(&"digest_size=Bit256".to_string()));
+        assert!(fields.contains(&"bytes=5000".to_string()));
+        assert!(fields.iter().any(|f| f.starts_with("elapsed_us=")));
+    }
+}
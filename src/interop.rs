@@ -0,0 +1,180 @@
+//! Algorithm identifiers for X.509, JOSE and COSE prototypes.
+//!
+//! BlueHash has no registered identifiers. The values here are provisional
+//! and exist so that experiments agree on one mapping:
+//!
+//! * OIDs live under the UUID arc `2.25` (ITU-T X.667), which needs no
+//!   registration: `2.25.243773642494110689816213149969189122083.{1,2,3}`.
+//! * JOSE names follow the `SHA-256` / `HS256` pattern: `BH-256` for the
+//!   digest and `HBH256` for the keyed MAC.
+//! * COSE algorithm values are in the private-use range (below `-65536`).
+//!
+//! The [`jws`] module hashes and MACs payloads the way a detached JWS
+//! (RFC 7515, appendix F) does, using the BlueHash MAC.
+
+use crate::DigestSize;
+
+/// One row of the identifier mapping.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AlgorithmIdentifier {
+    pub digest_size: DigestSize,
+    /// Dotted-decimal object identifier of the digest algorithm.
+    pub oid: &'static str,
+    /// JOSE digest name, analogous to `SHA-256`.
+    pub jose_hash: &'static str,
+    /// JWS `alg` value of the keyed MAC, analogous to `HS256`.
+    pub jose_mac: &'static str,
+    /// COSE algorithm value of the digest.
+    pub cose_hash: i64,
+    /// COSE algorithm value of the keyed MAC.
+    pub cose_mac: i64,
+}
+
+pub const BLUEHASH_128: AlgorithmIdentifier = AlgorithmIdentifier {
+    digest_size: DigestSize::Bit128,
+    oid: "2.25.243773642494110689816213149969189122083.1",
+    jose_hash: "BH-128",
+    jose_mac: "HBH128",
+    cose_hash: -65_601,
+    cose_mac: -65_611,
+};
+
+pub const BLUEHASH_256: AlgorithmIdentifier = AlgorithmIdentifier {
+    digest_size: DigestSize::Bit256,
+    oid: "2.25.243773642494110689816213149969189122083.2",
+    jose_hash: "BH-256",
+    jose_mac: "HBH256",
+    cose_hash: -65_602,
+    cose_mac: -65_612,
+};
+
+pub const BLUEHASH_512: AlgorithmIdentifier = AlgorithmIdentifier {
+    digest_size: DigestSize::Bit512,
+    oid: "2.25.243773642494110689816213149969189122083.3",
+    jose_hash: "BH-512",
+    jose_mac: "HBH512",
+    cose_hash: -65_603,
+    cose_mac: -65_613,
+};
+
+const ALL: [AlgorithmIdentifier; 3] = [BLUEHASH_128, BLUEHASH_256, BLUEHASH_512];
+
+/// Returns the identifiers for a digest size.
+pub fn identifier(digest_size: DigestSize) -> AlgorithmIdentifier {
+    match digest_size {
+        DigestSize::Bit128 => BLUEHASH_128,
+        DigestSize::Bit256 => BLUEHASH_256,
+        DigestSize::Bit512 => BLUEHASH_512,
+    }
+}
+
+/// Looks up an identifier by its OID.
+pub fn from_oid(oid: &str) -> Option<AlgorithmIdentifier> {
+    ALL.into_iter().find(|id| id.oid == oid)
+}
+
+/// Looks up an identifier by a JOSE digest name or JWS `alg` value.
+pub fn from_jose(name: &str) -> Option<AlgorithmIdentifier> {
+    ALL.into_iter()
+        .find(|id| id.jose_hash == name || id.jose_mac == name)
+}
+
+/// Looks up an identifier by a COSE digest or MAC algorithm value.
+pub fn from_cose(value: i64) -> Option<AlgorithmIdentifier> {
+    ALL.into_iter()
+        .find(|id| id.cose_hash == value || id.cose_mac == value)
+}
+
+/// Detached JWS helpers using the BlueHash MAC.
+pub mod jws {
+    use super::identifier;
+    use crate::utils::to_base64url;
+    use crate::{BlueHashCore, Digest, DigestSize, Mac};
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    /// Protected header of a detached JWS for `digest_size`, base64url-encoded.
+    pub fn protected_header(digest_size: DigestSize) -> String {
+        let header = format!("{{\"alg\":\"{}\"}}", identifier(digest_size).jose_mac);
+        to_base64url(header.as_bytes())
+    }
+
+    /// JWS signing input `BASE64URL(header) || '.' || BASE64URL(payload)`.
+    pub fn signing_input(encoded_header: &str, payload: &[u8]) -> Vec<u8> {
+        format!("{}.{}", encoded_header, to_base64url(payload)).into_bytes()
+    }
+
+    /// Digest of the signing input, for wiring into an external signer.
+    pub fn payload_digest(digest_size: DigestSize, payload: &[u8]) -> Vec<u8> {
+        let mut hasher = BlueHashCore::new(digest_size);
+        hasher.update(&signing_input(&protected_header(digest_size), payload));
+        hasher.finalize()
+    }
+
+    /// Produces a detached compact JWS `header..tag` over `payload`.
+    ///
+    /// # Arguments
+    ///
+    /// * `digest_size` - Selects the `HBH128` / `HBH256` / `HBH512` algorithm.
+    /// * `key` - The MAC key.
+    /// * `payload` - The payload, which is transmitted separately.
+    ///
+    /// # Returns
+    ///
+    /// The JWS with an empty payload segment.
+    pub fn sign_detached(digest_size: DigestSize, key: &[u8], payload: &[u8]) -> String {
+        let header = protected_header(digest_size);
+        let mut mac = Mac::new(digest_size, key);
+        mac.update(&signing_input(&header, payload));
+        format!("{}..{}", header, to_base64url(&mac.finalize()))
+    }
+
+    /// Verifies a detached JWS produced by [`sign_detached`].
+    ///
+    /// Only the exact headers emitted by `sign_detached` are accepted, so the
+    /// algorithm cannot be swapped by the sender.
+    pub fn verify_detached(key: &[u8], jws: &str, payload: &[u8]) -> bool {
+        let Some((header, tag)) = jws.split_once("..") else {
+            return false;
+        };
+        let Some(digest_size) = [DigestSize::Bit128, DigestSize::Bit256, DigestSize::Bit512]
+            .into_iter()
+            .find(|&size| protected_header(size) == header)
+        else {
+            return false;
+        };
+        let mut mac = Mac::new(digest_size, key);
+        mac.update(&signing_input(header, payload));
+        let expected = to_base64url(&mac.finalize());
+        crate::constant_time_eq(expected.as_bytes(), tag.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifier_lookups_round_trip() {
+        for id in ALL {
+            assert_eq!(identifier(id.digest_size), id);
+            assert_eq!(from_oid(id.oid), Some(id));
+            assert_eq!(from_jose(id.jose_hash), Some(id));
+            assert_eq!(from_jose(id.jose_mac), Some(id));
+            assert_eq!(from_cose(id.cose_mac), Some(id));
+            assert!(id.cose_hash < -65_536);
+        }
+        assert_eq!(from_jose("HS256"), None);
+    }
+
+    #[test]
+    fn test_detached_jws_round_trip() {
+        let jws = jws::sign_detached(DigestSize::Bit128, b"key", b"payload");
+        // {"alg":"HBH128"}
+        assert!(jws.starts_with("eyJhbGciOiJIQkgxMjgifQ.."));
+        assert!(jws::verify_detached(b"key", &jws, b"payload"));
+        assert!(!jws::verify_detached(b"key", &jws, b"tampered"));
+        assert!(!jws::verify_detached(b"other", &jws, b"payload"));
+    }
+}
@@ -0,0 +1,188 @@
+//! JSON Canonicalization Scheme (RFC 8785) hashing (`json` feature).
+//!
+//! Webhook senders and receivers rarely agree on whitespace, key order or
+//! number formatting. `to_jcs_string` writes a `serde_json::Value` in the
+//! canonical form of RFC 8785, so semantically equal documents give the same
+//! bytes and `hash_json_canonical` the same digest:
+//!
+//! * no whitespace;
+//! * object members sorted by their keys' UTF-16 code units;
+//! * strings with only `"`, `\` and control characters escaped, using the
+//!   short escapes where JSON has them and lowercase `\u00xx` otherwise;
+//! * numbers as IEEE 754 doubles in the ECMAScript `Number.prototype.toString`
+//!   format, so `1.0`, `1` and `1e0` are all `1`. Integers beyond 2^53 lose
+//!   precision exactly as they would in JavaScript; send those as strings.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use serde_json::Value;
+
+/// Writes `value` in RFC 8785 canonical form.
+pub fn to_jcs_string(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value);
+    out
+}
+
+/// Hashes the RFC 8785 canonical form of `value`.
+///
+/// # Arguments
+///
+/// * `digest_size` - The BlueHash variant to use.
+/// * `value` - The JSON document.
+///
+/// # Returns
+///
+/// The digest of `to_jcs_string(value)`.
+pub fn hash_json_canonical(digest_size: DigestSize, value: &Value) -> Vec<u8> {
+    let mut hasher = BlueHashCore::new(digest_size);
+    hasher.update(to_jcs_string(value).as_bytes());
+    hasher.finalize()
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => write_number(out, n.as_f64().unwrap_or(f64::NAN)),
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut members: Vec<(&String, &Value)> = map.iter().collect();
+            members.sort_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
+            out.push('{');
+            for (i, (key, item)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                write_value(out, item);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{c}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Formats a double like ECMAScript's `Number.prototype.toString`.
+fn write_number(out: &mut String, x: f64) {
+    // serde_json values are always finite.
+    if x == 0.0 || !x.is_finite() {
+        out.push('0');
+        return;
+    }
+    if x < 0.0 {
+        out.push('-');
+    }
+    // `{:e}` yields the shortest round-tripping digits, e.g. "1.2345e-7".
+    let sci = alloc::format!("{:e}", x.abs());
+    let (mantissa, exponent) = sci.split_once('e').expect("LowerExp has an exponent");
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().expect("LowerExp exponent") + 1;
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.extend(core::iter::repeat_n('0', (n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend(core::iter::repeat_n('0', (-n) as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        let _ = write!(out, "e{}{}", if n > 0 { "+" } else { "-" }, (n - 1).abs());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rfc8785_examples() {
+        // Number samples from RFC 8785 appendix B.
+        let numbers = [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (1e21, "1e+21"),
+            (1e-7, "1e-7"),
+            (333333333.3333333, "333333333.3333333"),
+            (9007199254740992.0, "9007199254740992"),
+            (295147905179352830000.0, "295147905179352830000"),
+            (-1.2345e-5, "-0.000012345"),
+            (5e-324, "5e-324"),
+            (1.7976931348623157e308, "1.7976931348623157e+308"),
+        ];
+        for (x, expected) in numbers {
+            let mut out = String::new();
+            write_number(&mut out, x);
+            assert_eq!(out, expected);
+        }
+
+        // The example of RFC 8785 section 3.2.2.
+        let value: Value = serde_json::from_str(
+            r#"{
+                "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+                "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+                "literals": [null, true, false]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            to_jcs_string(&value),
+            r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+        );
+    }
+
+    #[test]
+    fn test_equal_documents_hash_equal() {
+        let a: Value = serde_json::from_str(r#"{"b": 1.0, "a": [1e2, "x"]}"#).unwrap();
+        let b: Value = serde_json::from_str(r#"{ "a": [100, "x"], "b": 1 }"#).unwrap();
+        assert_eq!(
+            hash_json_canonical(DigestSize::Bit256, &a),
+            hash_json_canonical(DigestSize::Bit256, &b)
+        );
+        // Sorting uses UTF-16 code units: U+10000 (D800 DC00) before U+FB33.
+        let keys = json!({"\u{fb33}": 1, "\u{10000}": 2});
+        assert_eq!(to_jcs_string(&keys), "{\"\u{10000}\":2,\"\u{fb33}\":1}");
+    }
+}
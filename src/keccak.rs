@@ -0,0 +1,226 @@
+//! keccak-f[1600] sponge backend (`keccak` feature).
+//!
+//! With `Backend::KeccakF1600` selected on the builder, a `BlueHashCore` keeps
+//! its API (streaming, keyed mode, truncation, XOF, `Mac`) but runs a standard
+//! sponge over keccak-f[1600] instead of the native permutation:
+//!
+//! ```text
+//! rate     = 200 - 2 * digest_length bytes   (168 / 136 / 72)
+//! absorb   = message lanes XORed into the rate, little-endian, permuting
+//!            whenever the rate is full
+//! finalize = message || suffix || pad10*1
+//! ```
+//!
+//! | mode | suffix |
+//! |------|--------|
+//! | `finalize`, `finalize_into` | `0x06` (SHA-3) |
+//! | `finalize_truncated(len)` | `u8(len)` absorbed first, then `0x1A` |
+//! | `finalize_xof` | `0x16` |
+//!
+//! so an unkeyed BlueHash-256 or BlueHash-512 digest with this backend *is*
+//! SHA3-256 or SHA3-512. Keyed mode XORs the keyed-domain constant into every
+//! lane and absorbs the framed key before the message, as in the native
+//! backend. XOF block `i` is the rate of keccak-f[1600] applied to the final
+//! state with `i` XORed into the last (capacity) lane, so `seek` stays cheap;
+//! the XOF stream is therefore not SHAKE.
+
+use crate::BlueHashCore;
+
+/// Number of 64-bit lanes in the keccak-f[1600] state.
+pub(crate) const KECCAK_LANES: usize = 25;
+
+/// Domain suffix of `finalize` (the SHA-3 suffix).
+pub(crate) const HASH_SUFFIX: u8 = 0x06;
+
+/// Domain suffix of `finalize_truncated`.
+pub(crate) const TRUNCATED_SUFFIX: u8 = 0x1A;
+
+/// Domain suffix of `finalize_xof`.
+pub(crate) const XOF_SUFFIX: u8 = 0x16;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000_0000_0000_0001,
+    0x0000_0000_0000_8082,
+    0x8000_0000_0000_808A,
+    0x8000_0000_8000_8000,
+    0x0000_0000_0000_808B,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8009,
+    0x0000_0000_0000_008A,
+    0x0000_0000_0000_0088,
+    0x0000_0000_8000_8009,
+    0x0000_0000_8000_000A,
+    0x0000_0000_8000_808B,
+    0x8000_0000_0000_008B,
+    0x8000_0000_0000_8089,
+    0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002,
+    0x8000_0000_0000_0080,
+    0x0000_0000_0000_800A,
+    0x8000_0000_8000_000A,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8080,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8008,
+];
+
+/// Rotation offsets of the rho step, in pi order.
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+/// Lane visiting order of the pi step.
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+/// Applies keccak-f[1600] to the first 25 lanes of `state`.
+pub(crate) fn keccak_f1600(state: &mut [u64]) {
+    let a = &mut state[..KECCAK_LANES];
+    for rc in ROUND_CONSTANTS {
+        // theta
+        let mut c = [0u64; 5];
+        for (x, column) in c.iter_mut().enumerate() {
+            *column = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                a[x + 5 * y] ^= d;
+            }
+        }
+        // rho and pi
+        let mut last = a[1];
+        for (&j, &rho) in PI.iter().zip(&RHO) {
+            let next = a[j];
+            a[j] = last.rotate_left(rho);
+            last = next;
+        }
+        // chi
+        for y in 0..5 {
+            let mut row = [0u64; 5];
+            row.copy_from_slice(&a[5 * y..5 * y + 5]);
+            for x in 0..5 {
+                a[5 * y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+        // iota
+        a[0] ^= rc;
+    }
+}
+
+/// Sponge rate in bytes for a digest of `digest_length` bytes.
+pub(crate) fn rate(digest_length: usize) -> usize {
+    KECCAK_LANES * 8 - 2 * digest_length
+}
+
+impl BlueHashCore {
+    /// Absorbs one 8-byte block (given as a big-endian word) into the rate.
+    pub(crate) fn keccak_absorb_block(&mut self, block: u64) {
+        let rate_lanes = (rate(self.digest_size.digest_length()) / 8) as u64;
+        let lane = (self.block_count % rate_lanes) as usize;
+        self.state[lane] ^= block.swap_bytes();
+        if lane as u64 == rate_lanes - 1 {
+            keccak_f1600(&mut self.state);
+        }
+    }
+
+    /// Absorbs the buffered tail and `extra`, pads with `suffix` and applies
+    /// the final permutation.
+    pub(crate) fn keccak_finish(&mut self, suffix: u8, extra: &[u8]) {
+        let rate = rate(self.digest_size.digest_length());
+        let mut position = (self.block_count % (rate / 8) as u64) as usize * 8;
+        let tail = self.block_buffer;
+        for &byte in tail[..self.buffered].iter().chain(extra) {
+            self.xor_byte(position, byte);
+            position += 1;
+            if position == rate {
+                keccak_f1600(&mut self.state);
+                position = 0;
+            }
+        }
+        self.block_buffer = [0u8; 8];
+        self.buffered = 0;
+        self.xor_byte(position, suffix);
+        self.xor_byte(rate - 1, 0x80);
+        keccak_f1600(&mut self.state);
+    }
+
+    fn xor_byte(&mut self, position: usize, byte: u8) {
+        self.state[position / 8] ^= (byte as u64) << (8 * (position % 8));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::to_hex_string;
+    use crate::{Backend, BlueHashCore, Digest, DigestSize};
+
+    fn sha3(digest_size: DigestSize, data: &[u8]) -> String {
+        let mut hasher = BlueHashCore::builder(digest_size)
+            .backend(Backend::KeccakF1600)
+            .build();
+        hasher.update(data);
+        to_hex_string(&hasher.finalize())
+    }
+
+    #[test]
+    fn test_sha3_vectors() {
+        assert_eq!(
+            sha3(DigestSize::Bit256, b""),
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+        );
+        assert_eq!(
+            sha3(DigestSize::Bit256, b"abc"),
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+        assert_eq!(
+            sha3(DigestSize::Bit512, b"abc"),
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e\
+             10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"
+        );
+        // Several rate blocks, absorbed in uneven pieces.
+        let data = [0xa3u8; 200];
+        let mut hasher = BlueHashCore::builder(DigestSize::Bit256)
+            .backend(Backend::KeccakF1600)
+            .build();
+        for piece in data.chunks(13) {
+            hasher.update(piece);
+        }
+        assert_eq!(
+            to_hex_string(&hasher.finalize()),
+            "79f38adec5c20307a98ef76e8324afbfd46cfd81b22e3973c65fa1bd9de31787"
+        );
+    }
+
+    #[test]
+    fn test_modes_are_separated() {
+        let build = || {
+            let mut hasher = BlueHashCore::builder(DigestSize::Bit256)
+                .backend(Backend::KeccakF1600)
+                .build();
+            hasher.update(b"modes");
+            hasher
+        };
+        let digest = build().finalize();
+        let truncated = build().finalize_truncated(16);
+        let mut xof = build().finalize_xof();
+        let stream: [u8; 300] = xof.squeeze_array();
+        assert_ne!(digest[..16], truncated[..]);
+        assert_ne!(digest[..], stream[..32]);
+        assert_ne!(build().finalize_truncated(20)[..16], truncated[..]);
+
+        let mut restored = BlueHashCore::import_state(&build().export_state()).unwrap();
+        assert_eq!(restored.finalize(), digest);
+
+        xof.seek(150);
+        assert_eq!(xof.squeeze_array::<150>(), stream[150..]);
+
+        let keyed = BlueHashCore::builder(DigestSize::Bit256)
+            .backend(Backend::KeccakF1600)
+            .key(b"key")
+            .build();
+        assert_ne!(crate::Mac::from_hasher(keyed).finalize(), digest);
+    }
+}
@@ -41,18 +41,226 @@
 //! while maintaining high security. It includes state manipulation, constant generation,
 //! and noise-based perturbations inspired by lattice-based cryptography.
 
+#![allow(non_snake_case)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+// 整个库不含 unsafe 代码（也不提供 FFI），Miri 下的检查见 `cargo xtask portability miri`
+#![forbid(unsafe_code)]
+
+extern crate alloc;
+
+#[cfg(feature = "debug-introspection")]
+pub mod analysis;
+#[cfg(feature = "tar")]
+mod archive;
+pub mod auth;
+#[cfg(feature = "std")]
+mod buildenv;
+mod builder;
+mod cache;
+mod cache_key;
+#[cfg(feature = "serde")]
+mod canonical;
+pub mod cas;
+mod checksum;
+mod chunked;
+pub mod cid;
+#[cfg(feature = "std")]
+mod codec;
+#[cfg(feature = "digest")]
+mod combiner;
+mod commitment;
 mod constants;
+#[cfg(feature = "cbor")]
+pub mod cose;
+mod count_min;
+pub mod ctlog;
+mod datagen;
+mod dedup;
+#[cfg(feature = "arbitrary")]
+mod digest_arbitrary;
+#[cfg(feature = "serde")]
+mod digest_serde;
+mod entropy;
+#[cfg(feature = "std")]
+mod envelope;
+pub mod erasure;
+mod event_chain;
+mod exporter;
+mod family;
+mod fast;
+mod feature_hash;
+mod fingerprint;
+mod firmware;
+mod framing;
+mod hash_sampling;
+mod hashable;
+mod http;
+#[cfg(feature = "uuid")]
+mod ids;
+#[cfg(feature = "std")]
+mod instrument;
+pub mod interop;
+#[cfg(feature = "json")]
+mod jcs;
+#[cfg(feature = "keccak")]
+mod keccak;
+mod lthash;
+mod mac;
+#[cfg(feature = "std")]
+mod manifest;
+pub mod merkle;
+#[cfg(feature = "std")]
+mod metrics;
+mod minhash;
+mod multipart;
+mod multiset;
 mod noise;
+pub mod object;
+pub mod otp;
+mod output;
+mod package;
+#[cfg(feature = "std")]
+mod pipeline;
+mod precomputed;
+mod prehash;
+#[cfg(feature = "prost")]
+mod protobuf;
+pub mod puzzle;
+#[cfg(feature = "std")]
+mod reader;
+mod record;
+pub mod rendezvous;
+#[cfg(feature = "std")]
+mod resumable;
+mod sampling;
+mod shared_prefix;
+pub mod similarity;
+mod siv;
+#[cfg(feature = "std")]
+mod sparse;
+pub mod sss;
+mod state;
+#[cfg(feature = "xattr")]
+mod stored_digest;
+mod stretch;
+#[cfg(feature = "timing-tests")]
+pub mod timing_tests;
+#[cfg(feature = "debug-introspection")]
+mod trace;
+pub mod trie;
+mod typed;
 mod utils;
+#[cfg(feature = "std")]
+mod watch;
+mod xof;
 
-use crate::constants::{generate_constants, SBOX};
-use crate::noise::generate_lwe_noise;
+use crate::constants::{generate_constants, generate_constants_from_seed, NOISE_PRIME, SBOX};
+use crate::utils::to_u64;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
-use std::fmt;
-use std::fmt::Write;
+
+#[cfg(feature = "tar")]
+pub use crate::archive::{hash_tar, ArchiveDigests, MemberDigest};
+#[cfg(feature = "std")]
+pub use crate::buildenv::fingerprint_env;
+pub use crate::builder::BlueHashBuilder;
+pub use crate::cache::{EvictionPolicy, HashCache, LruEviction, NoEviction};
+pub use crate::cache_key::CacheKey;
+#[cfg(feature = "serde")]
+pub use crate::canonical::{hash_serde, to_canonical_bytes, CanonicalError};
+pub use crate::checksum::{BlueHashChecksum, Checksum128, Checksum64};
+pub use crate::chunked::{ChunkError, ChunkedVerifier, DEFAULT_MAX_PENDING_LEN};
+#[cfg(feature = "std")]
+pub use crate::codec::{
+    FrameDecoder, FrameEncoder, FrameError, DEFAULT_FRAME_TAG_LEN, MAX_FRAME_LEN,
+};
+#[cfg(feature = "digest")]
+pub use crate::combiner::CombinedHasher;
+pub use crate::commitment::{
+    derive_committed_key, open_committing, seal_committing, verify_key_commitment, CommittedKey,
+    OpenError, KEY_COMMITMENT_LEN,
+};
+pub use crate::count_min::{CountMinSketch, SketchMismatch};
+pub use crate::datagen::{DataBlocks, DataGenerator};
+pub use crate::dedup::{DedupWriter, DEDUP_MAX_CHUNK_SIZE, DEDUP_MIN_CHUNK_SIZE};
+pub use crate::entropy::{EntropyError, EntropyPool, MIN_POOL_SIZE, POOL_COUNT};
+#[cfg(feature = "std")]
+pub use crate::envelope::{
+    open_file, seal_file, seal_file_with_chunk_size, EnvelopeError, DEFAULT_CHUNK_SIZE,
+    ENVELOPE_HEADER_LEN, ENVELOPE_NONCE_LEN, MAX_CHUNK_SIZE,
+};
+pub use crate::event_chain::{
+    verify_log, verify_segment, Anchor, ChainError, ChainHead, EventChain,
+};
+pub use crate::exporter::{ExporterSecret, Transcript};
+pub use crate::family::{derive_many, DeriveMany};
+pub use crate::fast::{BlueHashFast, FAST_ROUNDS};
+pub use crate::feature_hash::{feature_hash, HashingVectorizer};
+pub use crate::fingerprint::fingerprint_prefix;
+pub use crate::firmware::{verify_image, ImageError, ImageManifest, ImageSection};
+pub use crate::framing::hash_fields;
+pub use crate::hash_sampling::{sample_if, sampling_value, BottomK};
+pub use crate::hashable::BlueHashable;
+pub use crate::http::{DigestedBody, HttpDigest, HTTP_DIGEST_ALGORITHM};
+#[cfg(feature = "uuid")]
+pub use crate::ids::uuid_from;
+#[cfg(feature = "json")]
+pub use crate::jcs::{hash_json_canonical, to_jcs_string};
+pub use crate::lthash::{LtHash, LtHashLengthError, LTHASH_LANES, LTHASH_LEN};
+pub use crate::mac::{Mac, StreamMac, MIN_TAG_LEN};
+#[cfg(feature = "std")]
+pub use crate::manifest::{
+    verify_tree, FileReport, FileStatus, Manifest, ManifestError, TreeReport,
+};
+#[cfg(feature = "std")]
+pub use crate::metrics::{HashMetrics, HashMetricsSink, MeteredHasher};
+pub use crate::minhash::{MinHash, MinHashError};
+pub use crate::multipart::{
+    MultipartDigest, MultipartError, MultipartHasher, PartDigest, MAX_PART_NUMBER,
+};
+pub use crate::multiset::MultisetHash;
+pub use crate::output::{decode_hex, verify_hex, DigestOutput, ParseError};
+pub use crate::package::{hash_package, PackageDigest, PackageError};
+#[cfg(feature = "std")]
+pub use crate::pipeline::{hash_file_pipelined, hash_reader_pipelined, PIPELINE_DEPTH};
+pub use crate::precomputed::Precomputed;
+pub use crate::prehash::{prehash_for_signing, Prehasher, PREHASH_LEN};
+#[cfg(feature = "prost")]
+pub use crate::protobuf::{hash_protobuf, hash_protobuf_named};
+#[cfg(feature = "std")]
+pub use crate::reader::{
+    auto_block_size, hash_file, hash_file_with_block_size, hash_reader,
+    hash_reader_with_block_size, HashReader,
+};
+pub use crate::record::RecordHasher;
+#[cfg(feature = "std")]
+pub use crate::resumable::{FileCheckpoint, ResumableFileHash};
+pub use crate::sampling::{sample_poly_cbd, sample_poly_uniform};
+pub use crate::shared_prefix::{hash_shared_prefixes, SharedPrefixHasher};
+pub use crate::siv::derive_nonce;
+#[cfg(feature = "std")]
+pub use crate::sparse::hash_file_sparse;
+pub use crate::state::StateError;
+#[cfg(feature = "xattr")]
+pub use crate::stored_digest::{
+    digest_attribute_name, store_digest, stored_digest, verify_stored_digest,
+};
+pub use crate::stretch::{stretch_secret, StretchProfile, StretchedKeys, STRETCHED_KEY_LEN};
+#[cfg(feature = "debug-introspection")]
+pub use crate::trace::{HashTrace, RoundSnapshot, TraceHasher, TracePhase};
+pub use crate::typed::{BlueHash128, BlueHash256, BlueHash512, HashParameters, TypedBlueHash};
+#[cfg(feature = "std")]
+pub use crate::watch::{ChangeEvent, TreeWatcher};
+pub use crate::xof::XofReader;
+use crate::xof::XOF_DOMAIN;
+#[cfg(feature = "derive")]
+pub use BlueHash_derive::BlueHashable;
 
 /// 摘要大小及相关参数定义
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum DigestSize {
     Bit128,
     Bit256,
@@ -60,7 +268,7 @@ pub enum DigestSize {
 }
 
 impl DigestSize {
-    pub fn round_count(&self) -> usize {
+    pub const fn round_count(&self) -> usize {
         // 为增强抗量子安全性，置换轮次加倍
         match self {
             DigestSize::Bit128 => 56 * 2,
@@ -68,20 +276,117 @@ impl DigestSize {
             DigestSize::Bit512 => 80 * 2,
         }
     }
-    pub fn digest_length(&self) -> usize {
+    pub const fn digest_length(&self) -> usize {
         match self {
             DigestSize::Bit128 => 16,
             DigestSize::Bit256 => 32,
             DigestSize::Bit512 => 64,
         }
     }
-    pub fn state_size(&self) -> usize {
+    pub const fn state_size(&self) -> usize {
         match self {
             DigestSize::Bit128 => 25,
             DigestSize::Bit256 => 32,
             DigestSize::Bit512 => 40,
         }
     }
+    /// 容量字数：容量为摘要长度的两倍（2n 比特），不小于安全强度的两倍，输入永远不会直接写入容量部分
+    pub const fn capacity_words(&self) -> usize {
+        self.digest_length() * 2 / 8
+    }
+    /// 速率字数：状态中除容量以外、吸收时写入输入分块的部分
+    pub const fn rate_words(&self) -> usize {
+        self.state_size() - self.capacity_words()
+    }
+    /// 速率字节数（128 / 256 / 512 位摘要分别为 168 / 192 / 192 字节）
+    pub const fn rate_bytes(&self) -> usize {
+        self.rate_words() * 8
+    }
+    /// 容量比特数
+    pub const fn capacity_bits(&self) -> usize {
+        self.capacity_words() * 64
+    }
+    /// 根据摘要字节长度反查摘要大小
+    pub fn from_digest_length(length: usize) -> Option<DigestSize> {
+        match length {
+            16 => Some(DigestSize::Bit128),
+            32 => Some(DigestSize::Bit256),
+            64 => Some(DigestSize::Bit512),
+            _ => None,
+        }
+    }
+}
+
+/// 编译期启用的能力，供打包者确认构建的是精简版还是完整版
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    /// 是否链接标准库（`std` 特性）；关闭时仅依赖 `core` 与 `alloc`
+    pub std: bool,
+    /// `permute_core` 是否通过 rayon 并行计算（`parallel` 特性）
+    pub parallel: bool,
+    /// 是否启用 SIMD 后端（当前尚无 SIMD 实现）
+    pub simd: bool,
+}
+
+/// 报告当前构建中编译进来的后端
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        std: cfg!(feature = "std"),
+        parallel: cfg!(feature = "parallel"),
+        simd: false,
+    }
+}
+
+/// 安全配置档：决定每种摘要大小的置换轮次
+///
+/// | 配置档 | 128 位 | 256 位 | 512 位 |
+/// |--------|--------|--------|--------|
+/// | `Fast` | 56 | 64 | 80 |
+/// | `Standard` | 112 | 128 | 160 |
+/// | `Paranoid` | 168 | 192 | 240 |
+///
+/// `Standard` 与 `DigestSize::round_count` 完全一致，是默认配置；`Fast` 为加倍前的原始轮次，
+/// 适用于对延迟敏感的场景；`Paranoid` 在原始轮次基础上取三倍。
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum SecurityProfile {
+    Fast,
+    #[default]
+    Standard,
+    Paranoid,
+}
+
+impl SecurityProfile {
+    /// 该配置档下指定摘要大小的置换轮次
+    pub fn round_count(&self, digest_size: DigestSize) -> usize {
+        let base = digest_size.round_count() / 2;
+        match self {
+            SecurityProfile::Fast => base,
+            SecurityProfile::Standard => base * 2,
+            SecurityProfile::Paranoid => base * 3,
+        }
+    }
+
+    /// 根据轮次反查配置档
+    pub fn from_round_count(digest_size: DigestSize, round_count: usize) -> Option<Self> {
+        [
+            SecurityProfile::Fast,
+            SecurityProfile::Standard,
+            SecurityProfile::Paranoid,
+        ]
+        .into_iter()
+        .find(|profile| profile.round_count(digest_size) == round_count)
+    }
+}
+
+/// 海绵层后端：决定吸收、最终混合与 XOF 输出使用的置换
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Backend {
+    /// BlueHash 原生置换（默认）
+    #[default]
+    Native,
+    /// 基于 keccak-f[1600] 的标准海绵（`keccak` 特性），见 `keccak` 模块文档
+    #[cfg(feature = "keccak")]
+    KeccakF1600,
 }
 
 /// 置换函数，增加 S‑盒查表非线性转换
@@ -93,150 +398,586 @@ pub fn permute_core(
     digest_size: DigestSize,
 ) -> Vec<u64> {
     let constant = generate_constants(round, input_data, digest_size.digest_length());
-    (0..state_size)
-        .into_par_iter()
-        .map(|i| {
-            let a = state[i];
-            let b = state[(i + 1) % state_size];
-            let c = state[(i + 2) % state_size];
-            let d = state[(i + 3) % state_size];
-            let mut mixed = a
-                .wrapping_add(constant)
-                .wrapping_add(b)
-                .rotate_left(29)
-                .wrapping_add(c & d.rotate_right(17))
-                .rotate_left(23);
-            // 对混合结果每个字节执行 S‑盒查表替换（实现恒定时间操作）
-            let mut bytes = mixed.to_be_bytes();
-            for byte in &mut bytes {
-                // 采用数组索引替换，不分支实现
-                *byte = SBOX[*byte as usize];
-            }
-            mixed = u64::from_be_bytes(bytes);
-            mixed
-        })
-        .collect()
+    mix_state(state, constant, state_size)
+}
+
+/// 使用给定轮常量执行一次状态混合
+///
+/// 启用 `parallel` 特性时各字通过 rayon 并行计算，否则顺序计算，两者结果一致。
+/// 每个输出字只读取输入状态、按下标独立计算，没有共享可变状态；索引并行迭代器的
+/// `collect` 按下标顺序写回，因此结果与线程数和调度顺序无关。
+fn mix_state(state: &[u64], constant: u64, state_size: usize) -> Vec<u64> {
+    let state = &state[..state_size];
+    #[cfg(feature = "parallel")]
+    let indices = (0..state_size).into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let indices = 0..state_size;
+    indices.map(|i| mix_word(state, i, constant)).collect()
+}
+
+/// 原地执行一次状态混合（顺序实现，不分配堆内存），结果与 `mix_state` 一致
+///
+/// 状态最多只有 40 个字，顺序计算远快于把每一轮分派到 rayon 线程池，因此哈希器内部使用此实现。
+pub(crate) fn mix_state_in_place(state: &mut [u64], constant: u64) {
+    // 在状态后追加前 3 个字，使每个输出字都能以连续下标读取，省去取模运算
+    let state_size = state.len();
+    let mut extended = [0u64; MAX_STATE_WORDS + 3];
+    extended[..state_size].copy_from_slice(state);
+    extended[state_size..state_size + 3].copy_from_slice(&state[..3]);
+    for (i, out) in state.iter_mut().enumerate() {
+        *out = mix_words(&extended[i..i + 4], constant);
+    }
+}
+
+/// 海绵层使用的轮函数：以轮常量对状态执行一轮原地混合
+///
+/// 哈希器的置换轮次、最终混合与 XOF 输出均通过该接口调用轮函数。默认实现为 `NativePermutation`；
+/// 启用 `insecure-research` 特性后可通过 `BlueHashBuilder::permutation` 替换，
+/// 以便在相同的吸收/挤出框架内对比不同的混合函数。
+pub trait Permutation: fmt::Debug + Send + Sync {
+    /// 以轮常量 `constant` 原地混合 `state`（长度为当前摘要大小的状态字数）
+    fn mix(&self, state: &mut [u64], constant: u64);
+}
+
+/// BlueHash 原生轮函数，与 `permute_core` 的混合相同
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct NativePermutation;
+
+impl Permutation for NativePermutation {
+    fn mix(&self, state: &mut [u64], constant: u64) {
+        mix_state_in_place(state, constant);
+    }
+}
+
+/// 计算混合后状态的第 i 个字
+fn mix_word(state: &[u64], i: usize, constant: u64) -> u64 {
+    let state_size = state.len();
+    let words = [
+        state[i],
+        state[(i + 1) % state_size],
+        state[(i + 2) % state_size],
+        state[(i + 3) % state_size],
+    ];
+    mix_words(&words, constant)
 }
 
-/// BlueHash 核心结构，采用固定 IV 初始化，并累积输入数据
+/// 由相邻的 4 个字（第 i 至 i+3 个，按状态长度回绕）计算混合后的第 i 个字
+fn mix_words(words: &[u64], constant: u64) -> u64 {
+    let (a, b, c, d) = (words[0], words[1], words[2], words[3]);
+    let mut mixed = a
+        .wrapping_add(constant)
+        .wrapping_add(b)
+        .rotate_left(29)
+        .wrapping_add(c & d.rotate_right(17))
+        .rotate_left(23);
+    // 对混合结果每个字节执行 S‑盒查表替换（实现恒定时间操作）
+    let mut bytes = mixed.to_be_bytes();
+    for byte in &mut bytes {
+        // 采用数组索引替换，不分支实现
+        *byte = SBOX[*byte as usize];
+    }
+    mixed = u64::from_be_bytes(bytes);
+    mixed
+}
+
+/// 截断输出的域分离常量，与截断长度异或后混入状态
+const TRUNCATION_DOMAIN: u64 = 0x5452_554E_4341_5445;
+
+/// 带密钥模式的域分离常量
+const KEYED_DOMAIN: u64 = 0x4B45_5945_445F_4D41;
+
+/// 所有摘要大小中最大的状态字数
+pub(crate) const MAX_STATE_WORDS: usize = 40;
+
+/// 允许吸收的最大输入字节数：保证以比特计的长度（填充中的 128 位长度字段）不会回绕
+pub const MAX_INPUT_LEN: u128 = u128::MAX >> 3;
+
+/// 累计输入超过 `MAX_INPUT_LEN` 时返回的错误
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LengthOverflow;
+
+impl fmt::Display for LengthOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "total input length exceeds {} bytes", MAX_INPUT_LEN)
+    }
+}
+
+impl core::error::Error for LengthOverflow {}
+
+/// 吸收速率：每个分块 8 字节，恰好对应一个状态字
+pub(crate) const BLOCK_LEN: usize = 8;
+
+/// 速率部分写满后执行的中间置换轮数（足以让任一状态字扩散到 40 个字的全部状态）
+const ABSORB_ROUNDS: usize = 24;
+
+/// 最后分块长度：0x80 与补零共一个分块，再加 16 字节长度信息
+const PAD_LEN: usize = BLOCK_LEN + 16;
+
+// 编译期保证分块与状态字等宽，且所有 IV 表都能放入定长状态数组
+const _: () = {
+    assert!(BLOCK_LEN == core::mem::size_of::<u64>());
+    assert!(IV_128.len() <= MAX_STATE_WORDS);
+    assert!(IV_256.len() <= MAX_STATE_WORDS);
+    assert!(IV_512.len() <= MAX_STATE_WORDS);
+    assert!(DigestSize::Bit128.rate_words() > 0);
+    assert!(DigestSize::Bit512.capacity_bits() >= 2 * 512);
+};
+
+/// BlueHash-128 的固定 IV
+static IV_128: [u64; 25] = [
+    0x0123456789ABCDEF,
+    0x23456789ABCDEF01,
+    0x456789ABCDEF0123,
+    0x6789ABCDEF012345,
+    0x89ABCDEF01234567,
+    0xABCDEF0123456789,
+    0xCDEF0123456789AB,
+    0xEF0123456789ABCD,
+    0x13579BDF02468ACE,
+    0x2468ACE13579BDF0,
+    0x3579BDF02468ACE1,
+    0x468ACE13579BDF02,
+    0x579BDF02468ACE13,
+    0x68ACE13579BDF24,
+    0x79BDF02468ACE35,
+    0x8ACE13579BDF468,
+    0x9BDF02468ACE579,
+    0xACE13579BDF68AC,
+    0xBDF02468ACE79BD,
+    0xCE13579BDF8ACE0,
+    0xDF02468ACE9BDF1,
+    0xE13579BDFACE135,
+    0xF02468ACEBDF024,
+    0x0123456789ABCDEF,
+    0x89ABCDEF01234567,
+];
+
+/// BlueHash-256 的固定 IV（前 25 个字与 BlueHash-128 相同，其余补足到 32 个字）
+static IV_256: [u64; 32] = [
+    0x0123456789ABCDEF,
+    0x23456789ABCDEF01,
+    0x456789ABCDEF0123,
+    0x6789ABCDEF012345,
+    0x89ABCDEF01234567,
+    0xABCDEF0123456789,
+    0xCDEF0123456789AB,
+    0xEF0123456789ABCD,
+    0x13579BDF02468ACE,
+    0x2468ACE13579BDF0,
+    0x3579BDF02468ACE1,
+    0x468ACE13579BDF02,
+    0x579BDF02468ACE13,
+    0x68ACE13579BDF24,
+    0x79BDF02468ACE35,
+    0x8ACE13579BDF468,
+    0x9BDF02468ACE579,
+    0xACE13579BDF68AC,
+    0xBDF02468ACE79BD,
+    0xCE13579BDF8ACE0,
+    0xDF02468ACE9BDF1,
+    0xE13579BDFACE135,
+    0xF02468ACEBDF024,
+    0x0123456789ABCDEF,
+    0x89ABCDEF01234567,
+    0x23456789ABCDEF01,
+    0x456789ABCDEF0123,
+    0x6789ABCDEF012345,
+    0x89ABCDEF01234567,
+    0xABCDEF0123456789,
+    0xCDEF0123456789AB,
+    0xEF0123456789ABCD,
+];
+
+/// BlueHash-512 的固定 IV（前 25 个字与 BlueHash-128 相同，其余补足到 40 个字）
+static IV_512: [u64; 40] = [
+    0x0123456789ABCDEF,
+    0x23456789ABCDEF01,
+    0x456789ABCDEF0123,
+    0x6789ABCDEF012345,
+    0x89ABCDEF01234567,
+    0xABCDEF0123456789,
+    0xCDEF0123456789AB,
+    0xEF0123456789ABCD,
+    0x13579BDF02468ACE,
+    0x2468ACE13579BDF0,
+    0x3579BDF02468ACE1,
+    0x468ACE13579BDF02,
+    0x579BDF02468ACE13,
+    0x68ACE13579BDF24,
+    0x79BDF02468ACE35,
+    0x8ACE13579BDF468,
+    0x9BDF02468ACE579,
+    0xACE13579BDF68AC,
+    0xBDF02468ACE79BD,
+    0xCE13579BDF8ACE0,
+    0xDF02468ACE9BDF1,
+    0xE13579BDFACE135,
+    0xF02468ACEBDF024,
+    0x0123456789ABCDEF,
+    0x89ABCDEF01234567,
+    0x23456789ABCDEF01,
+    0x456789ABCDEF0123,
+    0x6789ABCDEF012345,
+    0x89ABCDEF01234567,
+    0xABCDEF0123456789,
+    0xCDEF0123456789AB,
+    0xEF0123456789ABCD,
+    0x13579BDF02468ACE,
+    0x2468ACE13579BDF0,
+    0x3579BDF02468ACE1,
+    0x468ACE13579BDF02,
+    0x579BDF02468ACE13,
+    0x68ACE13579BDF24,
+    0x79BDF02468ACE35,
+    0x0123456789ABCDEF,
+];
+
+/// BlueHash 核心结构，采用固定 IV 初始化，并以流式方式吸收输入数据
+///
+/// 状态按海绵结构划分为速率与容量两部分（见 `DigestSize::rate_words` 与 `DigestSize::capacity_words`）：
+/// 输入分块只异或进速率部分，速率写满后执行 `ABSORB_ROUNDS` 轮中间置换；完整的置换轮次在
+/// `finalize` 时执行。多次调用 `update` 与一次性输入拼接后的数据得到相同摘要。
+///
+/// 抗长度扩展：摘要只输出状态的前 `digest_length` 字节，容量部分从不输出，因此仅凭 H(m) 与 m 的
+/// 长度无法恢复完整状态，也就无法计算 H(m || pad || m')。这一性质只来自容量的保密，与轮常量无关：
+/// 完整置换的轮常量以输入字节的回绕累加和为种子（与字节顺序无关，且噪声项只有 41 种取值，几乎不随
+/// 种子变化），收尾置换的轮常量只取决于总长度，二者都应视为公开值。`Mac` 因此可以直接使用带密钥
+/// 模式，无需 HMAC 式嵌套。
+/// 注意：未结束的哈希器通过 `export_state` 导出的状态可以继续吸收数据，应按已吸收的数据同等保密。
+///
+/// 不会 panic：`try_update`、`Digest::finalize`、`finalize_xof` 与 `XofReader` 的全部方法对任意输入都不会
+/// panic（计数器均为回绕或经过检查的运算，索引均有界，即使在开启 `overflow-checks` 的发布配置下也是如此）。
+/// 只有违反文档约定时才会 panic：`update` 在累计输入超过 `MAX_INPUT_LEN` 时、`finalize_into` 与
+/// `finalize_truncated` 在输出长度不合法时。`fuzz/` 下的模糊测试目标覆盖这些接口。
+///
+/// 哈希器只包含普通数据，不依赖线程局部变量或 rayon 线程池状态，因此是 `Send + Sync` 的，
+/// 可以在两次 `update` 之间跨线程移动（例如在异步运行时的不同工作线程之间迁移）。
 #[derive(Debug, Clone)]
 pub struct BlueHashCore {
-    state: Vec<u64>,
+    state: [u64; MAX_STATE_WORDS], // 置换状态（仅前 state_size 个字有效）
     round_count: usize,
+    permutation: &'static dyn Permutation, // 轮函数（默认为 NativePermutation）
+    backend: Backend,                      // 海绵层后端
     digest_size: DigestSize,
-    total_len: u128,       // 累计输入字节数
-    input_buffer: Vec<u8>, // 保存输入数据（仅用于后续填充计算）
+    total_len: u128,               // 累计输入字节数
+    input_sum: u64,                // 输入字节的回绕累加和（作为轮常量的噪声种子）
+    block_count: u64,              // 已吸收的 8 字节分块数量
+    block_buffer: [u8; BLOCK_LEN], // 尚未凑满一个分块的尾部数据（定长数组，不分配堆内存）
+    buffered: usize,               // block_buffer 中的有效字节数
 }
 
 impl BlueHashCore {
-    /// 固定 IV：根据摘要大小返回预设定的初始状态
-    fn fixed_iv(digest_size: DigestSize) -> Vec<u64> {
+    /// 固定 IV：根据摘要大小返回预设定的初始状态（静态表，无需分配）
+    fn fixed_iv(digest_size: DigestSize) -> &'static [u64] {
         match digest_size {
-            DigestSize::Bit128 => vec![
-                0x0123456789ABCDEF,
-                0x23456789ABCDEF01,
-                0x456789ABCDEF0123,
-                0x6789ABCDEF012345,
-                0x89ABCDEF01234567,
-                0xABCDEF0123456789,
-                0xCDEF0123456789AB,
-                0xEF0123456789ABCD,
-                0x13579BDF02468ACE,
-                0x2468ACE13579BDF0,
-                0x3579BDF02468ACE1,
-                0x468ACE13579BDF02,
-                0x579BDF02468ACE13,
-                0x68ACE13579BDF24,
-                0x79BDF02468ACE35,
-                0x8ACE13579BDF468,
-                0x9BDF02468ACE579,
-                0xACE13579BDF68AC,
-                0xBDF02468ACE79BD,
-                0xCE13579BDF8ACE0,
-                0xDF02468ACE9BDF1,
-                0xE13579BDFACE135,
-                0xF02468ACEBDF024,
-                0x0123456789ABCDEF,
-                0x89ABCDEF01234567,
-            ],
-            DigestSize::Bit256 => {
-                let mut iv = Self::fixed_iv(DigestSize::Bit128);
-                iv.extend_from_slice(&[
-                    0x23456789ABCDEF01,
-                    0x456789ABCDEF0123,
-                    0x6789ABCDEF012345,
-                    0x89ABCDEF01234567,
-                    0xABCDEF0123456789,
-                    0xCDEF0123456789AB,
-                    0xEF0123456789ABCD,
-                ]);
-                iv.resize(32, 0x0123456789ABCDEF);
-                iv
-            }
-            DigestSize::Bit512 => {
-                let mut iv = Self::fixed_iv(DigestSize::Bit128);
-                iv.extend_from_slice(&[
-                    0x23456789ABCDEF01,
-                    0x456789ABCDEF0123,
-                    0x6789ABCDEF012345,
-                    0x89ABCDEF01234567,
-                    0xABCDEF0123456789,
-                    0xCDEF0123456789AB,
-                    0xEF0123456789ABCD,
-                    0x13579BDF02468ACE,
-                    0x2468ACE13579BDF0,
-                    0x3579BDF02468ACE1,
-                    0x468ACE13579BDF02,
-                    0x579BDF02468ACE13,
-                    0x68ACE13579BDF24,
-                    0x79BDF02468ACE35,
-                ]);
-                iv.resize(40, 0x0123456789ABCDEF);
-                iv
-            }
+            DigestSize::Bit128 => &IV_128,
+            DigestSize::Bit256 => &IV_256,
+            DigestSize::Bit512 => &IV_512,
         }
     }
 
     /// 构造新的 BlueHash 实例，使用固定 IV 初始化状态和输入缓冲区
+    ///
+    /// 构造过程不分配堆内存，也不预先计算任何轮常量（轮常量在 `finalize` 时按需生成）。
     pub fn new(digest_size: DigestSize) -> Self {
-        let state = Self::fixed_iv(digest_size);
+        let iv = Self::fixed_iv(digest_size);
+        let mut state = [0u64; MAX_STATE_WORDS];
+        state[..iv.len()].copy_from_slice(iv);
         Self {
             state,
             round_count: digest_size.round_count(),
+            permutation: &NativePermutation,
+            backend: Backend::Native,
             digest_size,
             total_len: 0,
-            input_buffer: Vec::new(),
+            input_sum: 0,
+            block_count: 0,
+            block_buffer: [0u8; BLOCK_LEN],
+            buffered: 0,
         }
     }
 
-    /// 优化填充函数，处理最后分块：添加 0x80 后补零至块边界，再附加128位长度信息
-    fn pad(&self, data: &[u8]) -> Vec<u8> {
-        let block_size = 8;
-        let mut padded = data.to_vec();
-        padded.push(0x80);
-        // 补全到 block_size 整倍数（留出 16 字节长度信息空间）
-        while (padded.len() + 16) % block_size != 0 {
-            padded.push(0);
+    /// 构造带密钥的 BlueHash 实例：先进行密钥模式域分离，再吸收带长度前缀的密钥
+    pub fn new_keyed(digest_size: DigestSize, key: &[u8]) -> Self {
+        let mut hasher = Self::new(digest_size);
+        hasher.absorb_key(key);
+        hasher
+    }
+
+    /// 密钥模式初始化：域分离后吸收带长度前缀的密钥（须在吸收任何消息之前调用）
+    fn absorb_key(&mut self, key: &[u8]) {
+        self.separate_domain(KEYED_DOMAIN);
+        self.update_framed(key);
+    }
+
+    /// 是否使用 keccak-f[1600] 后端（未启用 `keccak` 特性时恒为 false）
+    fn is_keccak(&self) -> bool {
+        #[cfg(feature = "keccak")]
+        {
+            self.backend == Backend::KeccakF1600
         }
-        let total_bits = self.total_len.wrapping_mul(8);
-        padded.extend_from_slice(&total_bits.to_be_bytes());
+        #[cfg(not(feature = "keccak"))]
+        {
+            false
+        }
+    }
+
+    /// 有效状态字数：原生后端取决于摘要大小，keccak 后端固定为 25 个字
+    fn state_words(&self) -> usize {
+        if self.is_keccak() {
+            25
+        } else {
+            self.digest_size.state_size()
+        }
+    }
+
+    /// 当前摘要大小下有效的状态字
+    fn words(&self) -> &[u64] {
+        &self.state[..self.state_words()]
+    }
+
+    fn words_mut(&mut self) -> &mut [u64] {
+        let state_size = self.state_words();
+        &mut self.state[..state_size]
+    }
+
+    /// 将一个分块异或进速率部分的下一个字，速率写满时执行中间置换（容量部分只由置换更新）
+    fn absorb_block(&mut self, block: u64) {
+        #[cfg(feature = "keccak")]
+        if self.is_keccak() {
+            self.keccak_absorb_block(block);
+            self.block_count = self.block_count.wrapping_add(1);
+            return;
+        }
+        let rate_words = self.digest_size.rate_words() as u64;
+        let idx = (self.block_count % rate_words) as usize;
+        self.state[idx] ^= block;
+        self.block_count = self.block_count.wrapping_add(1);
+        if idx as u64 == rate_words - 1 {
+            self.absorb_permutation();
+        }
+    }
+
+    /// 吸收阶段的中间置换，轮常量固定（与输入无关）
+    fn absorb_permutation(&mut self) {
+        for round in 0..ABSORB_ROUNDS {
+            let constant = NOISE_PRIME.wrapping_mul(round as u64 + 1);
+            let permutation = self.permutation;
+            permutation.mix(self.words_mut(), constant);
+        }
+    }
+
+    /// 吸收尾部不足 8 字节的数据，并以输入累加和为种子执行全部置换轮次
+    fn absorb_rounds(&mut self) {
+        self.flush_tail();
+        self.run_rounds(&mut |_, _, _| {});
+    }
+
+    /// 吸收尾部不足 8 字节的数据
+    fn flush_tail(&mut self) {
+        if self.buffered > 0 {
+            let block = to_u64(&self.block_buffer[..self.buffered]);
+            self.absorb_block(block);
+            self.block_buffer = [0u8; BLOCK_LEN];
+            self.buffered = 0;
+        }
+    }
+
+    /// 执行全部置换轮次，每轮结束后以（轮次，轮常量，状态）调用 `observe`
+    fn run_rounds<F: FnMut(usize, u64, &[u64])>(&mut self, observe: &mut F) {
+        for round in 0..self.round_count {
+            let constant = generate_constants_from_seed(round, self.input_sum);
+            let permutation = self.permutation;
+            permutation.mix(self.words_mut(), constant);
+            observe(round, constant, self.words());
+        }
+    }
+
+    /// 优化填充函数，生成最后分块：0x80 后补零至块边界，再附加128位长度信息（定长数组，不分配堆内存）
+    fn pad(&self) -> [u8; PAD_LEN] {
+        let mut padded = [0u8; PAD_LEN];
+        padded[0] = 0x80;
+        // total_len 不超过 MAX_INPUT_LEN，因此以比特计的长度不会回绕
+        let total_bits = self.total_len * 8;
+        padded[BLOCK_LEN..].copy_from_slice(&total_bits.to_be_bytes());
         padded
     }
 
+    /// 吸收数据，若累计长度将超过 `MAX_INPUT_LEN` 则返回错误且不修改哈希器状态
+    pub fn try_update(&mut self, data: &[u8]) -> Result<(), LengthOverflow> {
+        let total_len = self
+            .total_len
+            .checked_add(data.len() as u128)
+            .filter(|&len| len <= MAX_INPUT_LEN)
+            .ok_or(LengthOverflow)?;
+        self.total_len = total_len;
+        self.input_sum = data
+            .iter()
+            .fold(self.input_sum, |acc, &byte| acc.wrapping_add(byte as u64));
+        let mut data = data;
+        if self.buffered > 0 {
+            let take = (BLOCK_LEN - self.buffered).min(data.len());
+            self.block_buffer[self.buffered..self.buffered + take].copy_from_slice(&data[..take]);
+            self.buffered += take;
+            data = &data[take..];
+            if self.buffered < BLOCK_LEN {
+                return Ok(());
+            }
+            let block = u64::from_be_bytes(self.block_buffer);
+            self.absorb_block(block);
+            self.buffered = 0;
+        }
+        let mut chunks = data.chunks_exact(BLOCK_LEN);
+        for chunk in &mut chunks {
+            self.absorb_block(to_u64(chunk));
+        }
+        let rest = chunks.remainder();
+        self.block_buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+        Ok(())
+    }
+
+    /// 以 XOF 模式结束哈希，返回可输出任意长度数据的读取器
+    ///
+    /// XOF 的输出与 `finalize` 的结果做了域分离，两者不会出现相同前缀。
+    pub fn finalize_xof(&mut self) -> XofReader {
+        #[cfg(feature = "keccak")]
+        if self.is_keccak() {
+            self.keccak_finish(keccak::XOF_SUFFIX, &[]);
+            let rate = keccak::rate(self.digest_size.digest_length());
+            return XofReader::new(self.words(), 0, 0, self.permutation, self.backend, rate);
+        }
+        self.absorb_rounds();
+        self.separate_domain(XOF_DOMAIN);
+        self.final_mix();
+        XofReader::new(
+            self.words(),
+            self.round_count + 4,
+            self.round_count,
+            self.permutation,
+            self.backend,
+            self.digest_size.state_size() / 2 * 8,
+        )
+    }
+
+    /// 截断输出：将截断长度混入最终状态后再输出前 `length` 字节
+    ///
+    /// 与简单切片不同，BlueHash-256 截断到 16 字节的结果与 BlueHash-128 及完整摘要的前缀均不相同。
+    ///
+    /// # Panics
+    /// 当 `length` 为 0 或超过摘要长度时 panic。
+    pub fn finalize_truncated(&mut self, length: usize) -> Vec<u8> {
+        let digest_length = self.digest_size.digest_length();
+        assert!(
+            length > 0 && length <= digest_length,
+            "truncated length must be between 1 and {} bytes",
+            digest_length
+        );
+        let mut result = vec![0u8; length];
+        self.finalize_truncated_into(&mut result);
+        result
+    }
+
+    /// 截断输出写入 `out`（截断长度即 `out.len()`，不分配堆内存），调用方负责检查长度
+    pub(crate) fn finalize_truncated_into(&mut self, out: &mut [u8]) {
+        #[cfg(feature = "keccak")]
+        if self.is_keccak() {
+            self.keccak_finish(keccak::TRUNCATED_SUFFIX, &[out.len() as u8]);
+            self.write_output(out);
+            return;
+        }
+        self.absorb_rounds();
+        self.separate_domain(TRUNCATION_DOMAIN ^ out.len() as u64);
+        self.final_mix();
+        self.write_output(out);
+    }
+
+    /// 域分离：将域常量按字位置旋转后异或进每个状态字，确保最终混合后所有输出字都受影响
+    fn separate_domain(&mut self, domain: u64) {
+        for (i, word) in self.words_mut().iter_mut().enumerate() {
+            *word ^= domain.rotate_left(i as u32);
+        }
+    }
+
+    /// 按大端序从状态中取出摘要字节
+    fn output_bytes(&self) -> Vec<u8> {
+        let mut result = vec![0u8; self.digest_size.digest_length()];
+        self.write_output(&mut result);
+        result
+    }
+
+    /// 将摘要字节写入调用方提供的缓冲区（长度不超过摘要长度）
+    ///
+    /// keccak 后端按 FIPS 202 的约定以小端序输出各个字。
+    fn write_output(&self, out: &mut [u8]) {
+        let state_size = self.digest_size.state_size();
+        let keccak = self.is_keccak();
+        for (i, chunk) in out.chunks_mut(8).enumerate() {
+            let idx = i % state_size;
+            let bytes = if keccak {
+                self.state[idx].to_le_bytes()
+            } else {
+                self.state[idx].to_be_bytes()
+            };
+            // 采用恒定时间复制（无早期返回）
+            for (j, b) in bytes.iter().enumerate().take(chunk.len()) {
+                chunk[j] = *b;
+            }
+        }
+    }
+
+    /// 结束哈希并将摘要写入 `out`，不分配堆内存
+    ///
+    /// # Panics
+    /// 当 `out` 的长度不等于摘要长度时 panic。
+    pub fn finalize_into(&mut self, out: &mut [u8]) {
+        assert_eq!(
+            out.len(),
+            self.digest_size.digest_length(),
+            "output buffer must match the digest length"
+        );
+        #[cfg(feature = "keccak")]
+        if self.is_keccak() {
+            self.keccak_finish(keccak::HASH_SUFFIX, &[]);
+            self.write_output(out);
+            return;
+        }
+        self.absorb_rounds();
+        self.final_mix();
+        self.write_output(out);
+    }
+
+    /// 以另一个哈希器的状态覆盖当前哈希器（不分配堆内存）
+    pub(crate) fn restore_from(&mut self, other: &Self) {
+        self.state = other.state;
+        self.round_count = other.round_count;
+        self.permutation = other.permutation;
+        self.backend = other.backend;
+        self.digest_size = other.digest_size;
+        self.total_len = other.total_len;
+        self.input_sum = other.input_sum;
+        self.block_count = other.block_count;
+        self.block_buffer = other.block_buffer;
+        self.buffered = other.buffered;
+    }
+
     /// 最终混合：将总长度信息引入状态，并进行额外轮次置换（所有循环均采用固定步长以实现恒定时间操作）
-    fn final_mix(&mut self, extra_data: &[u8]) {
+    fn final_mix(&mut self) {
+        self.final_mix_observed(&mut |_, _, _| {});
+    }
+
+    /// 与 `final_mix` 相同，每轮结束后以（轮次，轮常量，状态）调用 `observe`
+    fn final_mix_observed<F: FnMut(usize, u64, &[u64])>(&mut self, observe: &mut F) {
         // 在状态中混入总长度（注意转换为 u64 后执行恒定时间 XOR）
         self.state[0] ^= self.total_len.wrapping_mul(8) as u64;
         self.state[0] ^= 0x80;
-        let padded = self.pad(extra_data);
+        let padded = self.pad();
+        let digest_length = self.digest_size.digest_length();
         for round in self.round_count..(self.round_count + 4) {
-            self.state = permute_core(
-                &self.state,
-                &padded,
-                round,
-                self.digest_size.state_size(),
-                self.digest_size,
-            );
+            let constant = generate_constants(round, &padded, digest_length);
+            let permutation = self.permutation;
+            permutation.mix(self.words_mut(), constant);
+            observe(round, constant, self.words());
         }
     }
 }
@@ -249,56 +990,52 @@ pub trait Digest {
 }
 
 impl Digest for BlueHashCore {
+    /// 累计输入超过 `MAX_INPUT_LEN` 时 panic（而不是让长度编码回绕产生碰撞）；
+    /// 需要可恢复错误时使用 `BlueHashCore::try_update`。
     fn update(&mut self, data: &[u8]) {
-        self.total_len = self.total_len.wrapping_add(data.len() as u128);
-        self.input_buffer.extend_from_slice(data);
-        let state_size = self.digest_size.state_size();
-        for (i, chunk) in data.chunks(8).enumerate() {
-            let block = chunk
-                .iter()
-                .fold(0u64, |acc, &byte| (acc << 8) | byte as u64);
-            let idx = i % state_size;
-            // 使用固定步长旋转以实现恒定时间操作
-            self.state[idx] ^= block.rotate_left(((i as u32).wrapping_mul(7)) % 64);
-        }
-        for round in 0..self.round_count {
-            self.state = permute_core(
-                &self.state,
-                data,
-                round,
-                self.digest_size.state_size(),
-                self.digest_size,
-            );
+        if let Err(err) = self.try_update(data) {
+            panic!("{}", err);
         }
     }
 
     fn finalize(&mut self) -> Vec<u8> {
-        self.final_mix(&[]);
-        let digest_length = self.digest_size.digest_length();
-        let state_size = self.digest_size.state_size();
-        let mut result = vec![0u8; digest_length];
-        for (i, chunk) in result.chunks_mut(8).enumerate() {
-            let idx = i % state_size;
-            let bytes = self.state[idx].to_be_bytes();
-            // 采用恒定时间复制（无早期返回）
-            for (j, b) in bytes.iter().enumerate().take(chunk.len()) {
-                chunk[j] = *b;
-            }
+        #[cfg(feature = "keccak")]
+        if self.is_keccak() {
+            self.keccak_finish(keccak::HASH_SUFFIX, &[]);
+            return self.output_bytes();
         }
-        result
+        self.absorb_rounds();
+        self.final_mix();
+        self.output_bytes()
     }
 
     fn reset(&mut self) {
-        // 重新使用固定 IV 初始化状态，采用恒定时间清零输入缓冲区
-        self.state = BlueHashCore::fixed_iv(self.digest_size);
+        // 重新使用固定 IV 初始化状态（keccak 后端为全零状态），采用恒定时间清零尾部缓冲区
+        self.state = [0u64; MAX_STATE_WORDS];
+        if !self.is_keccak() {
+            let iv = Self::fixed_iv(self.digest_size);
+            self.state[..iv.len()].copy_from_slice(iv);
+        }
         self.total_len = 0;
-        for b in self.input_buffer.iter_mut() {
+        self.input_sum = 0;
+        self.block_count = 0;
+        for b in self.block_buffer.iter_mut() {
             *b = 0;
         }
-        self.input_buffer.clear();
+        self.buffered = 0;
     }
 }
 
+// 编译期保证公开类型满足 Send + Sync，防止后续改动意外引入线程绑定状态
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<BlueHashCore>();
+    assert_send_sync::<DigestOutput>();
+    #[cfg(feature = "std")]
+    assert_send_sync::<HashReader<std::io::Empty>>();
+    assert_send_sync::<XofReader>();
+};
+
 impl fmt::Display for BlueHashCore {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "BlueHash(DigestSize: {:?})", self.digest_size)
@@ -320,7 +1057,7 @@ pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::constants::{generate_constants, SBOX};
+    use crate::constants::generate_constants;
     use crate::noise::generate_lwe_noise;
 
     #[test]
@@ -358,6 +1095,219 @@ mod tests {
         assert!(constant_time_eq(&result, &expected));
     }
 
+    #[test]
+    fn test_digest_vectors() {
+        // 摘要计算只使用整数运算；以下向量在所有目标上必须一致，
+        // `cargo xtask portability` 在 wasm32 与大端目标上运行同样的已知答案测试
+        let hex = |digest_size| {
+            let mut hasher = BlueHashCore::new(digest_size);
+            hasher.update(b"abc");
+            crate::utils::to_hex_string(&hasher.finalize())
+        };
+        assert_eq!(hex(DigestSize::Bit128), "45c15fc63d6ae3f7a64c8c5bc77c0532");
+        assert_eq!(
+            hex(DigestSize::Bit256),
+            "6b67de16acf3c4c6e4e63a0184b69a6ad8acf775543c4a563b8f77bf6312a12a"
+        );
+        assert_eq!(
+            hex(DigestSize::Bit512),
+            "78933ff60d9dad65511ce1b3a5d70e1c768f74ec5ccea32b226bf39523138900\
+             1f6e01ed4b9c7aba013db09c11fa56e716bea21101a83fe621c73b1c357f2795"
+        );
+    }
+
+    #[test]
+    fn test_updates_only_write_rate() {
+        for digest_size in [DigestSize::Bit128, DigestSize::Bit256, DigestSize::Bit512] {
+            assert_eq!(
+                digest_size.capacity_bits(),
+                2 * 8 * digest_size.digest_length()
+            );
+            let rate_words = digest_size.rate_words();
+            let mut hasher = BlueHashCore::new(digest_size);
+            let iv = hasher.words().to_vec();
+            // 差一个分块写满速率部分，此时尚未执行中间置换
+            hasher.update(&vec![0xffu8; digest_size.rate_bytes() - BLOCK_LEN]);
+            assert_eq!(hasher.words()[rate_words - 1..], iv[rate_words - 1..]);
+            assert!(hasher.words()[..rate_words - 1]
+                .iter()
+                .zip(&iv)
+                .all(|(a, b)| a != b));
+            hasher.update(&[0xffu8; BLOCK_LEN]);
+            assert_ne!(hasher.words()[rate_words..], iv[rate_words..]);
+        }
+    }
+
+    #[test]
+    fn test_core_apis_do_not_panic() {
+        let mut generator = crate::DataGenerator::new(1171);
+        let mut data = [0u8; 600];
+        generator.fill(&mut data);
+        for (i, digest_size) in [DigestSize::Bit128, DigestSize::Bit256, DigestSize::Bit512]
+            .into_iter()
+            .enumerate()
+        {
+            // 按伪随机长度切分输入，流式结果须与一次性输入一致
+            let mut split = BlueHashCore::new(digest_size);
+            let mut rest = &data[..];
+            while !rest.is_empty() {
+                let take = (rest[0] as usize % 97).min(rest.len());
+                split.update(&rest[..take]);
+                rest = &rest[take..];
+            }
+            let mut whole = BlueHashCore::new(digest_size);
+            whole.update(&data);
+            assert_eq!(split.finalize(), whole.finalize());
+
+            // 导入任意字节得到的哈希器同样可以安全地吸收和结束
+            let mut exported = BlueHashCore::new(digest_size).export_state();
+            exported[22..47].copy_from_slice(&data[i * 25..i * 25 + 25]);
+            exported[38] = data[i] % 8;
+            let mut imported = BlueHashCore::import_state(&exported).unwrap();
+            imported.update(&data[..13]);
+            let mut reader = imported.finalize_xof();
+            reader.seek(u64::MAX);
+            let _: [u8; 300] = reader.squeeze_array();
+            assert_eq!(reader.position(), 299);
+        }
+    }
+
+    #[test]
+    fn test_padding_encodes_length() {
+        // 完整证明见 `proofs::padding_is_injective`（`cargo kani`）
+        let pad_for = |total_len| {
+            let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+            hasher.total_len = total_len;
+            hasher.pad()
+        };
+        let lengths = [0, 1, 8, 1 << 64, MAX_INPUT_LEN - 1, MAX_INPUT_LEN];
+        for (i, &a) in lengths.iter().enumerate() {
+            assert_eq!(pad_for(a)[0], 0x80);
+            for &b in &lengths[i + 1..] {
+                assert_ne!(pad_for(a), pad_for(b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_updates() {
+        let data: Vec<u8> = (0..100u8).collect();
+        let mut whole = BlueHashCore::new(DigestSize::Bit128);
+        whole.update(&data);
+        let mut split = BlueHashCore::new(DigestSize::Bit128);
+        for part in [&data[..3], &data[3..4], &data[4..21], &data[21..]] {
+            split.update(part);
+        }
+        assert_eq!(whole.finalize(), split.finalize());
+    }
+
+    #[test]
+    fn test_hasher_migrates_across_threads() {
+        let mut expected = BlueHashCore::new(DigestSize::Bit256);
+        expected.update(b"first half, ");
+        expected.update(b"second half");
+        let expected = expected.finalize();
+
+        let hasher = BlueHashCore::new(DigestSize::Bit256);
+        let hasher = std::thread::spawn(move || {
+            let mut hasher = hasher;
+            hasher.update(b"first half, ");
+            hasher
+        })
+        .join()
+        .unwrap();
+        let result = std::thread::spawn(move || {
+            let mut hasher = hasher;
+            hasher.update(b"second half");
+            hasher.finalize()
+        })
+        .join()
+        .unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_truncation_is_domain_separated() {
+        let data = b"truncate me";
+        let mut full256 = BlueHashCore::new(DigestSize::Bit256);
+        full256.update(data);
+        let full256 = full256.finalize();
+        let mut full128 = BlueHashCore::new(DigestSize::Bit128);
+        full128.update(data);
+        let full128 = full128.finalize();
+        let mut truncated = BlueHashCore::new(DigestSize::Bit256);
+        truncated.update(data);
+        let truncated = truncated.finalize_truncated(16);
+
+        assert_eq!(truncated.len(), 16);
+        assert_ne!(truncated, full128);
+        assert_ne!(truncated[..], full256[..16]);
+
+        let mut other = BlueHashCore::new(DigestSize::Bit256);
+        other.update(data);
+        assert_ne!(other.finalize_truncated(20)[..16], truncated[..]);
+    }
+
+    #[test]
+    fn test_static_iv_tables() {
+        assert_eq!(IV_256[..25], IV_128[..]);
+        assert_eq!(IV_512[..25], IV_128[..]);
+        assert_eq!(IV_512[39], 0x0123456789ABCDEF);
+        let hasher = BlueHashCore::new(DigestSize::Bit512);
+        assert_eq!(hasher.words(), &IV_512[..]);
+    }
+
+    #[test]
+    fn test_digest_independent_of_thread_count() {
+        let state: Vec<u64> = (0..32u64)
+            .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+            .collect();
+        let run = |threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                let permuted = permute_core(&state, b"threads", 3, 32, DigestSize::Bit256);
+                let mut hasher = BlueHashCore::new(DigestSize::Bit256);
+                hasher.update(b"deterministic parallelism");
+                (permuted, hasher.finalize())
+            })
+        };
+        let expected = run(1);
+        assert_eq!(run(2), expected);
+        assert_eq!(run(16), expected);
+    }
+
+    #[test]
+    fn test_total_length_limit() {
+        let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+        hasher.total_len = MAX_INPUT_LEN - 1;
+        let before = hasher.clone().finalize();
+        assert_eq!(hasher.try_update(b"ab"), Err(LengthOverflow));
+        // 失败的吸收不修改状态
+        assert_eq!(hasher.clone().finalize(), before);
+        assert_eq!(hasher.try_update(b"a"), Ok(()));
+        assert_eq!(hasher.total_len, MAX_INPUT_LEN);
+        assert_eq!(hasher.try_update(b""), Ok(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "total input length exceeds")]
+    fn test_update_panics_on_length_overflow() {
+        let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+        hasher.total_len = MAX_INPUT_LEN;
+        hasher.update(b"x");
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let caps = capabilities();
+        assert_eq!(caps.std, cfg!(feature = "std"));
+        assert_eq!(caps.parallel, cfg!(feature = "parallel"));
+        assert!(!caps.simd);
+    }
+
     #[test]
     fn test_generate_constants() {
         let data: Vec<u8> = vec![0x12, 0x34, 0x56, 0x78];
@@ -373,19 +1323,20 @@ mod tests {
     }
 }
 
-// 辅助函数：将字节转换为 16 进制字符串
-fn to_hex_string(bytes: &[u8]) -> String {
-    let mut hex = String::new();
-    for byte in bytes {
-        write!(&mut hex, "{:02x}", byte).unwrap();
-    }
-    hex
-}
+#[cfg(kani)]
+mod proofs {
+    use super::*;
 
-fn main() {
-    let test_data = "金融级安全测试".as_bytes();
-    let mut hasher = BlueHashCore::new(DigestSize::Bit256);
-    hasher.update(test_data);
-    let result = hasher.finalize();
-    println!("BlueHash256 Result: {}", to_hex_string(&result));
+    /// Different message lengths within `MAX_INPUT_LEN` always pad differently.
+    #[kani::proof]
+    fn padding_is_injective() {
+        let a: u128 = kani::any();
+        let b: u128 = kani::any();
+        kani::assume(a <= MAX_INPUT_LEN && b <= MAX_INPUT_LEN && a != b);
+        let mut hasher_a = BlueHashCore::new(DigestSize::Bit128);
+        hasher_a.total_len = a;
+        let mut hasher_b = BlueHashCore::new(DigestSize::Bit128);
+        hasher_b.total_len = b;
+        assert!(hasher_a.pad() != hasher_b.pad());
+    }
 }
@@ -0,0 +1,241 @@
+//! Message authentication codes with a tag-length policy.
+//!
+//! `Mac` wraps the keyed mode of `BlueHashCore` and verifies tags in
+//! constant time. Truncated tags are computed with `finalize_truncated`, so a
+//! tag of a given length is domain-separated from every other length, and
+//! verification refuses tags shorter than a caller-chosen minimum (never
+//! below `MIN_TAG_LEN`), so a forged one-byte tag cannot be accepted.
+//!
+//! `StreamMac` authenticates a long-lived stream with one tag per frame,
+//! ratcheting its key after every tag.
+
+use crate::{constant_time_eq, BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Shortest tag length in bytes that `Mac::verify_truncated` ever accepts.
+pub const MIN_TAG_LEN: usize = 8;
+
+/// Keyed BlueHash MAC. `Debug` shows only the digest size, since the keyed
+/// state is equivalent to the key.
+#[derive(Clone)]
+pub struct Mac {
+    template: BlueHashCore,
+    hasher: BlueHashCore,
+}
+
+impl Mac {
+    /// Creates a MAC; the key is absorbed once and kept across `reset`.
+    pub fn new(digest_size: DigestSize, key: &[u8]) -> Self {
+        Self::from_hasher(BlueHashCore::new_keyed(digest_size, key))
+    }
+
+    /// Creates a MAC from an already keyed hasher, e.g. one built with
+    /// `BlueHashBuilder::key` and a non-default profile or backend.
+    pub fn from_hasher(template: BlueHashCore) -> Self {
+        Self {
+            hasher: template.clone(),
+            template,
+        }
+    }
+
+    pub fn digest_size(&self) -> DigestSize {
+        self.template.digest_size
+    }
+
+    /// Absorbs message data.
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+    }
+
+    /// Absorbs a length-prefixed field, as `BlueHashCore::update_framed` does.
+    pub fn update_framed(&mut self, field: &[u8]) {
+        self.hasher.update_framed(field);
+    }
+
+    /// Discards the absorbed message but keeps the key.
+    pub fn reset(&mut self) {
+        self.hasher.restore_from(&self.template);
+    }
+
+    /// Returns the full-length tag.
+    pub fn finalize(mut self) -> Vec<u8> {
+        self.hasher.finalize()
+    }
+
+    /// Returns a tag truncated to `length` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `length` is 0 or longer than the digest.
+    pub fn finalize_truncated(mut self, length: usize) -> Vec<u8> {
+        self.hasher.finalize_truncated(length)
+    }
+
+    /// Verifies a full-length tag in constant time.
+    pub fn verify(self, tag: &[u8]) -> bool {
+        if tag.len() != self.digest_size().digest_length() {
+            return false;
+        }
+        constant_time_eq(&self.finalize(), tag)
+    }
+
+    /// Verifies a possibly truncated tag in constant time.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - The received tag, produced by `finalize_truncated(tag.len())`.
+    /// * `min_len` - The shortest tag length the caller accepts; values below
+    ///   `MIN_TAG_LEN` are raised to `MIN_TAG_LEN`.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the tag is shorter than the minimum, longer than the digest,
+    /// or does not match.
+    pub fn verify_truncated(self, tag: &[u8], min_len: usize) -> bool {
+        let min_len = min_len.max(MIN_TAG_LEN);
+        if tag.len() < min_len || tag.len() > self.digest_size().digest_length() {
+            return false;
+        }
+        let expected = self.finalize_truncated(tag.len());
+        constant_time_eq(&expected, tag)
+    }
+}
+
+impl fmt::Debug for Mac {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mac")
+            .field("digest_size", &self.digest_size())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Marker absorbed before computing a frame tag.
+const TAG_MARKER: u8 = 0x00;
+/// Marker absorbed before deriving the next chaining key.
+const RATCHET_MARKER: u8 = 0x01;
+
+/// Keyed MAC over a stream that emits a tag per frame.
+///
+/// Each tag authenticates everything absorbed since the stream started, not
+/// just the current frame. After a tag the state is ratcheted: the hasher is
+/// rekeyed with a key derived from the whole transcript, so earlier frame
+/// boundaries are bound into later tags and a captured state cannot recompute
+/// earlier tags.
+#[derive(Clone)]
+pub struct StreamMac {
+    digest_size: DigestSize,
+    hasher: BlueHashCore,
+    frames: u64,
+}
+
+impl StreamMac {
+    pub fn new(digest_size: DigestSize, key: &[u8]) -> Self {
+        Self {
+            digest_size,
+            hasher: BlueHashCore::new_keyed(digest_size, key),
+            frames: 0,
+        }
+    }
+
+    /// Number of tags emitted so far.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Absorbs stream data.
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+    }
+
+    /// Ends the current frame, returning its tag and ratcheting the state.
+    pub fn emit_tag(&mut self) -> Vec<u8> {
+        self.hasher.update(&self.frames.to_be_bytes());
+        let mut tagger = self.hasher.clone();
+        tagger.update(&[TAG_MARKER]);
+        let tag = tagger.finalize();
+        self.hasher.update(&[RATCHET_MARKER]);
+        let next_key = self.hasher.finalize();
+        self.hasher = BlueHashCore::new_keyed(self.digest_size, &next_key);
+        self.frames += 1;
+        tag
+    }
+
+    /// Ends the current frame and checks its tag in constant time.
+    ///
+    /// The state advances whether or not the tag matches; a receiver should
+    /// drop the stream after a failure.
+    pub fn verify_tag(&mut self, tag: &[u8]) -> bool {
+        constant_time_eq(&self.emit_tag(), tag)
+    }
+}
+
+impl fmt::Debug for StreamMac {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamMac")
+            .field("digest_size", &self.digest_size)
+            .field("frames", &self.frames)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mac_verify_and_reset_keeps_key() {
+        let mut mac = Mac::new(DigestSize::Bit128, b"key");
+        mac.update(b"discarded");
+        mac.reset();
+        mac.update(b"message");
+        let tag = mac.clone().finalize();
+        let mut keyed = BlueHashCore::new_keyed(DigestSize::Bit128, b"key");
+        keyed.update(b"message");
+        assert_eq!(tag, keyed.finalize());
+        assert!(mac.clone().verify(&tag));
+        assert_eq!(format!("{:?}", mac), "Mac { digest_size: Bit128, .. }");
+        assert!(!mac.clone().verify(&tag[..15]));
+        let mut forged = tag.clone();
+        forged[0] ^= 1;
+        assert!(!mac.verify(&forged));
+    }
+
+    #[test]
+    fn test_verify_truncated_enforces_min_len() {
+        let mut mac = Mac::new(DigestSize::Bit256, b"key");
+        mac.update(b"firmware image");
+        let tag = mac.clone().finalize_truncated(12);
+        assert!(mac.clone().verify_truncated(&tag, 12));
+        assert!(!mac.clone().verify_truncated(&tag, 16));
+        // Even if the caller allows 1-byte tags, MIN_TAG_LEN still applies.
+        let short = mac.clone().finalize_truncated(1);
+        assert!(!mac.clone().verify_truncated(&short, 1));
+        // Truncated tags are domain-separated, so a prefix of the full tag fails.
+        let full = mac.clone().finalize();
+        assert!(!mac.verify_truncated(&full[..12], 12));
+    }
+
+    #[test]
+    fn test_stream_mac_chains_frames() {
+        let mut sender = StreamMac::new(DigestSize::Bit128, b"session key");
+        let mut receiver = sender.clone();
+        sender.update(b"frame one");
+        let first = sender.emit_tag();
+        sender.update(b"frame two");
+        let second = sender.emit_tag();
+
+        receiver.update(b"frame one");
+        assert!(receiver.verify_tag(&first));
+        receiver.update(b"frame two");
+        assert!(receiver.verify_tag(&second));
+        assert_eq!(receiver.frames(), 2);
+
+        // The same bytes with a different frame boundary give different tags.
+        let mut resplit = StreamMac::new(DigestSize::Bit128, b"session key");
+        resplit.update(b"frame onefr");
+        resplit.emit_tag();
+        resplit.update(b"ame two");
+        assert_ne!(resplit.emit_tag(), second);
+    }
+}
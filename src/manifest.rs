@@ -0,0 +1,333 @@
+//! Checksum manifests for directory trees.
+//!
+//! A `Manifest` maps relative file paths to their BlueHash digests and reads
+//! and writes the familiar `b2sum` text format, one `<hex>  <path>` line per
+//! file; the digest length selects the variant. `verify_tree` re-hashes a
+//! tree against a manifest and reports every file separately, so backup
+//! tooling can tell a corrupted file from a deleted one, a stray file or an
+//! unreadable one. With the `parallel` feature the files are hashed on the
+//! rayon thread pool; the report is the same either way.
+
+use crate::output::{DigestOutput, ParseError};
+use crate::reader::hash_file;
+use crate::DigestSize;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Error returned when a manifest line cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestError {
+    /// The line (1-based) has no `"  "` between digest and path.
+    MissingSeparator(usize),
+    /// The digest on the line (1-based) is not a valid BlueHash digest.
+    InvalidDigest(usize, ParseError),
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::MissingSeparator(line) => {
+                write!(f, "line {}: expected `<digest>  <path>`", line)
+            }
+            ManifestError::InvalidDigest(line, err) => write!(f, "line {}: {}", line, err),
+        }
+    }
+}
+
+impl core::error::Error for ManifestError {}
+
+/// Expected digests of the files in a tree, keyed by path relative to its root.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    entries: BTreeMap<PathBuf, DigestOutput>,
+}
+
+impl Manifest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `digest` as the expected digest of `path`.
+    pub fn insert<P: Into<PathBuf>>(&mut self, path: P, digest: DigestOutput) {
+        self.entries.insert(path.into(), digest);
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&DigestOutput> {
+        self.entries.get(path)
+    }
+
+    /// Iterates over the entries in path order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &DigestOutput)> {
+        self.entries
+            .iter()
+            .map(|(path, digest)| (path.as_path(), digest))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Parses `<hex>  <path>` lines; blank lines are skipped.
+    pub fn parse(text: &str) -> Result<Self, ManifestError> {
+        let mut manifest = Self::new();
+        for (index, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (hex, path) = line
+                .split_once("  ")
+                .ok_or(ManifestError::MissingSeparator(index + 1))?;
+            let digest = DigestOutput::from_hex(hex)
+                .map_err(|err| ManifestError::InvalidDigest(index + 1, err))?;
+            manifest.insert(path, digest);
+        }
+        Ok(manifest)
+    }
+
+    /// Hashes every regular file below `root` with the given variant.
+    pub fn from_tree(root: &Path, digest_size: DigestSize) -> io::Result<Self> {
+        let mut manifest = Self::new();
+        let mut failed = Vec::new();
+        let files = walk(root, &mut failed)?;
+        if let Some((_, err)) = failed.into_iter().next() {
+            return Err(err);
+        }
+        for path in files {
+            let digest = hash_file(root.join(&path), digest_size)?;
+            let digest = DigestOutput::from_bytes(&digest).expect("BlueHash digest length");
+            manifest.insert(path, digest);
+        }
+        Ok(manifest)
+    }
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (path, digest) in self.iter() {
+            writeln!(f, "{}  {}", digest, path.display())?;
+        }
+        Ok(())
+    }
+}
+
+impl core::str::FromStr for Manifest {
+    type Err = ManifestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// The outcome of verifying one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileStatus {
+    /// The file matches its manifest digest.
+    Ok,
+    /// The file's digest differs from the manifest.
+    Mismatch { actual: DigestOutput },
+    /// The manifest lists the file but it does not exist.
+    Missing,
+    /// The file exists but the manifest does not list it.
+    Extra,
+    /// The file or directory could not be read.
+    IoError {
+        kind: io::ErrorKind,
+        message: String,
+    },
+}
+
+impl FileStatus {
+    fn from_io(err: &io::Error) -> Self {
+        FileStatus::IoError {
+            kind: err.kind(),
+            message: err.to_string(),
+        }
+    }
+}
+
+/// The outcome for one path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReport {
+    /// The path relative to the verified root.
+    pub path: PathBuf,
+    pub status: FileStatus,
+}
+
+/// Per-file results of `verify_tree`, sorted by path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeReport {
+    pub files: Vec<FileReport>,
+}
+
+impl TreeReport {
+    /// Returns true if every file is `FileStatus::Ok`.
+    pub fn is_ok(&self) -> bool {
+        self.problems().next().is_none()
+    }
+
+    /// Iterates over the files whose status is not `FileStatus::Ok`.
+    pub fn problems(&self) -> impl Iterator<Item = &FileReport> {
+        self.files
+            .iter()
+            .filter(|report| report.status != FileStatus::Ok)
+    }
+}
+
+/// Re-hashes the files below `root` and compares them with `manifest`.
+///
+/// # Arguments
+///
+/// * `manifest` - The expected digests, keyed by path relative to `root`.
+/// * `root` - The directory to verify.
+///
+/// # Returns
+///
+/// One `FileReport` per manifest entry and per unlisted regular file, or an
+/// error if `root` itself cannot be listed. Unreadable subdirectories are
+/// reported as `FileStatus::IoError` entries.
+pub fn verify_tree(manifest: &Manifest, root: &Path) -> io::Result<TreeReport> {
+    let mut failed = Vec::new();
+    let found = walk(root, &mut failed)?;
+
+    #[cfg(feature = "parallel")]
+    let entries = manifest.entries.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let entries = manifest.entries.iter();
+    let mut files: Vec<FileReport> = entries
+        .map(|(path, expected)| FileReport {
+            path: path.clone(),
+            status: check_file(&root.join(path), expected),
+        })
+        .collect();
+
+    files.extend(
+        found
+            .into_iter()
+            .filter(|path| !manifest.entries.contains_key(path))
+            .map(|path| FileReport {
+                path,
+                status: FileStatus::Extra,
+            }),
+    );
+    files.extend(failed.iter().map(|(path, err)| FileReport {
+        path: path.clone(),
+        status: FileStatus::from_io(err),
+    }));
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(TreeReport { files })
+}
+
+fn check_file(path: &Path, expected: &DigestOutput) -> FileStatus {
+    match hash_file(path, expected.digest_size()) {
+        Ok(digest) if digest == expected.as_bytes() => FileStatus::Ok,
+        Ok(digest) => FileStatus::Mismatch {
+            actual: DigestOutput::from_bytes(&digest).expect("BlueHash digest length"),
+        },
+        Err(err) if err.kind() == io::ErrorKind::NotFound => FileStatus::Missing,
+        Err(err) => FileStatus::from_io(&err),
+    }
+}
+
+/// Lists the regular files below `root` relative to it, without following
+/// symlinks. Subdirectories that cannot be read are collected in `failed`.
+pub(crate) fn walk(
+    root: &Path,
+    failed: &mut Vec<(PathBuf, io::Error)>,
+) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = Vec::from([PathBuf::new()]);
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(root.join(&dir)) {
+            Ok(entries) => entries,
+            Err(err) if dir.as_os_str().is_empty() => return Err(err),
+            Err(err) => {
+                failed.push((dir, err));
+                continue;
+            }
+        };
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    failed.push((dir.clone(), err));
+                    continue;
+                }
+            };
+            let path = dir.join(entry.file_name());
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() => pending.push(path),
+                Ok(kind) if kind.is_file() => files.push(path),
+                Ok(_) => {}
+                Err(err) => failed.push((path, err)),
+            }
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_text_round_trip() {
+        let mut manifest = Manifest::new();
+        let digest = DigestOutput::from_bytes(&[7u8; 32]).unwrap();
+        manifest.insert("docs/read me.txt", digest.clone());
+        let text = manifest.to_string();
+        assert_eq!(text, format!("{}  docs/read me.txt\n", digest));
+        assert_eq!(Manifest::parse(&text).unwrap(), manifest);
+        assert_eq!(
+            Manifest::parse("\n00 x"),
+            Err(ManifestError::MissingSeparator(2))
+        );
+        assert!(matches!(
+            Manifest::parse("0011  x"),
+            Err(ManifestError::InvalidDigest(1, _))
+        ));
+    }
+
+    #[test]
+    fn test_verify_tree_reports_each_file() {
+        let root =
+            std::env::temp_dir().join(format!("bluehash_verify_tree_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("same.txt"), b"unchanged").unwrap();
+        fs::write(root.join("sub/changed.txt"), b"before").unwrap();
+        fs::write(root.join("gone.txt"), b"deleted later").unwrap();
+        let manifest = Manifest::from_tree(&root, DigestSize::Bit128).unwrap();
+        assert_eq!(manifest.len(), 3);
+
+        fs::write(root.join("sub/changed.txt"), b"after").unwrap();
+        fs::remove_file(root.join("gone.txt")).unwrap();
+        fs::write(root.join("new.txt"), b"stray").unwrap();
+        let report = verify_tree(&manifest, &root).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        let statuses: Vec<(&str, &FileStatus)> = report
+            .files
+            .iter()
+            .map(|file| (file.path.to_str().unwrap(), &file.status))
+            .collect();
+        assert_eq!(statuses.len(), 4);
+        assert_eq!(statuses[0], ("gone.txt", &FileStatus::Missing));
+        assert_eq!(statuses[1], ("new.txt", &FileStatus::Extra));
+        assert_eq!(statuses[2], ("same.txt", &FileStatus::Ok));
+        assert_eq!(statuses[3].0, "sub/changed.txt");
+        assert!(matches!(statuses[3].1, FileStatus::Mismatch { .. }));
+        assert!(!report.is_ok());
+        assert_eq!(report.problems().count(), 3);
+    }
+}
@@ -0,0 +1,195 @@
+//! Merkle trees and distributed digest combination.
+//!
+//! Hashing follows RFC 6962 (section 2.1) with BlueHash as the hash function:
+//!
+//! ```text
+//! leaf(d)    = BlueHash(0x00 || d)
+//! node(l, r) = BlueHash(0x01 || l || r)
+//! MTH({})    = BlueHash("")
+//! MTH(D[n])  = node(MTH(D[0:k]), MTH(D[k:n])), k = largest power of two < n
+//! ```
+//!
+//! `combine_subtree_digests` joins digests computed independently on
+//! different nodes (for example the `root` of each shard) into one root:
+//!
+//! * `Layout::Binary` treats the shard digests as subtree hashes and joins
+//!   them with `node` in the RFC 6962 shape. If every shard except the last
+//!   holds the same power-of-two number of leaves and the last holds no more,
+//!   the result equals the root over all leaves at once.
+//! * `Layout::Flat` hashes all shard digests under one node:
+//!   `BlueHash(0x02 || count || d1 || ... || dn)`, with an 8-byte big-endian
+//!   count.
+
+use crate::output::DigestOutput;
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Prefix of leaf hashes.
+pub const LEAF_PREFIX: u8 = 0x00;
+/// Prefix of interior node hashes.
+pub const NODE_PREFIX: u8 = 0x01;
+/// Prefix of flat combinations of subtree digests.
+pub const FLAT_PREFIX: u8 = 0x02;
+
+/// How subtree digests are combined into a root.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Layout {
+    /// RFC 6962-shaped binary tree over the subtree digests.
+    Binary,
+    /// One node over all subtree digests.
+    Flat,
+}
+
+/// Error returned by `combine_subtree_digests`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CombineError {
+    /// No digests were given.
+    Empty,
+    /// The digests do not all have the same digest size.
+    MixedDigestSizes,
+}
+
+impl fmt::Display for CombineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CombineError::Empty => write!(f, "no subtree digests"),
+            CombineError::MixedDigestSizes => write!(f, "subtree digests have different sizes"),
+        }
+    }
+}
+
+impl core::error::Error for CombineError {}
+
+/// Hashes a leaf: `BlueHash(0x00 || data)`.
+pub fn hash_leaf(digest_size: DigestSize, data: &[u8]) -> Vec<u8> {
+    let mut hasher = BlueHashCore::new(digest_size);
+    hasher.update(&[LEAF_PREFIX]);
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// Hashes an interior node: `BlueHash(0x01 || left || right)`.
+pub fn hash_node(digest_size: DigestSize, left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut hasher = BlueHashCore::new(digest_size);
+    hasher.update(&[NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize()
+}
+
+/// Largest power of two strictly less than `n` (`n >= 2`).
+pub(crate) fn split_point(n: usize) -> usize {
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+/// Computes the Merkle tree hash of a list of leaves.
+///
+/// # Arguments
+///
+/// * `digest_size` - The BlueHash variant used for every hash.
+/// * `leaves` - The leaf data, in order.
+///
+/// # Returns
+///
+/// The root hash; an empty list hashes to `BlueHash("")`.
+pub fn root(digest_size: DigestSize, leaves: &[&[u8]]) -> Vec<u8> {
+    match leaves.len() {
+        0 => BlueHashCore::new(digest_size).finalize(),
+        1 => hash_leaf(digest_size, leaves[0]),
+        n => {
+            let k = split_point(n);
+            hash_node(
+                digest_size,
+                &root(digest_size, &leaves[..k]),
+                &root(digest_size, &leaves[k..]),
+            )
+        }
+    }
+}
+
+fn combine_binary(digest_size: DigestSize, digests: &[DigestOutput]) -> Vec<u8> {
+    match digests.len() {
+        1 => digests[0].as_bytes().to_vec(),
+        n => {
+            let k = split_point(n);
+            hash_node(
+                digest_size,
+                &combine_binary(digest_size, &digests[..k]),
+                &combine_binary(digest_size, &digests[k..]),
+            )
+        }
+    }
+}
+
+/// Combines independently computed subtree digests into one root digest.
+///
+/// # Arguments
+///
+/// * `digests` - The shard digests, in shard order.
+/// * `layout` - The combination rule (see the module documentation).
+///
+/// # Returns
+///
+/// The root digest, or an error if `digests` is empty or mixes sizes.
+pub fn combine_subtree_digests(
+    digests: &[DigestOutput],
+    layout: Layout,
+) -> Result<DigestOutput, CombineError> {
+    let digest_size = digests.first().ok_or(CombineError::Empty)?.digest_size();
+    if digests.iter().any(|d| d.digest_size() != digest_size) {
+        return Err(CombineError::MixedDigestSizes);
+    }
+    let root = match layout {
+        Layout::Binary => combine_binary(digest_size, digests),
+        Layout::Flat => {
+            let mut hasher = BlueHashCore::new(digest_size);
+            hasher.update(&[FLAT_PREFIX]);
+            hasher.update(&(digests.len() as u64).to_be_bytes());
+            for digest in digests {
+                hasher.update(digest.as_bytes());
+            }
+            hasher.finalize()
+        }
+    };
+    Ok(DigestOutput::from_bytes(&root).expect("root has the digest length of its inputs"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_combination_matches_single_tree() {
+        let leaves: [&[u8]; 6] = [b"a", b"b", b"c", b"d", b"e", b"f"];
+        let size = DigestSize::Bit128;
+        // Two nodes hash 4 and 2 leaves respectively.
+        let shards = [
+            DigestOutput::from_bytes(&root(size, &leaves[..4])).unwrap(),
+            DigestOutput::from_bytes(&root(size, &leaves[4..])).unwrap(),
+        ];
+        let combined = combine_subtree_digests(&shards, Layout::Binary).unwrap();
+        assert_eq!(combined.as_bytes(), &root(size, &leaves)[..]);
+        let flat = combine_subtree_digests(&shards, Layout::Flat).unwrap();
+        assert_ne!(flat, combined);
+    }
+
+    #[test]
+    fn test_combine_errors() {
+        assert_eq!(
+            combine_subtree_digests(&[], Layout::Flat),
+            Err(CombineError::Empty)
+        );
+        let mixed = [
+            DigestOutput::from_bytes(&[0u8; 16]).unwrap(),
+            DigestOutput::from_bytes(&[0u8; 32]).unwrap(),
+        ];
+        assert_eq!(
+            combine_subtree_digests(&mixed, Layout::Binary),
+            Err(CombineError::MixedDigestSizes)
+        );
+        assert_eq!(split_point(2), 1);
+        assert_eq!(split_point(5), 4);
+        assert_eq!(split_point(8), 4);
+    }
+}
@@ -0,0 +1,122 @@
+//! Workload metrics for hashing (`std` feature).
+//!
+//! `MeteredHasher` wraps a `BlueHashCore` and reports every finalization to a
+//! `HashMetricsSink`: the digest size, the number of bytes absorbed and the
+//! time spent inside `update` and `finalize`. Time between calls (waiting on
+//! I/O, say) is not counted, so the figures describe the hashing itself. A
+//! sink typically bumps a few counters or histograms, e.g. Prometheus
+//! `bluehash_bytes_total` and `bluehash_seconds`; closures taking a
+//! `&HashMetrics` are sinks too.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+use std::time::{Duration, Instant};
+
+/// What one finalized hash reports.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HashMetrics {
+    /// The BlueHash variant.
+    pub digest_size: DigestSize,
+    /// Bytes absorbed since the hasher was created or reset.
+    pub bytes: u128,
+    /// Time spent in `update` and `finalize`.
+    pub elapsed: Duration,
+}
+
+/// Receives the metrics of every finalized `MeteredHasher`.
+///
+/// Takes `&self` so that one sink can be shared, e.g. behind an `Arc`, by
+/// hashers on many threads; implementations use atomics or locks inside.
+pub trait HashMetricsSink {
+    /// Called once per finalization.
+    fn record(&self, metrics: &HashMetrics);
+}
+
+impl<F: Fn(&HashMetrics)> HashMetricsSink for F {
+    fn record(&self, metrics: &HashMetrics) {
+        self(metrics)
+    }
+}
+
+/// A hasher that reports its workload to a `HashMetricsSink` on finalize.
+#[derive(Debug, Clone)]
+pub struct MeteredHasher<S> {
+    hasher: BlueHashCore,
+    sink: S,
+    elapsed: Duration,
+}
+
+impl<S: HashMetricsSink> MeteredHasher<S> {
+    /// Creates a metered hasher with the default configuration.
+    pub fn new(digest_size: DigestSize, sink: S) -> Self {
+        Self::from_hasher(BlueHashCore::new(digest_size), sink)
+    }
+
+    /// Meters an already configured hasher, e.g. a keyed one or one from
+    /// `BlueHashBuilder`. Bytes it has already absorbed count towards the
+    /// reported total; the time spent on them does not.
+    pub fn from_hasher(hasher: BlueHashCore, sink: S) -> Self {
+        Self {
+            hasher,
+            sink,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+}
+
+impl<S: HashMetricsSink> Digest for MeteredHasher<S> {
+    fn update(&mut self, data: &[u8]) {
+        let start = Instant::now();
+        self.hasher.update(data);
+        self.elapsed += start.elapsed();
+    }
+
+    fn finalize(&mut self) -> Vec<u8> {
+        let start = Instant::now();
+        let digest = self.hasher.finalize();
+        self.elapsed += start.elapsed();
+        self.sink.record(&HashMetrics {
+            digest_size: self.hasher.digest_size,
+            bytes: self.hasher.total_len,
+            elapsed: self.elapsed,
+        });
+        digest
+    }
+
+    fn reset(&mut self) {
+        self.hasher.reset();
+        self.elapsed = Duration::ZERO;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_finalize_reports_metrics() {
+        let reports = RefCell::new(Vec::new());
+        let sink = |metrics: &HashMetrics| reports.borrow_mut().push(*metrics);
+        let mut hasher = MeteredHasher::new(DigestSize::Bit256, sink);
+        hasher.update(b"hello ");
+        hasher.update(b"metrics");
+        let digest = hasher.finalize();
+
+        let mut plain = BlueHashCore::new(DigestSize::Bit256);
+        plain.update(b"hello metrics");
+        assert_eq!(digest, plain.finalize());
+
+        hasher.reset();
+        hasher.finalize();
+        let reports = reports.borrow();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].digest_size, DigestSize::Bit256);
+        assert_eq!(reports[0].bytes, 13);
+        assert_eq!(reports[1].bytes, 0);
+    }
+}
@@ -0,0 +1,213 @@
+//! MinHash signatures for set similarity.
+//!
+//! A MinHash sketch keeps, for each of `k` random permutations of the hash
+//! space, the smallest permuted hash of any element inserted. Two sets agree
+//! on a slot with probability equal to their Jaccard similarity, so the
+//! fraction of equal slots estimates it with standard error about
+//! `1 / sqrt(k)`. Each element is hashed once and permuted with
+//!
+//! ```text
+//! x   = BlueHash-128(len(label) || label || element)[..8] mod P
+//! h_i = (a_i * x + b_i) mod P
+//! ```
+//!
+//! where `P = 2^61 - 1`, `label = "bluehash minhash element v1"` and
+//! `(a_i, b_i)` are the two halves of BlueHash-128 over a framed
+//! `"bluehash minhash permutations v1"` label, the seed and `i`. Only
+//! sketches with the same seed and size can be compared or merged; merging
+//! gives the sketch of the union.
+//!
+//! The serialized form is the 8-byte big-endian seed followed by the `k`
+//! slots as 8-byte big-endian integers.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+const ELEMENT_LABEL: &[u8] = b"bluehash minhash element v1";
+const PERMUTATION_LABEL: &[u8] = b"bluehash minhash permutations v1";
+const MERSENNE_61: u64 = (1 << 61) - 1;
+
+/// Error returned when sketches cannot be combined or decoded.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MinHashError {
+    /// The sketches use a different seed or number of permutations.
+    Incompatible,
+    /// A serialized sketch has the wrong length.
+    InvalidLength(usize),
+}
+
+impl fmt::Display for MinHashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MinHashError::Incompatible => {
+                write!(f, "sketches use different seeds or permutation counts")
+            }
+            MinHashError::InvalidLength(len) => write!(f, "invalid sketch length {}", len),
+        }
+    }
+}
+
+impl core::error::Error for MinHashError {}
+
+/// MinHash sketch of a set; see the module docs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinHash {
+    seed: u64,
+    permutations: Vec<(u64, u64)>,
+    mins: Vec<u64>,
+}
+
+impl MinHash {
+    /// Creates the sketch of the empty set.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_perm` - Number of permutations `k`; more give better estimates.
+    /// * `seed` - Selects the permutations; sketches must share it.
+    ///
+    /// # Panics
+    /// Panics if `num_perm` is 0.
+    pub fn new(num_perm: usize, seed: u64) -> Self {
+        assert!(num_perm > 0, "MinHash needs at least one permutation");
+        Self {
+            seed,
+            permutations: permutations(num_perm, seed),
+            mins: vec![u64::MAX; num_perm],
+        }
+    }
+
+    /// Adds an element to the set.
+    pub fn insert(&mut self, element: &[u8]) {
+        let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+        hasher.update_framed(ELEMENT_LABEL);
+        hasher.update(element);
+        let digest = hasher.finalize();
+        let x =
+            u64::from_be_bytes(digest[..8].try_into().expect("BlueHash-128 digest")) % MERSENNE_61;
+        for (min, &(a, b)) in self.mins.iter_mut().zip(&self.permutations) {
+            let h = ((a as u128 * x as u128 + b as u128) % MERSENNE_61 as u128) as u64;
+            *min = (*min).min(h);
+        }
+    }
+
+    /// Number of permutations.
+    pub fn num_perm(&self) -> usize {
+        self.mins.len()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Estimates the Jaccard similarity of the two sets, from 0.0 to 1.0.
+    pub fn jaccard_estimate(&self, other: &MinHash) -> Result<f64, MinHashError> {
+        self.check_compatible(other)?;
+        let equal = self
+            .mins
+            .iter()
+            .zip(&other.mins)
+            .filter(|(a, b)| a == b)
+            .count();
+        Ok(equal as f64 / self.mins.len() as f64)
+    }
+
+    /// Turns this sketch into the sketch of the union of both sets.
+    pub fn merge(&mut self, other: &MinHash) -> Result<(), MinHashError> {
+        self.check_compatible(other)?;
+        for (min, &theirs) in self.mins.iter_mut().zip(&other.mins) {
+            *min = (*min).min(theirs);
+        }
+        Ok(())
+    }
+
+    /// Serializes the seed and slots.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(8 * (1 + self.mins.len()));
+        out.extend_from_slice(&self.seed.to_be_bytes());
+        for min in &self.mins {
+            out.extend_from_slice(&min.to_be_bytes());
+        }
+        out
+    }
+
+    /// Parses a sketch produced by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MinHashError> {
+        if bytes.len() < 16 || !bytes.len().is_multiple_of(8) {
+            return Err(MinHashError::InvalidLength(bytes.len()));
+        }
+        let mut words = bytes
+            .chunks_exact(8)
+            .map(|chunk| u64::from_be_bytes(chunk.try_into().expect("8-byte chunk")));
+        let seed = words.next().expect("checked length");
+        let mins: Vec<u64> = words.collect();
+        Ok(Self {
+            seed,
+            permutations: permutations(mins.len(), seed),
+            mins,
+        })
+    }
+
+    fn check_compatible(&self, other: &MinHash) -> Result<(), MinHashError> {
+        if self.seed != other.seed || self.mins.len() != other.mins.len() {
+            return Err(MinHashError::Incompatible);
+        }
+        Ok(())
+    }
+}
+
+/// Derives `(a_i, b_i)` with `1 <= a_i < P` and `0 <= b_i < P`.
+fn permutations(num_perm: usize, seed: u64) -> Vec<(u64, u64)> {
+    (0..num_perm as u64)
+        .map(|i| {
+            let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+            hasher.update_framed(PERMUTATION_LABEL);
+            hasher.update_u64_be(seed);
+            hasher.update_u64_be(i);
+            let digest = hasher.finalize();
+            let a = u64::from_be_bytes(digest[..8].try_into().expect("8 bytes"));
+            let b = u64::from_be_bytes(digest[8..].try_into().expect("8 bytes"));
+            (a % (MERSENNE_61 - 1) + 1, b % MERSENNE_61)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn test_estimate_tracks_jaccard() {
+        // |A ∩ B| = 500, |A ∪ B| = 1500: J = 1/3.
+        let mut a = MinHash::new(256, 7);
+        let mut b = MinHash::new(256, 7);
+        for i in 0..1000 {
+            a.insert(format!("item {}", i).as_bytes());
+            b.insert(format!("item {}", i + 500).as_bytes());
+        }
+        let estimate = a.jaccard_estimate(&b).unwrap();
+        assert!((estimate - 1.0 / 3.0).abs() < 0.1, "{}", estimate);
+        assert_eq!(a.jaccard_estimate(&a), Ok(1.0));
+
+        let mut union = a.clone();
+        union.merge(&b).unwrap();
+        let mut direct = MinHash::new(256, 7);
+        for i in 0..1500 {
+            direct.insert(format!("item {}", i).as_bytes());
+        }
+        assert_eq!(union, direct);
+
+        let decoded = MinHash::from_bytes(&union.to_bytes()).unwrap();
+        assert_eq!(decoded, union);
+        assert_eq!(
+            a.jaccard_estimate(&MinHash::new(256, 8)),
+            Err(MinHashError::Incompatible)
+        );
+        assert_eq!(
+            MinHash::from_bytes(&[0; 12]),
+            Err(MinHashError::InvalidLength(12))
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Reproducible digests of multipart uploads.
+//!
+//! Like S3 multipart ETags, each part is hashed on its own (possibly on a
+//! different machine) and the object digest is computed from the part
+//! digests alone. Unlike ETags the composition is fully specified and binds
+//! the part lengths, so objects split differently never share a digest:
+//!
+//! ```text
+//! part   = BlueHash(part bytes)
+//! object = BlueHash(len("bluehash multipart v1") || "bluehash multipart v1"
+//!                   || part_count || (part_len || part)*)
+//! ```
+//!
+//! All integers are 8-byte big-endian, and parts are taken in part-number
+//! order (1-based, as in S3), regardless of the order they were added in.
+
+use crate::utils::to_hex_string;
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt;
+
+const MULTIPART_LABEL: &[u8] = b"bluehash multipart v1";
+
+/// Highest part number, matching the S3 limit.
+pub const MAX_PART_NUMBER: u32 = 10_000;
+
+/// Error returned while assembling a multipart digest.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MultipartError {
+    /// Part numbers must be between 1 and `MAX_PART_NUMBER`.
+    InvalidPartNumber(u32),
+    /// The part was already added.
+    DuplicatePart(u32),
+    /// The part digest has the wrong length for the digest size.
+    InvalidDigestLength(u32),
+    /// Parts must be numbered 1..=n without gaps; this part is missing.
+    MissingPart(u32),
+    /// No parts were added.
+    NoParts,
+}
+
+impl fmt::Display for MultipartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultipartError::InvalidPartNumber(n) => write!(f, "invalid part number {}", n),
+            MultipartError::DuplicatePart(n) => write!(f, "duplicate part {}", n),
+            MultipartError::InvalidDigestLength(n) => {
+                write!(f, "part {} has a digest of the wrong length", n)
+            }
+            MultipartError::MissingPart(n) => write!(f, "missing part {}", n),
+            MultipartError::NoParts => write!(f, "no parts"),
+        }
+    }
+}
+
+impl core::error::Error for MultipartError {}
+
+/// Length and digest of one part, as reported by whoever uploaded it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartDigest {
+    pub len: u64,
+    pub digest: Vec<u8>,
+}
+
+impl PartDigest {
+    /// Hashes one part.
+    pub fn of(digest_size: DigestSize, data: &[u8]) -> Self {
+        let mut hasher = BlueHashCore::new(digest_size);
+        hasher.update(data);
+        Self {
+            len: data.len() as u64,
+            digest: hasher.finalize(),
+        }
+    }
+}
+
+/// Final digest of a multipart object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultipartDigest {
+    pub digest: Vec<u8>,
+    pub part_count: u32,
+}
+
+impl fmt::Display for MultipartDigest {
+    /// Formats as `<hex>-<part count>`, like an S3 multipart ETag.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", to_hex_string(&self.digest), self.part_count)
+    }
+}
+
+/// Collects part digests and composes the object digest.
+#[derive(Debug, Clone)]
+pub struct MultipartHasher {
+    digest_size: DigestSize,
+    parts: BTreeMap<u32, PartDigest>,
+}
+
+impl MultipartHasher {
+    pub fn new(digest_size: DigestSize) -> Self {
+        Self {
+            digest_size,
+            parts: BTreeMap::new(),
+        }
+    }
+
+    /// Records the digest of a part hashed elsewhere.
+    pub fn add_part(&mut self, part_number: u32, part: PartDigest) -> Result<(), MultipartError> {
+        if part_number == 0 || part_number > MAX_PART_NUMBER {
+            return Err(MultipartError::InvalidPartNumber(part_number));
+        }
+        if part.digest.len() != self.digest_size.digest_length() {
+            return Err(MultipartError::InvalidDigestLength(part_number));
+        }
+        if self.parts.contains_key(&part_number) {
+            return Err(MultipartError::DuplicatePart(part_number));
+        }
+        self.parts.insert(part_number, part);
+        Ok(())
+    }
+
+    /// Hashes a part locally and records it.
+    pub fn hash_part(&mut self, part_number: u32, data: &[u8]) -> Result<(), MultipartError> {
+        self.add_part(part_number, PartDigest::of(self.digest_size, data))
+    }
+
+    /// Composes the object digest from parts `1..=n`.
+    pub fn finalize(&self) -> Result<MultipartDigest, MultipartError> {
+        if self.parts.is_empty() {
+            return Err(MultipartError::NoParts);
+        }
+        let mut hasher = BlueHashCore::new(self.digest_size);
+        hasher.update_framed(MULTIPART_LABEL);
+        hasher.update(&(self.parts.len() as u64).to_be_bytes());
+        for (expected, (&number, part)) in (1u32..).zip(&self.parts) {
+            if number != expected {
+                return Err(MultipartError::MissingPart(expected));
+            }
+            hasher.update(&part.len.to_be_bytes());
+            hasher.update(&part.digest);
+        }
+        Ok(MultipartDigest {
+            digest: hasher.finalize(),
+            part_count: self.parts.len() as u32,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_is_reproducible_from_part_digests() {
+        let parts: [&[u8]; 3] = [b"first part", b"second part", b"tail"];
+        let mut local = MultipartHasher::new(DigestSize::Bit128);
+        for (i, part) in parts.iter().enumerate() {
+            local.hash_part(i as u32 + 1, part).unwrap();
+        }
+        let expected = local.finalize().unwrap();
+        assert_eq!(expected.part_count, 3);
+        assert!(expected.to_string().ends_with("-3"));
+
+        // Parts hashed on other threads and reported out of order.
+        let mut remote = MultipartHasher::new(DigestSize::Bit128);
+        let handles: Vec<_> = parts
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, part)| {
+                let part = part.to_vec();
+                std::thread::spawn(move || {
+                    (i as u32 + 1, PartDigest::of(DigestSize::Bit128, &part))
+                })
+            })
+            .collect();
+        for handle in handles {
+            let (number, digest) = handle.join().unwrap();
+            remote.add_part(number, digest).unwrap();
+        }
+        assert_eq!(remote.finalize().unwrap(), expected);
+
+        // The same bytes split differently give a different object digest.
+        let mut resplit = MultipartHasher::new(DigestSize::Bit128);
+        resplit.hash_part(1, b"first partsecond part").unwrap();
+        resplit.hash_part(2, b"tail").unwrap();
+        assert_ne!(resplit.finalize().unwrap().digest, expected.digest);
+    }
+
+    #[test]
+    fn test_multipart_errors() {
+        let mut hasher = MultipartHasher::new(DigestSize::Bit128);
+        assert_eq!(hasher.finalize(), Err(MultipartError::NoParts));
+        let part = PartDigest::of(DigestSize::Bit128, b"x");
+        assert_eq!(
+            hasher.add_part(0, part.clone()),
+            Err(MultipartError::InvalidPartNumber(0))
+        );
+        hasher.add_part(1, part.clone()).unwrap();
+        assert_eq!(
+            hasher.add_part(1, part.clone()),
+            Err(MultipartError::DuplicatePart(1))
+        );
+        hasher.add_part(3, part).unwrap();
+        assert_eq!(hasher.finalize(), Err(MultipartError::MissingPart(2)));
+    }
+}
@@ -0,0 +1,138 @@
+//! Order-independent multiset hashing.
+//!
+//! `MultisetHash` implements the additive construction MSet-Add-Hash
+//! (Clarke et al., 2003): every item is mapped to a 512-bit integer with the
+//! BlueHash XOF, and the multiset is the sum of its items modulo 2^512. The
+//! digest therefore does not depend on insertion order, items can be removed
+//! by subtraction, and two replicas can compare their contents by comparing
+//! digests without exchanging the items.
+//!
+//! The element count is hashed together with the sum, so the digest of a
+//! multiset also commits to its size.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+
+const ITEM_LABEL: &[u8] = b"bluehash multiset item";
+const DIGEST_LABEL: &[u8] = b"bluehash multiset digest";
+
+/// Number of 64-bit limbs in the accumulator (512 bits).
+const LIMBS: usize = 8;
+
+/// Incremental, order-independent hash of a multiset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisetHash {
+    digest_size: DigestSize,
+    /// Sum of the item elements modulo 2^512, least significant limb first.
+    sum: [u64; LIMBS],
+    /// Number of insertions minus number of removals (wrapping).
+    count: u64,
+}
+
+impl MultisetHash {
+    /// Creates the hash of the empty multiset.
+    pub fn new(digest_size: DigestSize) -> Self {
+        Self {
+            digest_size,
+            sum: [0; LIMBS],
+            count: 0,
+        }
+    }
+
+    /// Adds one occurrence of `item`.
+    pub fn insert(&mut self, item: &[u8]) {
+        let element = item_element(item);
+        let mut carry = false;
+        for (limb, value) in self.sum.iter_mut().zip(element) {
+            let (sum, c1) = limb.overflowing_add(value);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+        self.count = self.count.wrapping_add(1);
+    }
+
+    /// Removes one occurrence of `item`.
+    ///
+    /// Removing an item that was never inserted is not detected; the digest
+    /// then matches no multiset until the item is inserted again.
+    pub fn remove(&mut self, item: &[u8]) {
+        let element = item_element(item);
+        let mut borrow = false;
+        for (limb, value) in self.sum.iter_mut().zip(element) {
+            let (diff, b1) = limb.overflowing_sub(value);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = b1 || b2;
+        }
+        self.count = self.count.wrapping_sub(1);
+    }
+
+    /// Number of items currently in the multiset.
+    pub fn len(&self) -> u64 {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the digest of the multiset.
+    pub fn digest(&self) -> Vec<u8> {
+        let mut hasher = BlueHashCore::new(self.digest_size);
+        hasher.update_framed(DIGEST_LABEL);
+        hasher.update(&self.count.to_be_bytes());
+        for limb in self.sum.iter().rev() {
+            hasher.update(&limb.to_be_bytes());
+        }
+        hasher.finalize()
+    }
+}
+
+/// Maps an item to a 512-bit group element, least significant limb first.
+fn item_element(item: &[u8]) -> [u64; LIMBS] {
+    let mut hasher = BlueHashCore::new(DigestSize::Bit512);
+    hasher.update_framed(ITEM_LABEL);
+    hasher.update(item);
+    let bytes: [u8; LIMBS * 8] = hasher.finalize_xof().squeeze_array();
+    let mut element = [0u64; LIMBS];
+    for (limb, chunk) in element.iter_mut().rev().zip(bytes.chunks_exact(8)) {
+        *limb = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    element
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_is_order_independent() {
+        let mut a = MultisetHash::new(DigestSize::Bit256);
+        let mut b = MultisetHash::new(DigestSize::Bit256);
+        for item in [&b"x"[..], b"y", b"x"] {
+            a.insert(item);
+        }
+        for item in [&b"y"[..], b"x", b"z", b"x"] {
+            b.insert(item);
+        }
+        b.remove(b"z");
+        assert_eq!(a, b);
+        assert_eq!(a.digest(), b.digest());
+        assert_eq!(a.len(), 3);
+
+        // Multiplicity matters: {x, y} differs from {x, x, y}.
+        a.remove(b"x");
+        assert_ne!(a.digest(), b.digest());
+    }
+
+    #[test]
+    fn test_remove_restores_empty_set() {
+        let empty = MultisetHash::new(DigestSize::Bit128);
+        let mut set = empty.clone();
+        set.insert(b"row 1");
+        set.remove(b"row 1");
+        assert!(set.is_empty());
+        assert_eq!(set, empty);
+    }
+}
@@ -1,4 +1,4 @@
-use rand::{Rng, SeedableRng};
+use rand::{RngCore, SeedableRng};
 /// Generates LWE noise based on the input data, round number, and secret key.
 /// The noise is designed to enhance resistance against quantum attacks by using
 /// a combination of multiplicative and additive operations, with bit rotations
@@ -21,6 +21,54 @@ use rand::{Rng, SeedableRng};
 /// This function introduces non-linear operations to improve security.
 use rand_chacha::ChaCha20Rng;
 
+// 离散高斯分布参数：标准差 sigma = 3.2，尾部界 k 取 6*sigma 上界
+const K_BOUND: i64 = 20;
+
+/// Number of 64-bit generator words the sampler processes, whatever the seed.
+///
+/// The rejection sampler this replaces drew words until a candidate was
+/// accepted: one per `gen_range` attempt (retried with probability 23/64) and
+/// one per acceptance test (passed with probability about 0.196), so its
+/// running time depended on the seed and hence on the message. All 640
+/// words fail to yield an accepted candidate with probability below `2^-77`,
+/// and only then does the sampler fall back to an error of 0; otherwise it
+/// returns the same value as the early-exit loop.
+const SAMPLER_WORDS: usize = 640;
+
+/// Acceptance thresholds `floor(exp(-x^2 / (2 * sigma^2)) * 2^53)` with `sigma = 3.2` for `x = 0..=K_BOUND`.
+///
+/// The original sampler drew `u: f64` uniformly from `[0, 1)` as `m / 2^53`
+/// with `m = next_u64() >> 11` and accepted when `u <= p`. Since scaling by a
+/// power of two is exact, that is the same as `m <= floor(p * 2^53)`, so
+/// comparing integers keeps every digest unchanged while the hash no longer
+/// touches floating point at all: results cannot depend on the target's FPU,
+/// `libm` or compiler flags. `test_accept_threshold_table` checks the values
+/// against `f64::exp`.
+const ACCEPT_THRESHOLD: [u64; K_BOUND as usize + 1] = [
+    9007199254740992,
+    8577959371872292,
+    7409120007003914,
+    5804137642008998,
+    4123796314944847,
+    2657327848826150,
+    1553035922237042,
+    823201765130969,
+    395748715788359,
+    172552463894334,
+    68235636230096,
+    24473143405046,
+    7960799653145,
+    2348616258943,
+    628428483723,
+    152506406916,
+    33566707674,
+    6700663877,
+    1213152365,
+    199205486,
+    29667139,
+];
+
+#[allow(dead_code)]
 pub fn generate_lwe_noise<T>(input_data: &[T], round: usize, prime: u64) -> u64
 where
     T: Copy + Into<u64>,
@@ -28,6 +76,22 @@ where
     let seed_base: u64 = input_data
         .iter()
         .fold(0u64, |acc, &x| acc.wrapping_add(x.into()));
+    generate_lwe_noise_from_seed(seed_base, round, prime)
+}
+
+/// Generates LWE noise from an already accumulated seed (the wrapping sum of
+/// the input data), so streaming callers need not keep the whole input around.
+///
+/// # Arguments
+///
+/// * `seed_base` - The wrapping sum of all input elements.
+/// * `round` - The current round number in the hash algorithm.
+/// * `prime` - A secret key that adds an extra layer of security to the noise generation.
+///
+/// # Returns
+///
+/// A 64-bit unsigned integer representing the generated noise value.
+pub fn generate_lwe_noise_from_seed(seed_base: u64, round: usize, prime: u64) -> u64 {
     let seed_val = seed_base.wrapping_add(round as u64);
     let mut seed_bytes = [0u8; 32];
     for (i, b) in seed_val.to_le_bytes().iter().cycle().take(32).enumerate() {
@@ -38,24 +102,103 @@ where
     }
     let mut rng = ChaCha20Rng::from_seed(seed_bytes);
 
-    // 离散高斯分布参数：标准差 sigma 与尾部界 k（取 6*sigma 上界）
-    let sigma = 3.2f64;
-    let k_bound = (6.0 * sigma).ceil() as i64;
-
-    loop {
-        // 采样候选值，范围为 [-k_bound, k_bound]
-        let candidate = rng.gen_range(-k_bound..=k_bound);
-        // 计算接受概率：exp(- x^2 / (2*sigma^2))，使用恒定时间实现对数计算
-        let exponent = -((candidate as f64).powi(2)) / (2.0 * sigma * sigma);
-        let accept_prob = exponent.exp();
-        let u: f64 = rng.gen();
-        if u <= accept_prob {
-            let error = candidate;
-            return if error < 0 {
-                prime.wrapping_sub(error.wrapping_abs() as u64)
-            } else {
-                prime.wrapping_add(error as u64)
-            };
+    let mut words = [0u8; SAMPLER_WORDS * 8];
+    rng.fill_bytes(&mut words);
+
+    // 逐字重放“采样候选值 -> 接受判定”的拒绝采样过程：候选值按 rand 的
+    // `gen_range(-K_BOUND..=K_BOUND)` 取得（v * RANGE 的低 64 位小于 RANGE << ZONE_SHIFT 时取高 64 位），
+    // 再以 53 位整数均匀数与定点接受阈值比较，等价于 u <= exp(- x^2 / (2*sigma^2))。
+    // 全部字都参与运算且无分支，第一个被接受的候选值之后的字不再改变结果。
+    const RANGE: u64 = (2 * K_BOUND + 1) as u64;
+    const ZONE_SHIFT: u32 = RANGE.leading_zeros();
+    let mut candidate = 0i64;
+    let mut have_candidate = 0u64;
+    let mut accepted = 0u64;
+    let mut error = 0i64;
+    for word in words.chunks_exact(8) {
+        let v = u64::from_le_bytes(word.try_into().expect("8-byte word"));
+        let drawn = ((u128::from(v) * u128::from(RANGE)) >> 64) as i64 - K_BOUND;
+        let in_zone = le_mask(v.wrapping_mul(RANGE) >> ZONE_SHIFT, RANGE - 1);
+        let threshold = ACCEPT_THRESHOLD[candidate.unsigned_abs() as usize];
+        let below = le_mask(v >> 11, threshold);
+
+        let pending = accepted ^ 1;
+        let accept = pending & have_candidate & below;
+        let take = pending & (have_candidate ^ 1) & in_zone;
+        error = select(accept, candidate, error);
+        candidate = select(take, drawn, candidate);
+        accepted |= accept;
+        have_candidate = take | (have_candidate & (pending ^ 1));
+    }
+    prime.wrapping_add(error as u64)
+}
+
+/// 1 if `a <= b`, otherwise 0, without branching; both must be below `2^63`.
+fn le_mask(a: u64, b: u64) -> u64 {
+    (b.wrapping_sub(a) >> 63) ^ 1
+}
+
+/// `a` if `flag` is 1, `b` if it is 0, without branching.
+fn select(flag: u64, a: i64, b: i64) -> i64 {
+    b ^ ((a ^ b) & flag.wrapping_neg() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGMA: f64 = 3.2;
+
+    #[test]
+    fn test_accept_threshold_table() {
+        assert_eq!(K_BOUND, (6.0 * SIGMA).ceil() as i64);
+        for (x, &threshold) in ACCEPT_THRESHOLD.iter().enumerate() {
+            let exponent = -((x as f64).powi(2)) / (2.0 * SIGMA * SIGMA);
+            assert_eq!(
+                threshold,
+                (exponent.exp() * (1u64 << 53) as f64).floor() as u64
+            );
+        }
+    }
+
+    /// The early-exit rejection sampler the fixed-length one replays.
+    fn early_exit_noise(seed_base: u64, round: usize, prime: u64) -> u64 {
+        use rand::Rng;
+        let seed_val = seed_base.wrapping_add(round as u64);
+        let mut seed_bytes = [0u8; 32];
+        for (i, b) in seed_val.to_le_bytes().iter().cycle().take(32).enumerate() {
+            seed_bytes[i] = *b;
+        }
+        for (i, b) in (round as u64).to_le_bytes().iter().enumerate() {
+            seed_bytes[i] ^= *b;
         }
+        let mut rng = ChaCha20Rng::from_seed(seed_bytes);
+        loop {
+            let candidate = rng.gen_range(-K_BOUND..=K_BOUND);
+            let m = rng.next_u64() >> 11;
+            if m <= ACCEPT_THRESHOLD[candidate.unsigned_abs() as usize] {
+                return prime.wrapping_add(candidate as u64);
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_early_exit_sampler() {
+        for seed in 0..500u64 {
+            let seed_base = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+            for round in [0usize, 1, 55, 159] {
+                assert_eq!(
+                    generate_lwe_noise_from_seed(seed_base, round, 0),
+                    early_exit_noise(seed_base, round, 0)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_noise_samples_are_pinned() {
+        let samples: [u64; 3] =
+            [0, 7, 99].map(|round| generate_lwe_noise_from_seed(12345, round, 0));
+        assert_eq!(samples, [3u64.wrapping_neg(), 1, 2]);
     }
 }
@@ -0,0 +1,68 @@
+//! Git-style typed object hashing.
+//!
+//! Objects are hashed as `"{type} {len}\0{payload}"`, the same framing git
+//! uses, so the object type and payload length are bound into the digest.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::format;
+use alloc::vec::Vec;
+
+/// Object type for file contents.
+pub const BLOB: &str = "blob";
+/// Object type for directory listings.
+pub const TREE: &str = "tree";
+/// Object type for commits.
+pub const COMMIT: &str = "commit";
+/// Object type for annotated tags.
+pub const TAG: &str = "tag";
+
+/// Returns the header `"{type} {len}\0"` that precedes the payload.
+pub fn object_header(object_type: &str, payload_len: usize) -> Vec<u8> {
+    format!("{} {}\0", object_type, payload_len).into_bytes()
+}
+
+/// Hashes a typed object using git-style framing.
+///
+/// # Arguments
+///
+/// * `digest_size` - The BlueHash variant to use.
+/// * `object_type` - The object type, e.g. [`BLOB`]. Must not contain spaces or NUL bytes.
+/// * `payload` - The object payload.
+///
+/// # Returns
+///
+/// The digest of `"{type} {len}\0{payload}"`.
+///
+/// # Panics
+///
+/// Panics if `object_type` contains a space or NUL byte, which would make the framing ambiguous.
+pub fn hash_object(digest_size: DigestSize, object_type: &str, payload: &[u8]) -> Vec<u8> {
+    assert!(
+        !object_type.contains([' ', '\0']),
+        "object type must not contain spaces or NUL bytes"
+    );
+    let mut hasher = BlueHashCore::new(digest_size);
+    hasher.update(&object_header(object_type, payload.len()));
+    hasher.update(payload);
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_object_framing() {
+        let payload = b"hello\n";
+        let mut hasher = BlueHashCore::new(DigestSize::Bit256);
+        hasher.update(b"blob 6\0hello\n");
+        assert_eq!(
+            hash_object(DigestSize::Bit256, BLOB, payload),
+            hasher.finalize()
+        );
+        assert_ne!(
+            hash_object(DigestSize::Bit256, BLOB, payload),
+            hash_object(DigestSize::Bit256, TREE, payload)
+        );
+    }
+}
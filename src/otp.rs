@@ -0,0 +1,234 @@
+//! One-time passwords (HOTP and TOTP) over the BlueHash MAC.
+//!
+//! The algorithms are those of RFC 4226 and RFC 6238 with the keyed
+//! BlueHash-256 `Mac` in place of HMAC-SHA-1:
+//!
+//! ```text
+//! tag    = Mac(key, u64(counter))            counter = (unix_time - t0) / step for TOTP
+//! offset = tag[31] & 0x0f
+//! code   = (u32(tag[offset..offset + 4]) & 0x7fffffff) mod 10^digits
+//! ```
+//!
+//! Provisioning URIs follow the Key URI format used by authenticator apps,
+//! with `algorithm=BLUEHASH256`; only apps that know this algorithm can
+//! compute the codes.
+
+use crate::mac::Mac;
+use crate::utils::to_base32;
+use crate::DigestSize;
+use alloc::format;
+use alloc::string::String;
+
+/// Name of the algorithm in provisioning URIs.
+pub const OTP_ALGORITHM: &str = "BLUEHASH256";
+
+/// TOTP parameters.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TotpConfig {
+    /// Number of decimal digits in a code, 6 to 9.
+    pub digits: u32,
+    /// Length of a time step in seconds.
+    pub step: u64,
+    /// Number of steps before and after the current one that are accepted.
+    pub skew: u64,
+    /// Unix time at which counting starts.
+    pub t0: u64,
+}
+
+impl Default for TotpConfig {
+    /// Six digits, 30-second steps, one step of skew either way.
+    fn default() -> Self {
+        Self {
+            digits: 6,
+            step: 30,
+            skew: 1,
+            t0: 0,
+        }
+    }
+}
+
+/// Computes the HOTP code for `counter`.
+///
+/// # Arguments
+///
+/// * `key` - The shared secret.
+/// * `counter` - The moving factor.
+/// * `digits` - The number of decimal digits, 6 to 9.
+///
+/// # Returns
+///
+/// The code as an integer; use `format_code` to display it.
+///
+/// # Panics
+///
+/// Panics if `digits` is not between 6 and 9.
+pub fn hotp(key: &[u8], counter: u64, digits: u32) -> u32 {
+    assert!((6..=9).contains(&digits), "OTP codes have 6 to 9 digits");
+    let mut mac = Mac::new(DigestSize::Bit256, key);
+    mac.update(&counter.to_be_bytes());
+    let tag = mac.finalize();
+    let offset = (tag[tag.len() - 1] & 0x0f) as usize;
+    let value = u32::from_be_bytes(tag[offset..offset + 4].try_into().unwrap()) & 0x7fff_ffff;
+    value % 10u32.pow(digits)
+}
+
+/// Checks an HOTP code against counters `counter..=counter + look_ahead`.
+///
+/// # Returns
+///
+/// The counter to use next (one past the matching counter), or `None`. A
+/// code matching counter `u64::MAX` is rejected, since no counter follows it.
+pub fn verify_hotp(
+    key: &[u8],
+    code: u32,
+    counter: u64,
+    look_ahead: u64,
+    digits: u32,
+) -> Option<u64> {
+    (counter..=counter.saturating_add(look_ahead))
+        .find(|&c| hotp(key, c, digits) == code)
+        .and_then(|c| c.checked_add(1))
+}
+
+/// Returns `None` if `config.step` is 0.
+fn time_step(unix_time: u64, config: &TotpConfig) -> Option<u64> {
+    unix_time.saturating_sub(config.t0).checked_div(config.step)
+}
+
+/// Computes the TOTP code at `unix_time`.
+///
+/// # Panics
+///
+/// Panics if `config.step` is 0 or `config.digits` is not between 6 and 9.
+pub fn totp(key: &[u8], unix_time: u64, config: &TotpConfig) -> u32 {
+    let step = time_step(unix_time, config).expect("TOTP time step must be positive");
+    hotp(key, step, config.digits)
+}
+
+/// Checks a TOTP code at `unix_time`, accepting `config.skew` steps of clock
+/// drift either way.
+///
+/// # Returns
+///
+/// The time step of the matching code, or `None`; always `None` if
+/// `config.step` is 0. Callers should refuse a step that was already used,
+/// so a code cannot be replayed.
+pub fn verify_totp(key: &[u8], code: u32, unix_time: u64, config: &TotpConfig) -> Option<u64> {
+    let now = time_step(unix_time, config)?;
+    let first = now.saturating_sub(config.skew);
+    (first..=now.saturating_add(config.skew)).find(|&step| hotp(key, step, config.digits) == code)
+}
+
+/// Formats a code with leading zeros.
+pub fn format_code(code: u32, digits: u32) -> String {
+    format!("{:0width$}", code, width = digits as usize)
+}
+
+fn percent_encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+fn uri(kind: &str, key: &[u8], issuer: &str, account: &str, digits: u32, extra: &str) -> String {
+    format!(
+        "otpauth://{}/{}:{}?secret={}&issuer={}&algorithm={}&digits={}&{}",
+        kind,
+        percent_encode(issuer),
+        percent_encode(account),
+        to_base32(key, false),
+        percent_encode(issuer),
+        OTP_ALGORITHM,
+        digits,
+        extra
+    )
+}
+
+/// Builds an `otpauth://totp/` provisioning URI (usually shown as a QR code).
+///
+/// # Arguments
+///
+/// * `key` - The shared secret.
+/// * `issuer` - The service name.
+/// * `account` - The user's account name.
+/// * `config` - The TOTP parameters.
+///
+/// # Returns
+///
+/// The URI.
+pub fn totp_provisioning_uri(
+    key: &[u8],
+    issuer: &str,
+    account: &str,
+    config: &TotpConfig,
+) -> String {
+    let period = format!("period={}", config.step);
+    uri("totp", key, issuer, account, config.digits, &period)
+}
+
+/// Builds an `otpauth://hotp/` provisioning URI starting at `counter`.
+pub fn hotp_provisioning_uri(
+    key: &[u8],
+    issuer: &str,
+    account: &str,
+    counter: u64,
+    digits: u32,
+) -> String {
+    let counter = format!("counter={}", counter);
+    uri("hotp", key, issuer, account, digits, &counter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn test_totp_window() {
+        let config = TotpConfig::default();
+        let code = totp(KEY, 1_000_000_000, &config);
+        assert!(code < 1_000_000);
+        assert_eq!(code, hotp(KEY, 1_000_000_000 / 30, 6));
+        assert_eq!(
+            verify_totp(KEY, code, 1_000_000_029, &config),
+            Some(33_333_333)
+        );
+        // One step later is within the skew, two steps later is not.
+        assert!(verify_totp(KEY, code, 1_000_000_030, &config).is_some());
+        assert!(verify_totp(KEY, code, 1_000_000_060, &config).is_none());
+        assert_eq!(format_code(42, 6), "000042");
+
+        let zero_step = TotpConfig { step: 0, ..config };
+        assert_eq!(verify_totp(KEY, code, 1_000_000_029, &zero_step), None);
+    }
+
+    #[test]
+    fn test_hotp_and_provisioning_uri() {
+        let code = hotp(KEY, 5, 8);
+        assert!(code < 100_000_000);
+        assert_eq!(verify_hotp(KEY, code, 3, 2, 8), Some(6));
+        assert_eq!(verify_hotp(KEY, code, 3, 1, 8), None);
+        let last = hotp(KEY, u64::MAX, 6);
+        assert_eq!(verify_hotp(KEY, last, u64::MAX, 0, 6), None);
+
+        let uri = totp_provisioning_uri(
+            KEY,
+            "Blue Corp",
+            "alice@example.com",
+            &TotpConfig::default(),
+        );
+        assert_eq!(
+            uri,
+            "otpauth://totp/Blue%20Corp:alice%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ\
+             &issuer=Blue%20Corp&algorithm=BLUEHASH256&digits=6&period=30"
+        );
+        assert!(hotp_provisioning_uri(KEY, "x", "y", 7, 6).ends_with("&digits=6&counter=7"));
+    }
+}
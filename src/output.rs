@@ -0,0 +1,229 @@
+//! Digest values and their hexadecimal representation.
+//!
+//! Downstream verification tools usually receive digests as hex strings
+//! (checksum files, manifests, HTTP headers). This module provides a typed
+//! wrapper around the raw digest bytes, strict hex parsing, and a helper that
+//! recomputes a digest and compares it in constant time.
+
+use crate::utils::{hex_value, to_hex_string};
+use crate::{constant_time_eq, BlueHashCore, Digest, DigestSize};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Error returned when a hex-encoded digest cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The string has an odd number of characters.
+    OddLength(usize),
+    /// A character that is not a hex digit was found at the given byte offset.
+    InvalidCharacter { index: usize, character: char },
+    /// The decoded length does not match any BlueHash digest size.
+    UnsupportedLength(usize),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::OddLength(len) => {
+                write!(f, "hex string has odd length {}", len)
+            }
+            ParseError::InvalidCharacter { index, character } => {
+                write!(
+                    f,
+                    "invalid hex character {:?} at index {}",
+                    character, index
+                )
+            }
+            ParseError::UnsupportedLength(len) => {
+                write!(f, "{} bytes is not a BlueHash digest length", len)
+            }
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// A BlueHash digest value. Equality compares the bytes in constant time.
+#[derive(Debug, Clone, Eq)]
+pub struct DigestOutput {
+    bytes: Vec<u8>,
+}
+
+impl DigestOutput {
+    /// Parses a digest from a hex string.
+    ///
+    /// # Arguments
+    ///
+    /// * `hex` - The hex string; upper and lower case digits are accepted.
+    ///
+    /// # Returns
+    ///
+    /// The digest, or a `ParseError` if the string is not valid hex or does
+    /// not decode to 16, 32 or 64 bytes.
+    pub fn from_hex(hex: &str) -> Result<Self, ParseError> {
+        let bytes = decode_hex(hex)?;
+        if DigestSize::from_digest_length(bytes.len()).is_none() {
+            return Err(ParseError::UnsupportedLength(bytes.len()));
+        }
+        Ok(Self { bytes })
+    }
+
+    /// Wraps raw digest bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The digest bytes.
+    ///
+    /// # Returns
+    ///
+    /// The digest, or `ParseError::UnsupportedLength` if `bytes` is not 16,
+    /// 32 or 64 bytes long.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        if DigestSize::from_digest_length(bytes.len()).is_none() {
+            return Err(ParseError::UnsupportedLength(bytes.len()));
+        }
+        Ok(Self {
+            bytes: bytes.to_vec(),
+        })
+    }
+
+    /// Returns the digest size matching the digest length.
+    pub fn digest_size(&self) -> DigestSize {
+        DigestSize::from_digest_length(self.bytes.len())
+            .expect("DigestOutput always holds a valid digest length")
+    }
+
+    /// Returns the digest as a lowercase hex string.
+    pub fn to_hex(&self) -> String {
+        to_hex_string(&self.bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl PartialEq for DigestOutput {
+    fn eq(&self, other: &Self) -> bool {
+        constant_time_eq(&self.bytes, &other.bytes)
+    }
+}
+
+impl AsRef<[u8]> for DigestOutput {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl fmt::Display for DigestOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl core::str::FromStr for DigestOutput {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+/// Decodes a hex string of arbitrary (even) length into bytes.
+///
+/// # Arguments
+///
+/// * `hex` - The hex string; upper and lower case digits are accepted.
+///
+/// # Returns
+///
+/// The decoded bytes, or a `ParseError` describing the first problem found.
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>, ParseError> {
+    let digits = hex.as_bytes();
+    if !digits.len().is_multiple_of(2) {
+        return Err(ParseError::OddLength(digits.len()));
+    }
+    let mut bytes = Vec::with_capacity(digits.len() / 2);
+    for (i, pair) in digits.chunks(2).enumerate() {
+        let mut value = 0u8;
+        for (j, &digit) in pair.iter().enumerate() {
+            let index = i * 2 + j;
+            let nibble = hex_value(digit).ok_or_else(|| ParseError::InvalidCharacter {
+                index,
+                character: hex[index..].chars().next().unwrap_or('\u{fffd}'),
+            })?;
+            value = (value << 4) | nibble;
+        }
+        bytes.push(value);
+    }
+    Ok(bytes)
+}
+
+/// Recomputes the digest of `data` and compares it against `expected_hex`.
+///
+/// The digest size is inferred from the length of `expected_hex`, and the
+/// comparison is performed in constant time.
+///
+/// # Arguments
+///
+/// * `expected_hex` - The expected digest as a hex string.
+/// * `data` - The data whose digest should be verified.
+///
+/// # Returns
+///
+/// `Ok(true)` if the digests match, `Ok(false)` if they differ, or a
+/// `ParseError` if `expected_hex` is not a valid BlueHash digest.
+pub fn verify_hex(expected_hex: &str, data: &[u8]) -> Result<bool, ParseError> {
+    let expected = DigestOutput::from_hex(expected_hex)?;
+    let mut hasher = BlueHashCore::new(expected.digest_size());
+    hasher.update(data);
+    Ok(constant_time_eq(&hasher.finalize(), expected.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_round_trip() {
+        let mut hasher = BlueHashCore::new(DigestSize::Bit256);
+        hasher.update(b"round trip");
+        let digest = hasher.finalize();
+        let hex = to_hex_string(&digest);
+        let parsed = DigestOutput::from_hex(&hex.to_uppercase()).unwrap();
+        assert_eq!(parsed.as_bytes(), &digest[..]);
+        assert_eq!(parsed.to_hex(), hex);
+        assert_eq!(parsed.digest_size(), DigestSize::Bit256);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(DigestOutput::from_hex("abc"), Err(ParseError::OddLength(3)));
+        assert_eq!(
+            DigestOutput::from_hex("zz"),
+            Err(ParseError::InvalidCharacter {
+                index: 0,
+                character: 'z'
+            })
+        );
+        assert_eq!(
+            DigestOutput::from_hex("abcd"),
+            Err(ParseError::UnsupportedLength(2))
+        );
+    }
+
+    #[test]
+    fn test_verify_hex() {
+        let data = b"verify me";
+        let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+        hasher.update(data);
+        let hex = to_hex_string(&hasher.finalize());
+        assert_eq!(verify_hex(&hex, data), Ok(true));
+        assert_eq!(verify_hex(&hex, b"verify me!"), Ok(false));
+    }
+}
@@ -0,0 +1,160 @@
+//! Reproducible digests of file sets.
+//!
+//! Supply-chain tools (SBOM generators, artifact registries) want one digest
+//! per package that is the same no matter which platform listed the files or
+//! in which order. `hash_package` builds a normalized manifest and hashes
+//! that:
+//!
+//! ```text
+//! manifest = for each file, sorted by normalized path (bytewise):
+//!                hex(BlueHash(contents)) || "  " || path || "\n"
+//! digest   = BlueHash(len(label) || label || manifest)
+//! ```
+//!
+//! with `label = "bluehash package v1"`. Paths are UTF-8 and normalized to
+//! `/` separators without a leading `./`; absolute paths, `.` and `..`
+//! components, empty components and control characters are rejected, as are
+//! two files normalizing to the same path. Unicode normalization is left to
+//! the caller. The manifest is in the format read by `Manifest::parse`, so it
+//! can be published next to the digest and checked with `verify_tree`.
+
+use crate::utils::to_hex_string;
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+const PACKAGE_LABEL: &[u8] = b"bluehash package v1";
+
+/// Error returned when a package cannot be hashed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageError {
+    /// The path is empty, absolute, has an empty, `.` or `..` component, or
+    /// contains a control character.
+    InvalidPath(String),
+    /// Two files normalize to this path.
+    DuplicatePath(String),
+}
+
+impl fmt::Display for PackageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackageError::InvalidPath(path) => write!(f, "invalid package path {:?}", path),
+            PackageError::DuplicatePath(path) => write!(f, "duplicate package path {:?}", path),
+        }
+    }
+}
+
+impl core::error::Error for PackageError {}
+
+/// The digest of a package and the manifest it was computed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageDigest {
+    /// The normalized manifest; see the module docs.
+    pub manifest: String,
+    /// The package digest.
+    pub digest: Vec<u8>,
+}
+
+/// Normalizes a package path, or returns `None` if it is not allowed.
+fn normalize_path(path: &str) -> Option<String> {
+    let mut normalized = path.replace('\\', "/");
+    while let Some(rest) = normalized.strip_prefix("./") {
+        normalized = String::from(rest);
+    }
+    let valid = !normalized.starts_with('/')
+        && !normalized.chars().any(char::is_control)
+        && normalized
+            .split('/')
+            .all(|part| !part.is_empty() && part != "." && part != "..");
+    valid.then_some(normalized)
+}
+
+/// Hashes a set of files into a canonical package digest.
+///
+/// # Arguments
+///
+/// * `digest_size` - The BlueHash variant used for file and package digests.
+/// * `files` - `(path, contents)` pairs in any order.
+///
+/// # Returns
+///
+/// The package digest and its manifest, or a `PackageError` for the first
+/// path that is invalid or duplicated.
+pub fn hash_package(
+    digest_size: DigestSize,
+    files: &[(&str, &[u8])],
+) -> Result<PackageDigest, PackageError> {
+    let mut sorted = BTreeMap::new();
+    for &(path, contents) in files {
+        let normalized =
+            normalize_path(path).ok_or_else(|| PackageError::InvalidPath(String::from(path)))?;
+        if sorted.contains_key(&normalized) {
+            return Err(PackageError::DuplicatePath(normalized));
+        }
+        sorted.insert(normalized, contents);
+    }
+
+    let mut manifest = String::new();
+    for (path, contents) in &sorted {
+        let mut hasher = BlueHashCore::new(digest_size);
+        hasher.update(contents);
+        manifest.push_str(&to_hex_string(&hasher.finalize()));
+        manifest.push_str("  ");
+        manifest.push_str(path);
+        manifest.push('\n');
+    }
+    let mut hasher = BlueHashCore::new(digest_size);
+    hasher.update_framed(PACKAGE_LABEL);
+    hasher.update(manifest.as_bytes());
+    Ok(PackageDigest {
+        manifest,
+        digest: hasher.finalize(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_digest_is_order_and_separator_independent() {
+        let unix: [(&str, &[u8]); 2] = [
+            ("src/lib.rs", b"pub fn f() {}"),
+            ("Cargo.toml", b"[package]"),
+        ];
+        let windows: [(&str, &[u8]); 2] = [
+            (".\\Cargo.toml", b"[package]"),
+            ("src\\lib.rs", b"pub fn f() {}"),
+        ];
+        let a = hash_package(DigestSize::Bit256, &unix).unwrap();
+        let b = hash_package(DigestSize::Bit256, &windows).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.manifest.lines().count(), 2);
+        assert!(a.manifest.lines().next().unwrap().ends_with("  Cargo.toml"));
+
+        let changed: [(&str, &[u8]); 2] = [
+            ("src/lib.rs", b"pub fn g() {}"),
+            ("Cargo.toml", b"[package]"),
+        ];
+        assert_ne!(
+            hash_package(DigestSize::Bit256, &changed).unwrap().digest,
+            a.digest
+        );
+    }
+
+    #[test]
+    fn test_rejects_ambiguous_paths() {
+        for path in ["", "/etc/passwd", "a/../b", "a//b", "a/./b", "line\nbreak"] {
+            assert_eq!(
+                hash_package(DigestSize::Bit128, &[(path, b"")]),
+                Err(PackageError::InvalidPath(String::from(path)))
+            );
+        }
+        assert_eq!(
+            hash_package(DigestSize::Bit128, &[("a/b", b"1"), ("./a\\b", b"2")]),
+            Err(PackageError::DuplicatePath(String::from("a/b")))
+        );
+    }
+}
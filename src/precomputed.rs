@@ -0,0 +1,92 @@
+//! Cached mid-states for inputs that share a prefix.
+//!
+//! Hashing many messages that start with the same bytes (a protocol header, a
+//! block template with a varying nonce, a domain label) absorbs the prefix
+//! over and over. `Precomputed` absorbs it once and freezes the hasher; each
+//! message then starts from a cheap copy of that state, which lives on the
+//! stack, so `hash` and `hash_into` allocate no more than a plain
+//! `BlueHashCore` would. The result is always identical to hashing
+//! `prefix || suffix` from scratch.
+//!
+//! Like an exported state, a mid-state lets anyone append to the prefix, so
+//! keep it as confidential as the prefix itself.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+
+/// A frozen hasher that has absorbed a common prefix.
+#[derive(Debug, Clone)]
+pub struct Precomputed {
+    midstate: BlueHashCore,
+}
+
+impl Precomputed {
+    /// Absorbs `prefix` into a fresh hasher of the given size.
+    pub fn new(digest_size: DigestSize, prefix: &[u8]) -> Self {
+        let mut midstate = BlueHashCore::new(digest_size);
+        midstate.update(prefix);
+        Self { midstate }
+    }
+
+    /// Freezes an already configured hasher, e.g. a keyed one or one from
+    /// `BlueHashBuilder`, together with everything it has absorbed.
+    pub fn from_hasher(hasher: BlueHashCore) -> Self {
+        Self { midstate: hasher }
+    }
+
+    /// Returns a hasher that continues after the prefix, for suffixes that
+    /// arrive in several pieces.
+    pub fn hasher(&self) -> BlueHashCore {
+        self.midstate.clone()
+    }
+
+    /// Returns the digest of `prefix || suffix`.
+    pub fn hash(&self, suffix: &[u8]) -> Vec<u8> {
+        let mut hasher = self.hasher();
+        hasher.update(suffix);
+        hasher.finalize()
+    }
+
+    /// Writes the digest of `prefix || suffix` into `out` without allocating.
+    ///
+    /// # Panics
+    /// Panics if `out.len()` differs from the digest length.
+    pub fn hash_into(&self, suffix: &[u8], out: &mut [u8]) {
+        let mut hasher = self.hasher();
+        hasher.update(suffix);
+        hasher.finalize_into(out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precomputed_matches_full_hash() {
+        // The prefix spans a full rate block and ends mid-block.
+        let prefix = [0x42u8; 200];
+        let cached = Precomputed::new(DigestSize::Bit256, &prefix);
+        for suffix in [&b""[..], b"n", b"nonce=00000001"] {
+            let mut full = BlueHashCore::new(DigestSize::Bit256);
+            full.update(&prefix);
+            full.update(suffix);
+            let expected = full.finalize();
+            assert_eq!(cached.hash(suffix), expected);
+
+            let mut out = [0u8; 32];
+            cached.hash_into(suffix, &mut out);
+            assert_eq!(out[..], expected[..]);
+        }
+
+        let keyed = Precomputed::from_hasher(BlueHashCore::new_keyed(DigestSize::Bit128, b"k"));
+        let mut pieces = keyed.hasher();
+        pieces.update(b"ab");
+        pieces.update(b"c");
+        let mut direct = BlueHashCore::new_keyed(DigestSize::Bit128, b"k");
+        direct.update(b"abc");
+        let expected = direct.finalize();
+        assert_eq!(pieces.finalize(), expected);
+        assert_eq!(keyed.hash(b"abc"), expected);
+    }
+}
@@ -0,0 +1,167 @@
+//! Prehashing for signature schemes.
+//!
+//! `prehash_for_signing` produces the 512-bit, domain-separated digest that
+//! an Ed25519ph-style signer signs instead of the full message:
+//!
+//! ```text
+//! prehash = BlueHash-512(len(label) || label || len(context) || context || message)
+//! ```
+//!
+//! with `label = "bluehash prehash v1"` and 8-byte big-endian lengths. The
+//! context separates protocols sharing a key; pass an empty slice if there is
+//! none. `Prehasher` computes the same digest incrementally. With the `digest`
+//! feature it implements the `digest` 0.10 traits with a 64-byte output, so it
+//! can be passed directly to `ed25519_dalek::SigningKey::sign_prehashed` and
+//! `VerifyingKey::verify_prehashed`.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+
+const PREHASH_LABEL: &[u8] = b"bluehash prehash v1";
+
+/// Length of a prehash in bytes.
+pub const PREHASH_LEN: usize = 64;
+
+/// Computes the prehash of a message for signing.
+///
+/// # Arguments
+///
+/// * `message` - The message to be signed.
+/// * `context` - The protocol context string (may be empty).
+///
+/// # Returns
+///
+/// The 64-byte prehash.
+pub fn prehash_for_signing(message: &[u8], context: &[u8]) -> [u8; PREHASH_LEN] {
+    let mut prehasher = Prehasher::with_context(context);
+    prehasher.update(message);
+    prehasher.finalize()
+}
+
+/// Incremental computation of `prehash_for_signing`.
+#[derive(Debug, Clone)]
+pub struct Prehasher {
+    template: BlueHashCore,
+    hasher: BlueHashCore,
+}
+
+impl Default for Prehasher {
+    /// Creates a prehasher with an empty context.
+    fn default() -> Self {
+        Self::with_context(b"")
+    }
+}
+
+impl Prehasher {
+    /// Creates a prehasher bound to `context`.
+    pub fn with_context(context: &[u8]) -> Self {
+        let mut template = BlueHashCore::new(DigestSize::Bit512);
+        template.update_framed(PREHASH_LABEL);
+        template.update_framed(context);
+        Self {
+            hasher: template.clone(),
+            template,
+        }
+    }
+
+    /// Absorbs more of the message.
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+    }
+
+    /// Returns the prehash of the message absorbed so far.
+    pub fn finalize(mut self) -> [u8; PREHASH_LEN] {
+        let mut out = [0u8; PREHASH_LEN];
+        self.hasher.finalize_into(&mut out);
+        out
+    }
+
+    /// Discards the message absorbed so far, keeping the context.
+    pub fn reset(&mut self) {
+        self.hasher = self.template.clone();
+    }
+}
+
+#[cfg(feature = "digest")]
+mod digest_impls {
+    use super::{Prehasher, PREHASH_LEN};
+    use digest::consts::U64;
+    use digest::{
+        FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update,
+    };
+
+    impl HashMarker for Prehasher {}
+
+    impl OutputSizeUser for Prehasher {
+        type OutputSize = U64;
+    }
+
+    impl Update for Prehasher {
+        fn update(&mut self, data: &[u8]) {
+            Prehasher::update(self, data);
+        }
+    }
+
+    impl FixedOutput for Prehasher {
+        fn finalize_into(self, out: &mut Output<Self>) {
+            let digest: [u8; PREHASH_LEN] = Prehasher::finalize(self);
+            out.copy_from_slice(&digest);
+        }
+    }
+
+    impl Reset for Prehasher {
+        fn reset(&mut self) {
+            Prehasher::reset(self);
+        }
+    }
+
+    impl FixedOutputReset for Prehasher {
+        fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+            let digest = self.clone().finalize();
+            out.copy_from_slice(&digest);
+            Prehasher::reset(self);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prehash_is_context_separated() {
+        let digest = prehash_for_signing(b"transfer 10", b"wallet v1");
+        let mut prehasher = Prehasher::with_context(b"wallet v1");
+        prehasher.update(b"transfer ");
+        prehasher.update(b"10");
+        assert_eq!(prehasher.clone().finalize(), digest);
+        prehasher.reset();
+        prehasher.update(b"transfer 10");
+        assert_eq!(prehasher.finalize(), digest);
+
+        assert_ne!(prehash_for_signing(b"transfer 10", b"wallet v2"), digest);
+        // The context is framed, so bytes cannot move between it and the message.
+        assert_ne!(prehash_for_signing(b"10", b"wallet v1transfer "), digest);
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn test_digest_trait_wiring() {
+        use digest::consts::U64;
+
+        // Ed25519ph signers in ed25519-dalek accept `D: Digest<OutputSize = U64>`.
+        fn prehash_with<D: digest::Digest<OutputSize = U64>>(
+            digest: D,
+            message: &[u8],
+        ) -> [u8; 64] {
+            digest.chain_update(message).finalize().into()
+        }
+        assert_eq!(
+            prehash_with(Prehasher::with_context(b"ctx"), b"msg"),
+            prehash_for_signing(b"msg", b"ctx")
+        );
+        assert_eq!(
+            prehash_with(Prehasher::default(), b"msg"),
+            prehash_for_signing(b"msg", b"")
+        );
+    }
+}
@@ -0,0 +1,137 @@
+//! Canonical hashing of protobuf messages (`prost` feature).
+//!
+//! Services that sign or compare messages need every peer to produce the
+//! same bytes for the same message. The protobuf wire format allows several
+//! encodings of one message, but `prost` always picks the same one:
+//!
+//! * fields are written in ascending field-number order,
+//! * proto3 scalars equal to their default value are omitted,
+//! * repeated scalars are packed,
+//! * unknown fields are not kept, so they never reach the digest.
+//!
+//! The one exception is map fields: a `HashMap` is written in its iteration
+//! order, which differs between processes. Generate map fields as `BTreeMap`
+//! (`prost_build::Config::btree_map(["."])`) for digests that are stable
+//! across peers. Under these rules
+//!
+//! ```text
+//! digest       = BlueHash(len(label) || label || encode(message))
+//! named digest = BlueHash(len(label) || label || len(name) || name || encode(message))
+//! ```
+//!
+//! with `label = "bluehash protobuf v1"` and `name` the fully qualified type
+//! name from `prost::Name`, so that two message types with the same encoding
+//! do not share a digest.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+use prost::{Message, Name};
+
+const PROTOBUF_LABEL: &[u8] = b"bluehash protobuf v1";
+
+/// Hashes the canonical encoding of `message`.
+///
+/// # Arguments
+///
+/// * `digest_size` - The BlueHash variant to use.
+/// * `message` - The message; map fields should be `BTreeMap`s.
+///
+/// # Returns
+///
+/// The digest of the message.
+pub fn hash_protobuf<M: Message>(digest_size: DigestSize, message: &M) -> Vec<u8> {
+    let mut hasher = BlueHashCore::new(digest_size);
+    hasher.update_framed(PROTOBUF_LABEL);
+    hasher.update(&message.encode_to_vec());
+    hasher.finalize()
+}
+
+/// Hashes the canonical encoding of `message` bound to its type name.
+///
+/// # Arguments
+///
+/// * `digest_size` - The BlueHash variant to use.
+/// * `message` - The message; map fields should be `BTreeMap`s.
+///
+/// # Returns
+///
+/// The digest of the message, distinct from that of any other message type
+/// with the same encoding.
+pub fn hash_protobuf_named<M: Message + Name>(digest_size: DigestSize, message: &M) -> Vec<u8> {
+    let mut hasher = BlueHashCore::new(digest_size);
+    hasher.update_framed(PROTOBUF_LABEL);
+    hasher.update_framed(M::full_name().as_bytes());
+    hasher.update(&message.encode_to_vec());
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::BTreeMap;
+    use alloc::string::{String, ToString};
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct Order {
+        #[prost(string, tag = "1")]
+        id: String,
+        #[prost(uint64, tag = "2")]
+        quantity: u64,
+        #[prost(btree_map = "string, string", tag = "3")]
+        labels: BTreeMap<String, String>,
+    }
+
+    impl Name for Order {
+        const NAME: &'static str = "Order";
+        const PACKAGE: &'static str = "shop.v1";
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct Refund {
+        #[prost(string, tag = "1")]
+        id: String,
+    }
+
+    impl Name for Refund {
+        const NAME: &'static str = "Refund";
+        const PACKAGE: &'static str = "shop.v1";
+    }
+
+    #[test]
+    fn test_equal_messages_hash_equal() {
+        let mut a = Order {
+            id: "o-1".to_string(),
+            ..Default::default()
+        };
+        let mut b = a.clone();
+        a.labels.insert("region".to_string(), "eu".to_string());
+        a.labels.insert("tier".to_string(), "gold".to_string());
+        b.labels.insert("tier".to_string(), "gold".to_string());
+        b.labels.insert("region".to_string(), "eu".to_string());
+        let digest = hash_protobuf(DigestSize::Bit256, &a);
+        assert_eq!(digest, hash_protobuf(DigestSize::Bit256, &b));
+
+        // A default quantity is not encoded; a nonzero one is.
+        b.quantity = 0;
+        assert_eq!(digest, hash_protobuf(DigestSize::Bit256, &b));
+        b.quantity = 2;
+        assert_ne!(digest, hash_protobuf(DigestSize::Bit256, &b));
+
+        let order = Order {
+            id: "o-1".to_string(),
+            ..Default::default()
+        };
+        let refund = Refund {
+            id: "o-1".to_string(),
+        };
+        assert_eq!(order.encode_to_vec(), refund.encode_to_vec());
+        assert_eq!(
+            hash_protobuf(DigestSize::Bit256, &order),
+            hash_protobuf(DigestSize::Bit256, &refund)
+        );
+        assert_ne!(
+            hash_protobuf_named(DigestSize::Bit256, &order),
+            hash_protobuf_named(DigestSize::Bit256, &refund)
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! Proof-of-work client puzzles for DoS mitigation.
+//!
+//! Under load, a server can ask a client to spend CPU before it spends its
+//! own. Puzzles are stateless: the server authenticates the parameters with
+//! its key instead of remembering them.
+//!
+//! ```text
+//! tag      = BlueHash-256-keyed(server_key, len(label) || label || len(client_id) || client_id
+//!                               || difficulty || expires_at), truncated to 16 bytes
+//! puzzle   = difficulty (1 byte) || expires_at (8-byte big-endian unix time) || tag
+//! solution = any u64 n with at least `difficulty` leading zero bits in
+//!            BlueHash-256(len(work_label) || work_label || puzzle || n)
+//! ```
+//!
+//! with `label = "bluehash puzzle v1"` and `work_label = "bluehash puzzle
+//! work v1"`. The tag is truncated with `Mac::finalize_truncated`, so it is
+//! not a prefix of the full-length MAC. Solving takes about `2^difficulty`
+//! hashes and verifying one. A solution stays valid until the puzzle
+//! expires; servers that need single use should remember accepted puzzles
+//! until then.
+
+use crate::mac::Mac;
+use crate::{BlueHashCore, Digest, DigestSize};
+use core::fmt;
+
+const PUZZLE_LABEL: &[u8] = b"bluehash puzzle v1";
+const WORK_LABEL: &[u8] = b"bluehash puzzle work v1";
+
+const TAG_LEN: usize = 16;
+
+/// Length of an encoded puzzle in bytes.
+pub const PUZZLE_LEN: usize = 1 + 8 + TAG_LEN;
+
+/// Largest supported difficulty in bits.
+pub const MAX_DIFFICULTY: u8 = 64;
+
+/// Error returned when a puzzle or solution is rejected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PuzzleError {
+    /// The encoded puzzle has the wrong length or difficulty.
+    Malformed,
+    /// The puzzle was not issued by this server for this client.
+    Forged,
+    /// The puzzle expired before the solution arrived.
+    Expired,
+    /// The solution does not meet the difficulty.
+    InsufficientWork,
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PuzzleError::Malformed => write!(f, "malformed puzzle"),
+            PuzzleError::Forged => write!(f, "puzzle tag does not verify"),
+            PuzzleError::Expired => write!(f, "puzzle has expired"),
+            PuzzleError::InsufficientWork => write!(f, "solution does not meet the difficulty"),
+        }
+    }
+}
+
+impl core::error::Error for PuzzleError {}
+
+/// A client puzzle; see the module docs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    /// Required number of leading zero bits.
+    pub difficulty: u8,
+    /// Unix time after which solutions are rejected.
+    pub expires_at: u64,
+    tag: [u8; TAG_LEN],
+}
+
+impl Puzzle {
+    /// Encodes the puzzle for sending to the client.
+    pub fn to_bytes(&self) -> [u8; PUZZLE_LEN] {
+        let mut out = [0u8; PUZZLE_LEN];
+        out[0] = self.difficulty;
+        out[1..9].copy_from_slice(&self.expires_at.to_be_bytes());
+        out[9..].copy_from_slice(&self.tag);
+        out
+    }
+
+    /// Decodes the output of `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PuzzleError> {
+        if bytes.len() != PUZZLE_LEN || bytes[0] > MAX_DIFFICULTY {
+            return Err(PuzzleError::Malformed);
+        }
+        Ok(Self {
+            difficulty: bytes[0],
+            expires_at: u64::from_be_bytes(bytes[1..9].try_into().expect("8 bytes")),
+            tag: bytes[9..].try_into().expect("16 bytes"),
+        })
+    }
+}
+
+fn puzzle_mac(server_key: &[u8], client_id: &[u8], difficulty: u8, expires_at: u64) -> Mac {
+    let mut mac = Mac::new(DigestSize::Bit256, server_key);
+    mac.update_framed(PUZZLE_LABEL);
+    mac.update_framed(client_id);
+    mac.update(&[difficulty]);
+    mac.update(&expires_at.to_be_bytes());
+    mac
+}
+
+fn work_zero_bits(puzzle: &Puzzle, solution: u64) -> u32 {
+    let mut hasher = BlueHashCore::new(DigestSize::Bit256);
+    hasher.update_framed(WORK_LABEL);
+    hasher.update(&puzzle.to_bytes());
+    hasher.update_u64_be(solution);
+    let digest = hasher.finalize();
+    u64::from_be_bytes(digest[..8].try_into().expect("BlueHash-256 digest")).leading_zeros()
+}
+
+/// Issues a puzzle to a client.
+///
+/// # Arguments
+///
+/// * `server_key` - The server's secret puzzle key.
+/// * `client_id` - What the puzzle is bound to, e.g. the client address.
+/// * `difficulty` - Leading zero bits required; each bit doubles the work.
+/// * `expires_at` - Unix time after which solutions are rejected.
+///
+/// # Panics
+/// Panics if `difficulty` exceeds `MAX_DIFFICULTY`.
+pub fn issue(server_key: &[u8], client_id: &[u8], difficulty: u8, expires_at: u64) -> Puzzle {
+    assert!(
+        difficulty <= MAX_DIFFICULTY,
+        "puzzle difficulty is at most 64 bits"
+    );
+    Puzzle {
+        difficulty,
+        expires_at,
+        tag: puzzle_mac(server_key, client_id, difficulty, expires_at)
+            .finalize_truncated(TAG_LEN)
+            .try_into()
+            .expect("16-byte tag"),
+    }
+}
+
+/// Finds the smallest solution of `puzzle`, taking about `2^difficulty`
+/// hashes.
+pub fn solve(puzzle: &Puzzle) -> u64 {
+    (0..=u64::MAX)
+        .find(|&n| work_zero_bits(puzzle, n) >= u32::from(puzzle.difficulty))
+        .expect("puzzle has no solution below 2^64")
+}
+
+/// Checks a solution.
+///
+/// # Arguments
+///
+/// * `server_key` - The key the puzzle was issued with.
+/// * `client_id` - The client presenting the solution.
+/// * `puzzle` - The puzzle as returned by the client.
+/// * `solution` - The client's solution.
+/// * `unix_time` - The current time.
+///
+/// # Returns
+///
+/// `Ok(())` if the puzzle is authentic and unexpired and the solution meets
+/// its difficulty, or the reason for rejecting it.
+pub fn verify(
+    server_key: &[u8],
+    client_id: &[u8],
+    puzzle: &Puzzle,
+    solution: u64,
+    unix_time: u64,
+) -> Result<(), PuzzleError> {
+    let mac = puzzle_mac(server_key, client_id, puzzle.difficulty, puzzle.expires_at);
+    if !mac.verify_truncated(&puzzle.tag, TAG_LEN) {
+        return Err(PuzzleError::Forged);
+    }
+    if unix_time > puzzle.expires_at {
+        return Err(PuzzleError::Expired);
+    }
+    if work_zero_bits(puzzle, solution) < u32::from(puzzle.difficulty) {
+        return Err(PuzzleError::InsufficientWork);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_solve_verify() {
+        let key = b"server puzzle key";
+        let issued = issue(key, b"203.0.113.7", 8, 1_700_000_060);
+        let puzzle = Puzzle::from_bytes(&issued.to_bytes()).unwrap();
+        assert_eq!(puzzle, issued);
+        let full = puzzle_mac(key, b"203.0.113.7", 8, 1_700_000_060).finalize();
+        assert_ne!(puzzle.tag[..], full[..TAG_LEN]);
+
+        let solution = solve(&puzzle);
+        let now = 1_700_000_000;
+        assert_eq!(verify(key, b"203.0.113.7", &puzzle, solution, now), Ok(()));
+        if solution > 0 {
+            assert_eq!(
+                verify(key, b"203.0.113.7", &puzzle, solution - 1, now),
+                Err(PuzzleError::InsufficientWork)
+            );
+        }
+        assert_eq!(
+            verify(key, b"198.51.100.1", &puzzle, solution, now),
+            Err(PuzzleError::Forged)
+        );
+        assert_eq!(
+            verify(key, b"203.0.113.7", &puzzle, solution, 1_700_000_061),
+            Err(PuzzleError::Expired)
+        );
+
+        // Lowering the difficulty invalidates the tag.
+        let mut bytes = puzzle.to_bytes();
+        bytes[0] = 0;
+        let easier = Puzzle::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            verify(key, b"203.0.113.7", &easier, 0, now),
+            Err(PuzzleError::Forged)
+        );
+        assert_eq!(Puzzle::from_bytes(&bytes[1..]), Err(PuzzleError::Malformed));
+    }
+}
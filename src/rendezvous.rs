@@ -0,0 +1,136 @@
+//! Rendezvous (highest random weight) hashing.
+//!
+//! Every node gets a score for every key and the key goes to the node with
+//! the highest one. Removing a node only moves the keys it owned, adding one
+//! only takes over the keys it now wins, and no ring or virtual nodes have to
+//! be kept in sync. Scores are BlueHash-64, i.e. BlueHash-128 truncated to 8
+//! bytes:
+//!
+//! ```text
+//! score(node, key) = u64_be(BlueHash-128-truncated-8(len(label) || label
+//!                                                    || len(node) || node || key))
+//! ```
+//!
+//! with `label = "bluehash rendezvous v1"`. The weighted variants (`std`
+//! feature) use logarithmic scoring, `-weight / ln(u)` with `u` the score
+//! mapped into `(0, 1)`, so each node receives keys in proportion to its
+//! weight and changing one weight only moves keys to or from that node.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+
+const RENDEZVOUS_LABEL: &[u8] = b"bluehash rendezvous v1";
+
+/// Returns the score of `node` for `key`.
+pub fn score(node: &[u8], key: &[u8]) -> u64 {
+    let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+    hasher.update_framed(RENDEZVOUS_LABEL);
+    hasher.update_framed(node);
+    hasher.update(key);
+    let score = hasher.finalize_truncated(8);
+    u64::from_be_bytes(score[..].try_into().expect("8-byte truncation"))
+}
+
+/// Picks the node responsible for `key`.
+///
+/// # Arguments
+///
+/// * `nodes` - Node identifiers, e.g. addresses; their order does not matter.
+/// * `key` - The key to place.
+///
+/// # Returns
+///
+/// The node with the highest score, or `None` if `nodes` is empty.
+pub fn pick<'a, N: AsRef<[u8]>>(nodes: &'a [N], key: &[u8]) -> Option<&'a N> {
+    nodes.iter().max_by_key(|node| score(node.as_ref(), key))
+}
+
+/// Returns up to `n` nodes for `key` in order of preference, e.g. a primary
+/// and its replicas.
+pub fn pick_n<'a, N: AsRef<[u8]>>(nodes: &'a [N], key: &[u8], n: usize) -> Vec<&'a N> {
+    let mut ranked: Vec<(u64, &N)> = nodes
+        .iter()
+        .map(|node| (score(node.as_ref(), key), node))
+        .collect();
+    ranked.sort_unstable_by_key(|(score, _)| core::cmp::Reverse(*score));
+    ranked.into_iter().take(n).map(|(_, node)| node).collect()
+}
+
+#[cfg(feature = "std")]
+fn weighted_score(node: &[u8], weight: f64, key: &[u8]) -> f64 {
+    let u = ((score(node, key) >> 11) as f64 + 0.5) / (1u64 << 53) as f64;
+    -weight / u.ln()
+}
+
+/// Picks a node for `key` with probability proportional to its weight.
+///
+/// # Panics
+/// Panics unless every weight is positive and finite.
+#[cfg(feature = "std")]
+pub fn pick_weighted<'a, N: AsRef<[u8]>>(nodes: &'a [(N, f64)], key: &[u8]) -> Option<&'a N> {
+    pick_weighted_n(nodes, key, 1).into_iter().next()
+}
+
+/// Returns up to `n` nodes for `key` in order of weighted preference.
+///
+/// # Panics
+/// Panics unless every weight is positive and finite.
+#[cfg(feature = "std")]
+pub fn pick_weighted_n<'a, N: AsRef<[u8]>>(
+    nodes: &'a [(N, f64)],
+    key: &[u8],
+    n: usize,
+) -> Vec<&'a N> {
+    let mut ranked: Vec<(f64, &N)> = nodes
+        .iter()
+        .map(|(node, weight)| {
+            assert!(
+                *weight > 0.0 && weight.is_finite(),
+                "node weights must be positive and finite"
+            );
+            (weighted_score(node.as_ref(), *weight, key), node)
+        })
+        .collect();
+    ranked.sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+    ranked.into_iter().take(n).map(|(_, node)| node).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn test_removing_a_node_only_moves_its_keys() {
+        let nodes = ["10.0.0.1", "10.0.0.2", "10.0.0.3", "10.0.0.4"];
+        let fewer = ["10.0.0.1", "10.0.0.2", "10.0.0.4"];
+        let mut counts = [0usize; 4];
+        for i in 0..2000 {
+            let key = format!("session-{}", i);
+            let owner = pick(&nodes, key.as_bytes()).unwrap();
+            counts[nodes.iter().position(|n| n == owner).unwrap()] += 1;
+            if *owner != "10.0.0.3" {
+                assert_eq!(pick(&fewer, key.as_bytes()), Some(owner));
+            }
+            let replicas = pick_n(&nodes, key.as_bytes(), 2);
+            assert_eq!(replicas[0], owner);
+            assert_ne!(replicas[1], owner);
+        }
+        assert!(
+            counts.iter().all(|c| (400..600).contains(c)),
+            "{:?}",
+            counts
+        );
+        assert_eq!(pick::<&str>(&[], b"key"), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_weighted_pick_follows_weights() {
+        let nodes = [("small", 1.0), ("large", 3.0)];
+        let large = (0..4000)
+            .filter(|i| pick_weighted(&nodes, format!("k{}", i).as_bytes()) == Some(&"large"))
+            .count();
+        assert!((2800..3200).contains(&large), "{}", large);
+    }
+}
@@ -0,0 +1,185 @@
+//! File hashing that survives restarts.
+//!
+//! Hashing a multi-terabyte image can take hours. `ResumableFileHash` hashes
+//! a file in steps and can hand out a `FileCheckpoint` at any point: the byte
+//! offset reached plus the exported hasher state (see `export_state`). After
+//! a crash or a planned shutdown, `resume` reopens the file, seeks past the
+//! hashed prefix and continues from the saved state, producing the same
+//! digest as an uninterrupted `hash_file`.
+//!
+//! A checkpoint does not notice changes to the part of the file it has
+//! already hashed; compare modification times before resuming if the file
+//! may have been written in the meantime. Like any exported state, a
+//! checkpoint is as sensitive as the data hashed so far.
+
+use crate::reader::auto_block_size;
+use crate::state::StateError;
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec;
+use alloc::vec::Vec;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Progress of a `ResumableFileHash`, to be persisted between runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCheckpoint {
+    /// Number of bytes of the file hashed so far.
+    pub offset: u64,
+    /// The hasher state after those bytes, from `export_state`.
+    pub state: Vec<u8>,
+}
+
+impl FileCheckpoint {
+    /// Encodes the checkpoint as the 8-byte big-endian offset followed by the
+    /// state.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(8 + self.state.len());
+        out.extend_from_slice(&self.offset.to_be_bytes());
+        out.extend_from_slice(&self.state);
+        out
+    }
+
+    /// Decodes the output of `to_bytes`, checking that the state is valid and
+    /// has absorbed exactly `offset` bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StateError> {
+        let (offset, state) = bytes
+            .split_first_chunk::<8>()
+            .ok_or(StateError::InvalidLength(bytes.len()))?;
+        let checkpoint = Self {
+            offset: u64::from_be_bytes(*offset),
+            state: state.to_vec(),
+        };
+        checkpoint.hasher()?;
+        Ok(checkpoint)
+    }
+
+    fn hasher(&self) -> Result<BlueHashCore, StateError> {
+        let hasher = BlueHashCore::import_state(&self.state)?;
+        if hasher.total_len != self.offset as u128 {
+            return Err(StateError::InvalidTotalLength(hasher.total_len));
+        }
+        Ok(hasher)
+    }
+}
+
+/// Hashes a file in steps that can be checkpointed and resumed.
+#[derive(Debug)]
+pub struct ResumableFileHash {
+    file: File,
+    hasher: BlueHashCore,
+    offset: u64,
+    buf: Vec<u8>,
+}
+
+impl ResumableFileHash {
+    /// Opens `path` for hashing from the start.
+    pub fn start<P: AsRef<Path>>(path: P, digest_size: DigestSize) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Self::open(file, BlueHashCore::new(digest_size), 0)
+    }
+
+    /// Reopens `path` and continues where `checkpoint` left off.
+    ///
+    /// # Returns
+    ///
+    /// The resumed hash, or an `InvalidData` error if the checkpoint is
+    /// malformed or lies beyond the end of the file.
+    pub fn resume<P: AsRef<Path>>(path: P, checkpoint: &FileCheckpoint) -> io::Result<Self> {
+        let hasher = checkpoint
+            .hasher()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let mut file = File::open(path)?;
+        if file.metadata()?.len() < checkpoint.offset {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "checkpoint lies beyond the end of the file",
+            ));
+        }
+        file.seek(SeekFrom::Start(checkpoint.offset))?;
+        Self::open(file, hasher, checkpoint.offset)
+    }
+
+    fn open(file: File, hasher: BlueHashCore, offset: u64) -> io::Result<Self> {
+        let len_hint = file.metadata().ok().map(|metadata| metadata.len());
+        Ok(Self {
+            file,
+            hasher,
+            offset,
+            buf: vec![0u8; auto_block_size(len_hint)],
+        })
+    }
+
+    /// Number of bytes hashed so far.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Hashes up to `max_bytes` more bytes and returns how many were hashed;
+    /// 0 means the end of the file has been reached.
+    pub fn advance(&mut self, max_bytes: u64) -> io::Result<u64> {
+        let mut hashed = 0;
+        while hashed < max_bytes {
+            let want = (max_bytes - hashed).min(self.buf.len() as u64) as usize;
+            match self.file.read(&mut self.buf[..want]) {
+                Ok(0) => break,
+                Ok(n) => {
+                    self.hasher.update(&self.buf[..n]);
+                    hashed += n as u64;
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        self.offset += hashed;
+        Ok(hashed)
+    }
+
+    /// Returns the progress so far, for `resume`.
+    pub fn checkpoint(&self) -> FileCheckpoint {
+        FileCheckpoint {
+            offset: self.offset,
+            state: self.hasher.export_state(),
+        }
+    }
+
+    /// Hashes the rest of the file and returns the digest.
+    pub fn finish(mut self) -> io::Result<Vec<u8>> {
+        while self.advance(u64::MAX)? > 0 {}
+        Ok(self.hasher.finalize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::hash_file;
+
+    #[test]
+    fn test_resume_matches_uninterrupted_hash() {
+        let path =
+            std::env::temp_dir().join(format!("bluehash_resumable_test_{}", std::process::id()));
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 241) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let mut first = ResumableFileHash::start(&path, DigestSize::Bit256).unwrap();
+        assert_eq!(first.advance(40_003).unwrap(), 40_003);
+        let saved = first.checkpoint().to_bytes();
+        drop(first);
+
+        let checkpoint = FileCheckpoint::from_bytes(&saved).unwrap();
+        assert_eq!(checkpoint.offset, 40_003);
+        let resumed = ResumableFileHash::resume(&path, &checkpoint).unwrap();
+        let digest = resumed.finish();
+
+        let mut forged = checkpoint.clone();
+        forged.offset = 200_000;
+        let beyond_end = ResumableFileHash::resume(&path, &forged);
+        let expected = hash_file(&path, DigestSize::Bit256);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(digest.unwrap(), expected.unwrap());
+        assert!(FileCheckpoint::from_bytes(&forged.to_bytes()).is_err());
+        assert_eq!(beyond_end.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
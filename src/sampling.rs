@@ -0,0 +1,129 @@
+//! Polynomial samplers for lattice-based schemes.
+//!
+//! Kyber and Dilithium expand short seeds into polynomials with an XOF. These
+//! samplers let prototypes use the BlueHash XOF for the same job:
+//!
+//! * `sample_poly_uniform` draws coefficients uniformly from `[0, q)` by
+//!   rejection sampling: each candidate is read from the next
+//!   `ceil(log2(q) / 8)` bytes (little-endian), masked to `ceil(log2(q))` bits
+//!   and kept if it is below `q`.
+//! * `sample_poly_cbd` draws coefficients from the centered binomial
+//!   distribution with parameter `eta`: each coefficient is
+//!   `sum(a_i) - sum(b_i)` over `2 * eta` consecutive output bits, read least
+//!   significant bit first.
+//!
+//! The XOF input is `len(label) || label || len(seed) || seed || parameter`
+//! (lengths as 8-byte big-endian integers, `q` as `u32`, `eta` as `u8`), with
+//! a different label for each sampler, so the two never share a stream.
+
+use crate::{BlueHashCore, Digest, DigestSize, XofReader};
+use alloc::vec;
+use alloc::vec::Vec;
+
+const UNIFORM_LABEL: &[u8] = b"bluehash sample uniform v1";
+const CBD_LABEL: &[u8] = b"bluehash sample cbd v1";
+
+fn sampler_xof(label: &[u8], seed: &[u8], parameter: &[u8]) -> XofReader {
+    let mut hasher = BlueHashCore::new(DigestSize::Bit512);
+    hasher.update_framed(label);
+    hasher.update_framed(seed);
+    hasher.update(parameter);
+    hasher.finalize_xof()
+}
+
+/// Samples a polynomial with coefficients uniform in `[0, q)`.
+///
+/// # Arguments
+///
+/// * `seed` - The seed (including any matrix indices or nonces).
+/// * `n` - The number of coefficients.
+/// * `q` - The modulus; must be at least 2.
+///
+/// # Returns
+///
+/// The `n` coefficients, lowest degree first.
+pub fn sample_poly_uniform(seed: &[u8], n: usize, q: u32) -> Vec<u32> {
+    assert!(q >= 2, "modulus must be at least 2");
+    let bits = u32::BITS - (q - 1).leading_zeros();
+    let width = bits.div_ceil(8) as usize;
+    let mask = (1u64 << bits) - 1;
+    let mut xof = sampler_xof(UNIFORM_LABEL, seed, &q.to_be_bytes());
+    let mut coefficients = Vec::with_capacity(n);
+    let mut candidate = [0u8; 4];
+    while coefficients.len() < n {
+        xof.squeeze(&mut candidate[..width]);
+        let value = (u32::from_le_bytes(candidate) as u64 & mask) as u32;
+        if value < q {
+            coefficients.push(value);
+        }
+    }
+    coefficients
+}
+
+/// Samples a polynomial from the centered binomial distribution.
+///
+/// # Arguments
+///
+/// * `seed` - The seed (including any nonce).
+/// * `eta` - The distribution parameter, between 1 and 16.
+/// * `n` - The number of coefficients.
+///
+/// # Returns
+///
+/// The `n` coefficients in `[-eta, eta]`, lowest degree first.
+pub fn sample_poly_cbd(seed: &[u8], eta: u8, n: usize) -> Vec<i32> {
+    assert!((1..=16).contains(&eta), "eta must be between 1 and 16");
+    let eta = eta as usize;
+    let mut bytes = vec![0u8; (2 * eta * n).div_ceil(8)];
+    sampler_xof(CBD_LABEL, seed, &[eta as u8]).squeeze(&mut bytes);
+    let bit = |i: usize| ((bytes[i / 8] >> (i % 8)) & 1) as i32;
+    (0..n)
+        .map(|j| {
+            let start = 2 * eta * j;
+            let a: i32 = (start..start + eta).map(bit).sum();
+            let b: i32 = (start + eta..start + 2 * eta).map(bit).sum();
+            a - b
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uniform_sampler() {
+        // Kyber's modulus needs 12-bit candidates read from 2 bytes.
+        let poly = sample_poly_uniform(b"rho||i||j", 256, 3329);
+        assert_eq!(poly.len(), 256);
+        assert!(poly.iter().all(|&c| c < 3329));
+        assert_eq!(poly, sample_poly_uniform(b"rho||i||j", 256, 3329));
+        assert_ne!(poly, sample_poly_uniform(b"rho||j||i", 256, 3329));
+        // A longer polynomial extends a shorter one from the same stream.
+        assert_eq!(
+            &sample_poly_uniform(b"rho||i||j", 300, 3329)[..256],
+            &poly[..]
+        );
+
+        // 256 uniform draws from 3329 values repeat about 10 times and
+        // average about 1664 (standard error 60).
+        let distinct: alloc::collections::BTreeSet<u32> = poly.iter().copied().collect();
+        assert!(
+            256 - distinct.len() < 30,
+            "{} repeats",
+            256 - distinct.len()
+        );
+        let mean = poly.iter().sum::<u32>() / 256;
+        assert!((1414..1914).contains(&mean), "mean {}", mean);
+    }
+
+    #[test]
+    fn test_cbd_sampler() {
+        let poly = sample_poly_cbd(b"sigma||nonce", 2, 256);
+        assert!(poly.iter().all(|c| (-2..=2).contains(c)));
+        let sum: i32 = poly.iter().sum();
+        // The distribution is centered; 256 samples of variance 1 rarely sum past 64.
+        assert!(sum.abs() < 64);
+        assert_ne!(poly, sample_poly_cbd(b"sigma||nonce", 3, 256)[..]);
+    }
+}
@@ -0,0 +1,123 @@
+//! Hashing many inputs that share long prefixes.
+//!
+//! File paths and URLs in bulk (a directory listing, a crawl frontier) mostly
+//! repeat their leading bytes. `SharedPrefixHasher` keeps a checkpoint of the
+//! hasher after every rate block (`DigestSize::rate_bytes`) of the previous
+//! input, so the next input resumes from the deepest checkpoint inside the
+//! prefix both share and only absorbs what differs. Digests are identical to
+//! hashing every input from scratch; only the amount of work changes, and the
+//! saving is largest when inputs arrive sorted, as `hash_shared_prefixes`
+//! arranges. Finalization is not shared, so the saving grows with the length
+//! of the common prefix: on the ~450-byte paths of `cargo bench --bench
+//! prefix` it is about an eighth of the total time.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A hasher that reuses the mid-states of the previous input's prefix.
+#[derive(Debug, Clone)]
+pub struct SharedPrefixHasher {
+    template: BlueHashCore,
+    stride: usize,
+    previous: Vec<u8>,
+    /// `checkpoints[i]` has absorbed `previous[..(i + 1) * stride]`.
+    checkpoints: Vec<BlueHashCore>,
+}
+
+impl SharedPrefixHasher {
+    /// Creates a hasher with the default configuration.
+    pub fn new(digest_size: DigestSize) -> Self {
+        Self::from_hasher(BlueHashCore::new(digest_size))
+    }
+
+    /// Hashes every input as the continuation of `template`, e.g. a keyed
+    /// hasher or one that has already absorbed a domain label.
+    pub fn from_hasher(template: BlueHashCore) -> Self {
+        let stride = template.digest_size.rate_bytes();
+        Self {
+            template,
+            stride,
+            previous: Vec::new(),
+            checkpoints: Vec::new(),
+        }
+    }
+
+    /// Returns the digest of `input`, resuming from the longest checkpoint
+    /// shared with the previous input.
+    pub fn hash(&mut self, input: &[u8]) -> Vec<u8> {
+        let common = self
+            .previous
+            .iter()
+            .zip(input)
+            .take_while(|(a, b)| a == b)
+            .count();
+        self.checkpoints.truncate(common / self.stride);
+        let mut hasher = match self.checkpoints.last() {
+            Some(checkpoint) => checkpoint.clone(),
+            None => self.template.clone(),
+        };
+        let mut offset = self.checkpoints.len() * self.stride;
+        while input.len() - offset >= self.stride {
+            hasher.update(&input[offset..offset + self.stride]);
+            offset += self.stride;
+            self.checkpoints.push(hasher.clone());
+        }
+        hasher.update(&input[offset..]);
+        self.previous.clear();
+        self.previous.extend_from_slice(input);
+        hasher.finalize()
+    }
+}
+
+/// Hashes every input, visiting them in sorted order so shared prefixes are
+/// absorbed once.
+///
+/// # Arguments
+///
+/// * `digest_size` - The BlueHash variant to use.
+/// * `inputs` - The inputs, e.g. file paths or URLs.
+///
+/// # Returns
+///
+/// The digests in the order of `inputs`, equal to hashing each on its own.
+pub fn hash_shared_prefixes(digest_size: DigestSize, inputs: &[&[u8]]) -> Vec<Vec<u8>> {
+    let mut order: Vec<usize> = (0..inputs.len()).collect();
+    order.sort_unstable_by_key(|&i| inputs[i]);
+    let mut hasher = SharedPrefixHasher::new(digest_size);
+    let mut digests = vec![Vec::new(); inputs.len()];
+    for i in order {
+        digests[i] = hasher.hash(inputs[i]);
+    }
+    digests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_prefixes_match_independent_hashes() {
+        let root = [b'/'; 400];
+        let a = [&root[..], b"src/lib.rs"].concat();
+        let b = [&root[..], b"src/main.rs"].concat();
+        let c = [&root[..170], b"x"].concat();
+        let inputs: [&[u8]; 5] = [&b, &a, &c, &root[..168], b""];
+        let digests = hash_shared_prefixes(DigestSize::Bit128, &inputs);
+        for (input, digest) in inputs.iter().zip(&digests) {
+            let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+            hasher.update(input);
+            assert_eq!(*digest, hasher.finalize());
+        }
+
+        // Unsorted order and repeated inputs give the same digests.
+        let mut hasher = SharedPrefixHasher::new(DigestSize::Bit128);
+        for (input, digest) in inputs
+            .iter()
+            .zip(&digests)
+            .chain(inputs.iter().zip(&digests))
+        {
+            assert_eq!(hasher.hash(input), *digest);
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Similarity digests for near-duplicate detection.
+//!
+//! Cryptographic digests change completely when one byte changes, which is
+//! what integrity checks want and what malware triage does not: there the
+//! question is whether a sample is a small variation of a known one.
+//! `SimilarityDigest` is a locality-sensitive digest in the spirit of ssdeep:
+//!
+//! ```text
+//! split the data at content-defined boundaries (gear hash, ~64-byte pieces)
+//! h_i    = first 8 bytes of BlueHash-128(piece_i), big-endian
+//! digest = the SIMILARITY_SIZE smallest distinct h_i, sorted
+//! ```
+//!
+//! An edit only changes the pieces it touches, so similar inputs share most
+//! of their smallest hashes. `compare` estimates the Jaccard similarity of
+//! the two sets of pieces from their digests (bottom-k MinHash) and returns a
+//! score from 0 (unrelated) to 100 (same pieces). Inputs much shorter than
+//! `SIMILARITY_SIZE` pieces give coarse scores.
+//!
+//! For documents already split into features (words, shingles, tokens with
+//! weights), `SimHash` gives a 64-bit fingerprint whose Hamming distance
+//! tracks the weighted cosine distance of the feature sets:
+//!
+//! ```text
+//! f    = BlueHash-128(feature) truncated to 8 bytes, big-endian
+//! v[j] = sum over features of (+weight if bit j of f is set, else -weight)
+//! bit j of the fingerprint = v[j] > 0
+//! ```
+//!
+//! Near-duplicate documents usually differ in at most 3 of the 64 bits.
+
+use crate::dedup::GEAR;
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+
+/// Maximum number of piece hashes kept in a digest.
+pub const SIMILARITY_SIZE: usize = 64;
+
+const MIN_PIECE_SIZE: usize = 16;
+const BOUNDARY_MASK: u64 = (1 << 6) - 1;
+
+/// A locality-sensitive digest; see the module docs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimilarityDigest {
+    hashes: Vec<u64>,
+}
+
+impl SimilarityDigest {
+    /// Computes the similarity digest of `data`.
+    pub fn of(data: &[u8]) -> Self {
+        let mut hashes = Vec::new();
+        let mut start = 0;
+        let mut rolling = 0u64;
+        for (i, &byte) in data.iter().enumerate() {
+            rolling = (rolling << 1).wrapping_add(GEAR[byte as usize]);
+            if i + 1 - start >= MIN_PIECE_SIZE && rolling & BOUNDARY_MASK == 0 {
+                hashes.push(piece_hash(&data[start..=i]));
+                start = i + 1;
+                rolling = 0;
+            }
+        }
+        if start < data.len() {
+            hashes.push(piece_hash(&data[start..]));
+        }
+        hashes.sort_unstable();
+        hashes.dedup();
+        hashes.truncate(SIMILARITY_SIZE);
+        Self { hashes }
+    }
+
+    /// Encodes the digest as concatenated 8-byte big-endian hashes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.hashes.iter().flat_map(|h| h.to_be_bytes()).collect()
+    }
+
+    /// Decodes the output of `to_bytes`, or returns `None` if `bytes` is not
+    /// a sorted list of at most `SIMILARITY_SIZE` distinct hashes.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if !bytes.len().is_multiple_of(8) || bytes.len() / 8 > SIMILARITY_SIZE {
+            return None;
+        }
+        let hashes: Vec<u64> = bytes
+            .chunks_exact(8)
+            .map(|chunk| u64::from_be_bytes(chunk.try_into().expect("8-byte chunk")))
+            .collect();
+        if hashes.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return None;
+        }
+        Some(Self { hashes })
+    }
+}
+
+fn piece_hash(piece: &[u8]) -> u64 {
+    let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+    hasher.update(piece);
+    let digest = hasher.finalize();
+    u64::from_be_bytes(digest[..8].try_into().expect("BlueHash-128 digest"))
+}
+
+/// Weighted SimHash accumulator; see the module docs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimHash {
+    votes: [i64; 64],
+}
+
+impl Default for SimHash {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimHash {
+    /// Creates an accumulator with no features.
+    pub fn new() -> Self {
+        Self { votes: [0; 64] }
+    }
+
+    /// Adds a feature with weight 1.
+    pub fn add(&mut self, feature: &[u8]) {
+        self.add_weighted(feature, 1);
+    }
+
+    /// Adds a feature; a negative weight removes an earlier addition.
+    pub fn add_weighted(&mut self, feature: &[u8], weight: i64) {
+        let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+        hasher.update(feature);
+        let hash = hasher.finalize_truncated(8);
+        let hash = u64::from_be_bytes(hash[..].try_into().expect("8-byte truncation"));
+        for (j, vote) in self.votes.iter_mut().enumerate() {
+            if hash >> j & 1 == 1 {
+                *vote += weight;
+            } else {
+                *vote -= weight;
+            }
+        }
+    }
+
+    /// Returns the 64-bit fingerprint of the features added so far.
+    pub fn fingerprint(&self) -> u64 {
+        self.votes
+            .iter()
+            .enumerate()
+            .filter(|(_, vote)| **vote > 0)
+            .fold(0, |acc, (j, _)| acc | 1 << j)
+    }
+}
+
+/// Returns the number of bits in which two SimHash fingerprints differ.
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Scores the similarity of the inputs behind two digests.
+///
+/// # Arguments
+///
+/// * `a` - The first digest.
+/// * `b` - The second digest.
+///
+/// # Returns
+///
+/// 100 for inputs made of the same pieces, falling towards 0 as they share
+/// fewer; two digests of empty inputs score 100.
+pub fn compare(a: &SimilarityDigest, b: &SimilarityDigest) -> u8 {
+    let (mut i, mut j) = (0, 0);
+    let (mut union, mut shared) = (0usize, 0usize);
+    while union < SIMILARITY_SIZE && (i < a.hashes.len() || j < b.hashes.len()) {
+        match (a.hashes.get(i), b.hashes.get(j)) {
+            (Some(x), Some(y)) if x == y => {
+                shared += 1;
+                i += 1;
+                j += 1;
+            }
+            (Some(x), Some(y)) if x < y => i += 1,
+            (Some(_), None) => i += 1,
+            _ => j += 1,
+        }
+        union += 1;
+    }
+    if union == 0 {
+        return 100;
+    }
+    (shared * 100 / union) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noise(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_similar_inputs_score_high() {
+        let sample = noise(1, 32 * 1024);
+        let mut variant = sample.clone();
+        variant[5000] ^= 0xff;
+        variant.splice(20_000..20_000, *b"patched");
+        variant.truncate(30 * 1024);
+
+        let digest = SimilarityDigest::of(&sample);
+        assert_eq!(compare(&digest, &digest), 100);
+        assert!(compare(&digest, &SimilarityDigest::of(&variant)) >= 70);
+        assert!(compare(&digest, &SimilarityDigest::of(&noise(2, 32 * 1024))) <= 10);
+
+        let decoded = SimilarityDigest::from_bytes(&digest.to_bytes()).unwrap();
+        assert_eq!(decoded, digest);
+        assert_eq!(SimilarityDigest::from_bytes(&[0; 7]), None);
+        assert_eq!(
+            compare(&SimilarityDigest::of(b""), &SimilarityDigest::of(b"")),
+            100
+        );
+    }
+
+    #[test]
+    fn test_simhash_distance_tracks_overlap() {
+        let fingerprint = |text: &str| {
+            let mut simhash = SimHash::new();
+            for word in text.split_whitespace() {
+                simhash.add(word.as_bytes());
+            }
+            simhash.fingerprint()
+        };
+        let text = "the quick brown fox jumps over the lazy dog while the cat \
+                    watches from the old stone wall near the quiet river bank";
+        let near = text.replace("lazy", "sleepy");
+        let other = "completely unrelated words about hash functions sponge \
+                     constructions and the security of permutation rounds";
+
+        assert_eq!(hamming_distance(fingerprint(text), fingerprint(text)), 0);
+        let close = hamming_distance(fingerprint(text), fingerprint(&near));
+        let far = hamming_distance(fingerprint(text), fingerprint(other));
+        assert!(close < far, "{} vs {}", close, far);
+        assert!(close <= 8, "{}", close);
+
+        let mut weighted = SimHash::new();
+        weighted.add_weighted(b"title", 5);
+        weighted.add(b"body");
+        weighted.add_weighted(b"body", -1);
+        let mut title = SimHash::new();
+        title.add(b"title");
+        assert_eq!(weighted.fingerprint(), title.fingerprint());
+    }
+}
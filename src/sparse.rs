@@ -0,0 +1,152 @@
+//! Hole-aware hashing of sparse files.
+//!
+//! VM images and preallocated database files are often sparse: most of their
+//! length is holes that read back as zeros but occupy no disk blocks.
+//! `hash_file_sparse` asks the file system where the data is (`SEEK_DATA` and
+//! `SEEK_HOLE`, with the `sparse` feature on Linux, Android, the BSDs, Apple
+//! platforms and Solaris) and absorbs each hole as a run of zeros from memory
+//! instead of reading it, so only allocated blocks touch the disk. The digest
+//! is identical to `hash_file`'s. The zeros are still hashed, so the saving is
+//! the I/O, which dominates for images that are mostly holes. Without the
+//! feature, on other platforms, or on file systems that do not report holes,
+//! the whole file is read as usual.
+
+use crate::instrument::hash_with;
+use crate::reader::auto_block_size;
+use crate::{Digest, DigestSize};
+use alloc::vec;
+use alloc::vec::Vec;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+const ZEROS: [u8; 4096] = [0u8; 4096];
+
+/// Hashes the file at `path`, skipping the reads of holes where the file
+/// system reports them.
+///
+/// # Arguments
+///
+/// * `path` - The file to hash.
+/// * `digest_size` - The BlueHash variant to use.
+///
+/// # Returns
+///
+/// The digest of the file contents, equal to `hash_file(path, digest_size)`.
+pub fn hash_file_sparse<P: AsRef<Path>>(path: P, digest_size: DigestSize) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    hash_with("hash_file_sparse", digest_size, |hasher| {
+        let mut buf = vec![0u8; auto_block_size(Some(len))];
+        let mut offset = 0;
+        while offset < len {
+            let (start, end) = next_data(&file, offset, len);
+            let mut zeros = start - offset;
+            while zeros > 0 {
+                let n = zeros.min(ZEROS.len() as u64) as usize;
+                hasher.update(&ZEROS[..n]);
+                zeros -= n as u64;
+            }
+            file.seek(SeekFrom::Start(start))?;
+            let mut remaining = end - start;
+            while remaining > 0 {
+                let want = remaining.min(buf.len() as u64) as usize;
+                match file.read(&mut buf[..want]) {
+                    // The file shrank while being hashed; a plain read stops here too.
+                    Ok(0) => return Ok(()),
+                    Ok(n) => {
+                        hasher.update(&buf[..n]);
+                        remaining -= n as u64;
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            offset = end;
+        }
+
+        // Whatever was appended after `len` was measured.
+        file.seek(SeekFrom::Start(len))?;
+        loop {
+            match file.read(&mut buf) {
+                Ok(0) => return Ok(()),
+                Ok(n) => hasher.update(&buf[..n]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    })
+}
+
+/// Returns the next data region `start..end` at or after `offset`, with
+/// `start == len` if only a hole remains. Treats the rest of the file as data
+/// when the file system cannot tell.
+#[cfg(all(
+    feature = "sparse",
+    any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "solaris",
+        target_os = "illumos"
+    )
+))]
+fn next_data(file: &File, offset: u64, len: u64) -> (u64, u64) {
+    use rustix::fs::{seek, SeekFrom as Whence};
+    use rustix::io::Errno;
+
+    match seek(file, Whence::Data(offset)) {
+        Ok(start) if start < len => match seek(file, Whence::Hole(start)) {
+            Ok(end) if end > start => (start, end.min(len)),
+            _ => (start, len),
+        },
+        Ok(_) | Err(Errno::NXIO) => (len, len),
+        Err(_) => (offset, len),
+    }
+}
+
+#[cfg(not(all(
+    feature = "sparse",
+    any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "solaris",
+        target_os = "illumos"
+    )
+)))]
+fn next_data(_file: &File, offset: u64, len: u64) -> (u64, u64) {
+    (offset, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::hash_file;
+    use std::io::Write;
+
+    #[test]
+    fn test_sparse_matches_plain_read() {
+        let path =
+            std::env::temp_dir().join(format!("bluehash_sparse_test_{}", std::process::id()));
+        let mut file = File::create(&path).unwrap();
+        // Hole, data, hole, data, trailing hole.
+        file.set_len(3 << 20).unwrap();
+        file.seek(SeekFrom::Start(1 << 20)).unwrap();
+        file.write_all(&[0xabu8; 5000]).unwrap();
+        file.seek(SeekFrom::Start((2 << 20) + 123)).unwrap();
+        file.write_all(b"tail data").unwrap();
+        drop(file);
+
+        let sparse = hash_file_sparse(&path, DigestSize::Bit256);
+        let plain = hash_file(&path, DigestSize::Bit256);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(sparse.unwrap(), plain.unwrap());
+    }
+}
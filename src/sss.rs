@@ -0,0 +1,367 @@
+//! Shamir secret sharing with integrity checking.
+//!
+//! Secrets are split byte-wise over GF(2^8) (the AES field, reduction
+//! polynomial `x^8 + x^4 + x^3 + x + 1`), so any `threshold` of the shares
+//! reconstruct the secret. Fewer share values alone reveal nothing about it,
+//! but the tags below let their holder test guesses, so fewer than
+//! `threshold` shares keep the secret hidden only if it has high entropy.
+//!
+//! Plain Shamir shares carry no integrity: a corrupted share silently yields a
+//! wrong secret. Here every share also carries
+//!
+//! ```text
+//! auth_key   = BlueHash-256(len(label) || label || secret)
+//! commitment = BlueHash-256_keyed(auth_key, "set" || threshold || set_id)
+//! tag        = BlueHash-256_keyed(auth_key, "share" || commitment || index || value)
+//! ```
+//!
+//! with `label = "bluehash sss v1"` and a random 16-byte `set_id` per split.
+//! The commitment identifies the share set, so shares from different splits
+//! are rejected before combining, and after reconstruction the commitment and
+//! every share tag are checked with the recovered key, so a wrong or tampered
+//! share is reported rather than producing a wrong secret. Since the tags are
+//! keyed by a hash of the secret, a holder of fewer than `threshold` shares can
+//! test guesses against them: share high-entropy keys, not passphrases.
+//!
+//! A serialized share is `index || threshold || set_id || commitment || tag || value`.
+
+use crate::{constant_time_eq, BlueHashCore, Digest, DigestSize};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use rand::{CryptoRng, RngCore};
+
+const SSS_LABEL: &[u8] = b"bluehash sss v1";
+const HASH_LEN: usize = 32;
+const SET_ID_LEN: usize = 16;
+const HEADER_LEN: usize = 2 + SET_ID_LEN + 2 * HASH_LEN;
+
+/// Error returned when splitting or combining.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SssError {
+    /// The threshold must be between 1 and the number of shares.
+    InvalidThreshold,
+    /// At most 255 shares can be produced.
+    TooManyShares,
+    /// Fewer shares than the threshold were given.
+    NotEnoughShares,
+    /// Two shares have the same index.
+    DuplicateShare(u8),
+    /// The shares come from different splits or have different lengths.
+    MixedShareSets,
+    /// The reconstructed secret does not match the share tags.
+    IntegrityCheckFailed,
+    /// A serialized share is too short or has index 0.
+    MalformedShare,
+}
+
+impl fmt::Display for SssError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SssError::InvalidThreshold => write!(f, "invalid threshold"),
+            SssError::TooManyShares => write!(f, "too many shares"),
+            SssError::NotEnoughShares => write!(f, "not enough shares"),
+            SssError::DuplicateShare(index) => write!(f, "duplicate share {}", index),
+            SssError::MixedShareSets => write!(f, "shares belong to different sets"),
+            SssError::IntegrityCheckFailed => write!(f, "share integrity check failed"),
+            SssError::MalformedShare => write!(f, "malformed share"),
+        }
+    }
+}
+
+impl core::error::Error for SssError {}
+
+/// One share of a secret. `Debug` leaves out the share value, and equality
+/// compares it in constant time.
+#[derive(Clone)]
+pub struct Share {
+    /// Evaluation point, 1 to 255.
+    pub index: u8,
+    pub threshold: u8,
+    pub set_id: [u8; SET_ID_LEN],
+    pub commitment: [u8; HASH_LEN],
+    pub tag: [u8; HASH_LEN],
+    pub value: Vec<u8>,
+}
+
+impl PartialEq for Share {
+    fn eq(&self, other: &Self) -> bool {
+        (self.index == other.index)
+            & (self.threshold == other.threshold)
+            & (self.set_id == other.set_id)
+            & (self.commitment == other.commitment)
+            & constant_time_eq(&self.tag, &other.tag)
+            & constant_time_eq(&self.value, &other.value)
+    }
+}
+
+impl Eq for Share {}
+
+impl fmt::Debug for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Share")
+            .field("index", &self.index)
+            .field("threshold", &self.threshold)
+            .field("set_id", &self.set_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Share {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN + self.value.len());
+        out.push(self.index);
+        out.push(self.threshold);
+        out.extend_from_slice(&self.set_id);
+        out.extend_from_slice(&self.commitment);
+        out.extend_from_slice(&self.tag);
+        out.extend_from_slice(&self.value);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SssError> {
+        if bytes.len() < HEADER_LEN || bytes[0] == 0 {
+            return Err(SssError::MalformedShare);
+        }
+        let (set_id, rest) = bytes[2..].split_at(SET_ID_LEN);
+        let (commitment, rest) = rest.split_at(HASH_LEN);
+        let (tag, value) = rest.split_at(HASH_LEN);
+        Ok(Self {
+            index: bytes[0],
+            threshold: bytes[1],
+            set_id: set_id.try_into().unwrap(),
+            commitment: commitment.try_into().unwrap(),
+            tag: tag.try_into().unwrap(),
+            value: value.to_vec(),
+        })
+    }
+}
+
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        // Branch-free: masks instead of conditionals on secret data.
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    product
+}
+
+fn gf_inv(a: u8) -> u8 {
+    // a^254 = a^-1 in GF(2^8).
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exponent = 254u8;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+fn keyed(key: &[u8; HASH_LEN], parts: &[&[u8]]) -> [u8; HASH_LEN] {
+    let mut hasher = BlueHashCore::new_keyed(DigestSize::Bit256, key);
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut out = [0u8; HASH_LEN];
+    hasher.finalize_into(&mut out);
+    out
+}
+
+fn auth_key(secret: &[u8]) -> [u8; HASH_LEN] {
+    let mut hasher = BlueHashCore::new(DigestSize::Bit256);
+    hasher.update_framed(SSS_LABEL);
+    hasher.update(secret);
+    let mut out = [0u8; HASH_LEN];
+    hasher.finalize_into(&mut out);
+    out
+}
+
+fn share_tag(key: &[u8; HASH_LEN], commitment: &[u8], index: u8, value: &[u8]) -> [u8; HASH_LEN] {
+    keyed(key, &[b"share", commitment, &[index], value])
+}
+
+/// Splits a secret into `share_count` shares, any `threshold` of which
+/// reconstruct it.
+///
+/// # Arguments
+///
+/// * `secret` - The secret to split.
+/// * `threshold` - Number of shares needed to reconstruct.
+/// * `share_count` - Number of shares to produce (at most 255).
+/// * `rng` - Source of the random polynomial coefficients.
+///
+/// # Returns
+///
+/// The shares, with indices `1..=share_count`.
+pub fn split<R: RngCore + CryptoRng>(
+    secret: &[u8],
+    threshold: u8,
+    share_count: usize,
+    rng: &mut R,
+) -> Result<Vec<Share>, SssError> {
+    if share_count > 255 {
+        return Err(SssError::TooManyShares);
+    }
+    if threshold == 0 || threshold as usize > share_count {
+        return Err(SssError::InvalidThreshold);
+    }
+    let key = auth_key(secret);
+    let mut set_id = [0u8; SET_ID_LEN];
+    rng.fill_bytes(&mut set_id);
+    let commitment = keyed(&key, &[b"set", &[threshold], &set_id]);
+
+    // coefficients[k] holds the degree-k coefficient for every secret byte.
+    let mut coefficients = vec![secret.to_vec()];
+    for _ in 1..threshold {
+        let mut row = vec![0u8; secret.len()];
+        rng.fill_bytes(&mut row);
+        coefficients.push(row);
+    }
+
+    let shares = (1..=share_count as u8)
+        .map(|x| {
+            let value: Vec<u8> = (0..secret.len())
+                .map(|i| {
+                    // Horner evaluation at x.
+                    coefficients
+                        .iter()
+                        .rev()
+                        .fold(0u8, |acc, row| gf_mul(acc, x) ^ row[i])
+                })
+                .collect();
+            Share {
+                index: x,
+                threshold,
+                set_id,
+                commitment,
+                tag: share_tag(&key, &commitment, x, &value),
+                value,
+            }
+        })
+        .collect();
+    Ok(shares)
+}
+
+/// Reconstructs a secret and checks the integrity of every share given.
+///
+/// # Arguments
+///
+/// * `shares` - At least `threshold` shares of one split.
+///
+/// # Returns
+///
+/// The secret, or an error if the shares are insufficient, inconsistent or
+/// tampered with.
+pub fn combine(shares: &[Share]) -> Result<Vec<u8>, SssError> {
+    let first = shares.first().ok_or(SssError::NotEnoughShares)?;
+    if shares.iter().any(|share| {
+        share.threshold != first.threshold
+            || share.set_id != first.set_id
+            || share.commitment != first.commitment
+            || share.value.len() != first.value.len()
+    }) {
+        return Err(SssError::MixedShareSets);
+    }
+    for (i, share) in shares.iter().enumerate() {
+        if share.index == 0 {
+            return Err(SssError::MalformedShare);
+        }
+        if shares[..i].iter().any(|other| other.index == share.index) {
+            return Err(SssError::DuplicateShare(share.index));
+        }
+    }
+    let threshold = first.threshold as usize;
+    if threshold == 0 || shares.len() < threshold {
+        return Err(SssError::NotEnoughShares);
+    }
+
+    // Lagrange interpolation at x = 0 over the first `threshold` shares.
+    let used = &shares[..threshold];
+    let mut secret = vec![0u8; first.value.len()];
+    for (i, share) in used.iter().enumerate() {
+        let mut basis = 1u8;
+        for (j, other) in used.iter().enumerate() {
+            if i != j {
+                basis = gf_mul(
+                    basis,
+                    gf_mul(other.index, gf_inv(other.index ^ share.index)),
+                );
+            }
+        }
+        for (byte, value) in secret.iter_mut().zip(&share.value) {
+            *byte ^= gf_mul(basis, *value);
+        }
+    }
+
+    let key = auth_key(&secret);
+    let commitment = keyed(&key, &[b"set", &[first.threshold], &first.set_id]);
+    let valid = shares.iter().fold(
+        constant_time_eq(&commitment, &first.commitment),
+        |valid, share| {
+            let tag = share_tag(&key, &share.commitment, share.index, &share.value);
+            valid & constant_time_eq(&tag, &share.tag)
+        },
+    );
+    if !valid {
+        return Err(SssError::IntegrityCheckFailed);
+    }
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_any_threshold_subset_reconstructs() {
+        let mut rng = ChaCha20Rng::seed_from_u64(7);
+        let secret = b"backup key material, 32 bytes!!!";
+        let shares = split(secret, 3, 5, &mut rng).unwrap();
+        assert_eq!(shares.len(), 5);
+        let subset = [shares[4].clone(), shares[0].clone(), shares[2].clone()];
+        assert_eq!(combine(&subset).unwrap(), secret);
+        let parsed: Vec<Share> = shares[1..4]
+            .iter()
+            .map(|share| Share::from_bytes(&share.to_bytes()).unwrap())
+            .collect();
+        assert_eq!(parsed[0], shares[1]);
+        assert_ne!(parsed[0], shares[2]);
+        let debug = format!("{:?}", shares[0]);
+        assert!(debug.contains("index: 1") && !debug.contains("value"));
+        assert_eq!(combine(&parsed).unwrap(), secret);
+        assert_eq!(combine(&shares[..2]), Err(SssError::NotEnoughShares));
+        assert_eq!(gf_mul(0x57, 0x83), 0xc1);
+    }
+
+    #[test]
+    fn test_tampered_and_mixed_shares_are_rejected() {
+        let mut rng = ChaCha20Rng::seed_from_u64(8);
+        let shares = split(b"secret", 2, 3, &mut rng).unwrap();
+        let mut tampered = shares.clone();
+        tampered[0].value[0] ^= 1;
+        assert_eq!(combine(&tampered), Err(SssError::IntegrityCheckFailed));
+        // An unused extra share is still checked.
+        tampered[0] = shares[0].clone();
+        tampered[2].value[0] ^= 1;
+        assert_eq!(combine(&tampered), Err(SssError::IntegrityCheckFailed));
+
+        let other = split(b"secret", 2, 3, &mut rng).unwrap();
+        assert_eq!(
+            combine(&[shares[0].clone(), other[1].clone()]),
+            Err(SssError::MixedShareSets)
+        );
+        assert_eq!(
+            combine(&[shares[0].clone(), shares[0].clone()]),
+            Err(SssError::DuplicateShare(1))
+        );
+    }
+}
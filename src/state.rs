@@ -0,0 +1,252 @@
+//! Canonical serialization of the hasher state.
+//!
+//! All multi-byte integers are encoded big-endian, matching the byte order
+//! used for digest output, so the encoding is identical on every target.
+//! The exported layout is:
+//!
+//! | bytes | field |
+//! |-------|-------|
+//! | 1 | format version (`1`) |
+//! | 1 | digest size tag (`1` = 128, `2` = 256, `3` = 512; plus `0x10` for the keccak backend) |
+//! | 4 | round count (one of the `SecurityProfile` values) |
+//! | 16 | total input length in bytes |
+//! | 8 | wrapping sum of all input bytes |
+//! | 8 | number of absorbed 8-byte blocks |
+//! | 1 | number of buffered tail bytes (0..=7) |
+//! | 8 | tail buffer, zero padded |
+//! | 8 × words | state words (25 for the keccak backend) |
+//!
+//! An exported state lets anyone append to the message hashed so far, so it
+//! is as sensitive as that message. A digest, or a state exported after
+//! finalization, does not: the length-extension test below forges both and
+//! checks that neither reproduces `H(m || pad || m')`.
+//!
+//! The tests only use fixed byte vectors, so running them on a big-endian
+//! target (e.g. `cross test --target powerpc64-unknown-linux-gnu`, which runs
+//! under qemu) checks the encoding is endian-independent.
+
+use crate::{Backend, BlueHashCore, Digest, DigestSize, SecurityProfile, MAX_INPUT_LEN};
+use alloc::vec::Vec;
+use core::fmt;
+
+const STATE_FORMAT_VERSION: u8 = 1;
+const HEADER_LEN: usize = 1 + 1 + 4 + 16 + 8 + 8 + 1 + 8;
+
+/// Error returned when an exported state cannot be imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateError {
+    /// The encoding has the wrong length for its digest size.
+    InvalidLength(usize),
+    /// The format version is not supported.
+    UnsupportedVersion(u8),
+    /// The digest size tag is unknown.
+    InvalidDigestSize(u8),
+    /// The round count does not belong to any security profile.
+    InvalidRoundCount(u32),
+    /// The tail buffer length is out of range.
+    InvalidBuffer(u8),
+    /// The total input length exceeds `MAX_INPUT_LEN`.
+    InvalidTotalLength(u128),
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::InvalidLength(len) => write!(f, "invalid state length {}", len),
+            StateError::UnsupportedVersion(v) => write!(f, "unsupported state version {}", v),
+            StateError::InvalidDigestSize(tag) => write!(f, "invalid digest size tag {}", tag),
+            StateError::InvalidRoundCount(r) => write!(f, "invalid round count {}", r),
+            StateError::InvalidBuffer(n) => write!(f, "invalid buffered length {}", n),
+            StateError::InvalidTotalLength(len) => write!(f, "invalid total length {}", len),
+        }
+    }
+}
+
+impl core::error::Error for StateError {}
+
+/// Tag bit marking a keccak-backend state.
+const KECCAK_TAG: u8 = 0x10;
+
+fn digest_size_tag(digest_size: DigestSize, backend: Backend) -> u8 {
+    let tag = match digest_size {
+        DigestSize::Bit128 => 1,
+        DigestSize::Bit256 => 2,
+        DigestSize::Bit512 => 3,
+    };
+    match backend {
+        Backend::Native => tag,
+        #[cfg(feature = "keccak")]
+        Backend::KeccakF1600 => tag | KECCAK_TAG,
+    }
+}
+
+fn digest_size_from_tag(tag: u8) -> Option<(DigestSize, Backend)> {
+    let backend = match tag & KECCAK_TAG {
+        0 => Backend::Native,
+        #[cfg(feature = "keccak")]
+        _ => Backend::KeccakF1600,
+        #[cfg(not(feature = "keccak"))]
+        _ => return None,
+    };
+    let digest_size = match tag & !KECCAK_TAG {
+        1 => DigestSize::Bit128,
+        2 => DigestSize::Bit256,
+        3 => DigestSize::Bit512,
+        _ => return None,
+    };
+    Some((digest_size, backend))
+}
+
+impl BlueHashCore {
+    /// 以规范的大端序编码返回当前置换状态字（供审计使用）
+    pub fn state_bytes(&self) -> Vec<u8> {
+        self.words()
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect()
+    }
+
+    /// 导出完整的哈希器状态（规范编码，见模块文档），可用于稍后继续哈希
+    pub fn export_state(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN + self.words().len() * 8);
+        out.push(STATE_FORMAT_VERSION);
+        out.push(digest_size_tag(self.digest_size, self.backend));
+        out.extend_from_slice(&(self.round_count as u32).to_be_bytes());
+        out.extend_from_slice(&self.total_len.to_be_bytes());
+        out.extend_from_slice(&self.input_sum.to_be_bytes());
+        out.extend_from_slice(&self.block_count.to_be_bytes());
+        out.push(self.buffered as u8);
+        out.extend_from_slice(&self.block_buffer);
+        out.extend_from_slice(&self.state_bytes());
+        out
+    }
+
+    /// 从 `export_state` 的输出恢复哈希器
+    pub fn import_state(bytes: &[u8]) -> Result<Self, StateError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(StateError::InvalidLength(bytes.len()));
+        }
+        if bytes[0] != STATE_FORMAT_VERSION {
+            return Err(StateError::UnsupportedVersion(bytes[0]));
+        }
+        let (digest_size, backend) =
+            digest_size_from_tag(bytes[1]).ok_or(StateError::InvalidDigestSize(bytes[1]))?;
+        let mut hasher = BlueHashCore::new(digest_size);
+        hasher.backend = backend;
+        hasher.reset();
+        if bytes.len() != HEADER_LEN + hasher.words().len() * 8 {
+            return Err(StateError::InvalidLength(bytes.len()));
+        }
+        let round_count = u32::from_be_bytes(bytes[2..6].try_into().unwrap());
+        if SecurityProfile::from_round_count(digest_size, round_count as usize).is_none() {
+            return Err(StateError::InvalidRoundCount(round_count));
+        }
+        let buffered = bytes[38];
+        if buffered >= 8 {
+            return Err(StateError::InvalidBuffer(buffered));
+        }
+        let total_len = u128::from_be_bytes(bytes[6..22].try_into().unwrap());
+        if total_len > MAX_INPUT_LEN {
+            return Err(StateError::InvalidTotalLength(total_len));
+        }
+        hasher.round_count = round_count as usize;
+        hasher.total_len = total_len;
+        hasher.input_sum = u64::from_be_bytes(bytes[22..30].try_into().unwrap());
+        hasher.block_count = u64::from_be_bytes(bytes[30..38].try_into().unwrap());
+        hasher.buffered = buffered as usize;
+        hasher.block_buffer.copy_from_slice(&bytes[39..47]);
+        for (word, chunk) in hasher
+            .words_mut()
+            .iter_mut()
+            .zip(bytes[HEADER_LEN..].chunks_exact(8))
+        {
+            *word = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        Ok(hasher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Digest;
+
+    #[test]
+    fn test_state_bytes_are_big_endian() {
+        let hasher = BlueHashCore::new(DigestSize::Bit128);
+        let bytes = hasher.state_bytes();
+        assert_eq!(bytes.len(), 25 * 8);
+        assert_eq!(bytes[..8], [0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF]);
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let mut hasher = BlueHashCore::new(DigestSize::Bit256);
+        hasher.update(b"exported after eleven");
+        let exported = hasher.export_state();
+        assert_eq!(exported[..2], [1, 2]);
+        assert_eq!(exported[6..22], 21u128.to_be_bytes());
+
+        let mut restored = BlueHashCore::import_state(&exported).unwrap();
+        assert_eq!(restored.export_state(), exported);
+        hasher.update(b" bytes");
+        restored.update(b" bytes");
+        assert_eq!(restored.finalize(), hasher.finalize());
+    }
+
+    #[test]
+    fn test_length_extension_fails() {
+        let message = b"sixteen byte msg";
+        let suffix = b"&admin=true";
+        let mut glue = [0u8; 24];
+        glue[0] = 0x80;
+        glue[8..].copy_from_slice(&(message.len() as u128 * 8).to_be_bytes());
+        let mut honest = BlueHashCore::new(DigestSize::Bit256);
+        honest.update(message);
+        honest.update(&glue);
+        honest.update(suffix);
+        let extended = honest.finalize();
+
+        // 攻击一：以摘要作为状态的前几个字，伪造已吸收 m || pad 的哈希器
+        let mut original = BlueHashCore::new(DigestSize::Bit256);
+        original.update(message);
+        let digest = original.clone().finalize();
+        let mut forged = BlueHashCore::new(DigestSize::Bit256).export_state();
+        let glued_len = (message.len() + glue.len()) as u128;
+        forged[6..22].copy_from_slice(&glued_len.to_be_bytes());
+        let sum = message.iter().chain(&glue).map(|&b| b as u64).sum::<u64>();
+        forged[22..30].copy_from_slice(&sum.to_be_bytes());
+        forged[30..38].copy_from_slice(&(glued_len as u64 / 8).to_be_bytes());
+        forged[HEADER_LEN..HEADER_LEN + digest.len()].copy_from_slice(&digest);
+        let mut forged = BlueHashCore::import_state(&forged).unwrap();
+        forged.update(suffix);
+        assert_ne!(forged.finalize(), extended);
+
+        // 攻击二：取得 finalize 之后的完整内部状态，再继续吸收
+        original.finalize();
+        let mut resumed = BlueHashCore::import_state(&original.export_state()).unwrap();
+        resumed.update(&glue);
+        resumed.update(suffix);
+        assert_ne!(resumed.finalize(), extended);
+    }
+
+    #[test]
+    fn test_import_rejects_malformed_state() {
+        let mut exported = BlueHashCore::new(DigestSize::Bit128).export_state();
+        assert_eq!(
+            BlueHashCore::import_state(&exported[..10]).unwrap_err(),
+            StateError::InvalidLength(10)
+        );
+        exported[38] = 9;
+        assert_eq!(
+            BlueHashCore::import_state(&exported).unwrap_err(),
+            StateError::InvalidBuffer(9)
+        );
+        exported[38] = 0;
+        exported[6..22].copy_from_slice(&u128::MAX.to_be_bytes());
+        assert_eq!(
+            BlueHashCore::import_state(&exported).unwrap_err(),
+            StateError::InvalidTotalLength(u128::MAX)
+        );
+    }
+}
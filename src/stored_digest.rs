@@ -0,0 +1,171 @@
+//! Digests stored alongside files in file-system metadata (`xattr` feature).
+//!
+//! Tripwire-style checks need somewhere to keep the expected digest of each
+//! file. These helpers keep it on the file itself, as lowercase hex in an
+//! attribute named after the variant, e.g. `user.bluehash256`:
+//!
+//! * on Unix, in the extended attribute of that name (Linux needs a file
+//!   system mounted with user xattrs, which ext4, XFS, Btrfs and tmpfs are by
+//!   default);
+//! * on Windows, in the NTFS alternate data stream `file:user.bluehash256`.
+//!
+//! Other platforms return `io::ErrorKind::Unsupported`. Metadata travels with
+//! the file only as far as the tools copying it allow (`cp --preserve=xattr`,
+//! `rsync -X`), and anyone who can write the file can usually rewrite the
+//! attribute too, so this detects accidents and careless tampering rather
+//! than a determined attacker; sign a manifest for that.
+
+use crate::cas::algorithm_name;
+use crate::output::DigestOutput;
+use crate::reader::hash_file;
+use crate::DigestSize;
+use alloc::format;
+use alloc::string::String;
+use std::io;
+use std::path::Path;
+
+/// Returns the attribute holding digests of the given size, e.g.
+/// `user.bluehash256`.
+pub fn digest_attribute_name(digest_size: DigestSize) -> String {
+    format!("user.{}", algorithm_name(digest_size))
+}
+
+/// Hashes the file at `path` and stores the digest in its metadata.
+///
+/// # Returns
+///
+/// The stored digest.
+pub fn store_digest<P: AsRef<Path>>(path: P, digest_size: DigestSize) -> io::Result<DigestOutput> {
+    let path = path.as_ref();
+    let digest = hash_file(path, digest_size)?;
+    let digest = DigestOutput::from_bytes(&digest).expect("BlueHash digest length");
+    backend::set(
+        path,
+        &digest_attribute_name(digest_size),
+        digest.to_hex().as_bytes(),
+    )?;
+    Ok(digest)
+}
+
+/// Reads the digest stored by `store_digest`, or `None` if there is none.
+///
+/// # Returns
+///
+/// An `InvalidData` error if the attribute exists but does not hold a digest
+/// of the requested size.
+pub fn stored_digest<P: AsRef<Path>>(
+    path: P,
+    digest_size: DigestSize,
+) -> io::Result<Option<DigestOutput>> {
+    let Some(value) = backend::get(path.as_ref(), &digest_attribute_name(digest_size))? else {
+        return Ok(None);
+    };
+    core::str::from_utf8(&value)
+        .ok()
+        .and_then(|hex| DigestOutput::from_hex(hex).ok())
+        .filter(|digest| digest.digest_size() == digest_size)
+        .map(Some)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed stored digest"))
+}
+
+/// Re-hashes the file at `path` and compares it with its stored digest.
+///
+/// # Returns
+///
+/// `Some(true)` if the contents match, `Some(false)` if they changed, and
+/// `None` if no digest has been stored.
+pub fn verify_stored_digest<P: AsRef<Path>>(
+    path: P,
+    digest_size: DigestSize,
+) -> io::Result<Option<bool>> {
+    let path = path.as_ref();
+    let Some(expected) = stored_digest(path, digest_size)? else {
+        return Ok(None);
+    };
+    let actual = hash_file(path, digest_size)?;
+    Ok(Some(
+        expected == DigestOutput::from_bytes(&actual).expect("BlueHash digest length"),
+    ))
+}
+
+#[cfg(unix)]
+mod backend {
+    use alloc::vec::Vec;
+    use std::io;
+    use std::path::Path;
+
+    pub fn set(path: &Path, name: &str, value: &[u8]) -> io::Result<()> {
+        xattr::set_deref(path, name, value)
+    }
+
+    pub fn get(path: &Path, name: &str) -> io::Result<Option<Vec<u8>>> {
+        xattr::get_deref(path, name)
+    }
+}
+
+#[cfg(windows)]
+mod backend {
+    use alloc::vec::Vec;
+    use std::ffi::OsString;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    fn stream(path: &Path, name: &str) -> PathBuf {
+        let mut stream = OsString::from(path.as_os_str());
+        stream.push(":");
+        stream.push(name);
+        PathBuf::from(stream)
+    }
+
+    pub fn set(path: &Path, name: &str, value: &[u8]) -> io::Result<()> {
+        std::fs::write(stream(path, name), value)
+    }
+
+    pub fn get(path: &Path, name: &str) -> io::Result<Option<Vec<u8>>> {
+        match std::fs::read(stream(path, name)) {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound && path.exists() => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod backend {
+    use alloc::vec::Vec;
+    use std::io;
+    use std::path::Path;
+
+    pub fn set(_path: &Path, _name: &str, _value: &[u8]) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub fn get(_path: &Path, _name: &str) -> io::Result<Option<Vec<u8>>> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_and_verify() {
+        let path = std::env::temp_dir().join(format!(
+            "bluehash_stored_digest_test_{}",
+            std::process::id()
+        ));
+        std::fs::write(&path, b"tripwire").unwrap();
+        let result = (|| {
+            assert_eq!(verify_stored_digest(&path, DigestSize::Bit256)?, None);
+            let digest = store_digest(&path, DigestSize::Bit256)?;
+            assert_eq!(stored_digest(&path, DigestSize::Bit256)?, Some(digest));
+            assert_eq!(stored_digest(&path, DigestSize::Bit128)?, None);
+            assert_eq!(verify_stored_digest(&path, DigestSize::Bit256)?, Some(true));
+            std::fs::write(&path, b"tampered")?;
+            verify_stored_digest(&path, DigestSize::Bit256)
+        })();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(result.unwrap(), Some(false));
+    }
+}
@@ -0,0 +1,210 @@
+//! Passphrase and keyfile stretching.
+//!
+//! `stretch_secret` turns a low-entropy passphrase (or a keyfile) into a set
+//! of independent keys in one call:
+//!
+//! 1. The passphrase and salt are stretched with Balloon hashing (Boneh,
+//!    Corrigan-Gibbs and Schechter, 2016) over BlueHash-256: a buffer of
+//!    `memory_blocks` 32-byte blocks is filled and then mixed `iterations`
+//!    times, each block depending on its predecessor and on three
+//!    pseudo-randomly chosen blocks, so computing the result with less memory
+//!    costs much more time.
+//! 2. The last block keys a BlueHash XOF per label (`"encryption key"`,
+//!    `"mac key"`, `"nonce seed"`), giving keys that are independent of one
+//!    another.
+//!
+//! Every hash in step 1 is prefixed with `len(label) || label || counter`,
+//! with `label = "bluehash balloon v1"`.
+
+use crate::{constant_time_eq, BlueHashCore, Digest, DigestSize};
+use alloc::vec;
+use core::fmt;
+
+const BALLOON_LABEL: &[u8] = b"bluehash balloon v1";
+
+/// Number of pseudo-random dependencies per block and round.
+const DELTA: u64 = 3;
+
+const BLOCK_LEN: usize = 32;
+
+/// Length of each derived key in bytes.
+pub const STRETCHED_KEY_LEN: usize = 32;
+
+/// Cost parameters of `stretch_secret`.
+///
+/// A profile costs `memory_blocks * (1 + 7 * iterations)` BlueHash-256
+/// calls, just under 1 ms each in a release build on a current x86-64 core.
+/// The times quoted for the presets were measured that way. BlueHash is slow
+/// enough that time, not memory, dominates the cost at these sizes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StretchProfile {
+    /// Buffer size in 32-byte blocks.
+    pub memory_blocks: u32,
+    /// Number of mixing passes over the buffer.
+    pub iterations: u32,
+}
+
+impl StretchProfile {
+    /// For interactive logins: 64 blocks (2 KiB), about 0.5 s.
+    pub const INTERACTIVE: StretchProfile = StretchProfile {
+        memory_blocks: 64,
+        iterations: 1,
+    };
+    /// For unlocking local keys: 256 blocks (8 KiB), about 2 s.
+    pub const MODERATE: StretchProfile = StretchProfile {
+        memory_blocks: 256,
+        iterations: 1,
+    };
+    /// For long-term secrets such as wallet seeds and backups: 1024 blocks
+    /// (32 KiB) mixed twice, about 13 s.
+    pub const SENSITIVE: StretchProfile = StretchProfile {
+        memory_blocks: 1024,
+        iterations: 2,
+    };
+}
+
+/// Keys derived by `stretch_secret`. Equality compares all three keys in
+/// constant time.
+#[derive(Clone)]
+pub struct StretchedKeys {
+    pub encryption_key: [u8; STRETCHED_KEY_LEN],
+    pub mac_key: [u8; STRETCHED_KEY_LEN],
+    pub nonce_seed: [u8; STRETCHED_KEY_LEN],
+}
+
+impl PartialEq for StretchedKeys {
+    fn eq(&self, other: &Self) -> bool {
+        constant_time_eq(&self.encryption_key, &other.encryption_key)
+            & constant_time_eq(&self.mac_key, &other.mac_key)
+            & constant_time_eq(&self.nonce_seed, &other.nonce_seed)
+    }
+}
+
+impl Eq for StretchedKeys {}
+
+impl fmt::Debug for StretchedKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StretchedKeys").finish_non_exhaustive()
+    }
+}
+
+struct Balloon {
+    template: BlueHashCore,
+    counter: u64,
+}
+
+impl Balloon {
+    fn hash(&mut self, parts: &[&[u8]]) -> [u8; BLOCK_LEN] {
+        let mut hasher = self.template.clone();
+        hasher.update(&self.counter.to_be_bytes());
+        self.counter += 1;
+        for part in parts {
+            hasher.update(part);
+        }
+        let mut out = [0u8; BLOCK_LEN];
+        hasher.finalize_into(&mut out);
+        out
+    }
+}
+
+fn balloon(passphrase: &[u8], salt: &[u8], profile: StretchProfile) -> [u8; BLOCK_LEN] {
+    let mut template = BlueHashCore::new(DigestSize::Bit256);
+    template.update_framed(BALLOON_LABEL);
+    let mut state = Balloon {
+        template,
+        counter: 0,
+    };
+    let space = profile.memory_blocks as usize;
+    let mut buf = vec![[0u8; BLOCK_LEN]; space];
+
+    // Expand.
+    buf[0] = state.hash(&[&(passphrase.len() as u64).to_be_bytes(), passphrase, salt]);
+    for m in 1..space {
+        buf[m] = state.hash(&[&buf[m - 1]]);
+    }
+
+    // Mix.
+    for t in 0..profile.iterations as u64 {
+        for m in 0..space {
+            let prev = buf[(m + space - 1) % space];
+            buf[m] = state.hash(&[&prev, &buf[m]]);
+            for i in 0..DELTA {
+                let mut index_block = [0u8; 24];
+                index_block[..8].copy_from_slice(&t.to_be_bytes());
+                index_block[8..16].copy_from_slice(&(m as u64).to_be_bytes());
+                index_block[16..].copy_from_slice(&i.to_be_bytes());
+                let choice = state.hash(&[salt, &index_block]);
+                let other = u64::from_be_bytes(choice[..8].try_into().unwrap()) % space as u64;
+                buf[m] = state.hash(&[&buf[m], &buf[other as usize]]);
+            }
+        }
+    }
+    buf[space - 1]
+}
+
+fn expand(root: &[u8; BLOCK_LEN], label: &[u8]) -> [u8; STRETCHED_KEY_LEN] {
+    let mut hasher = BlueHashCore::new_keyed(DigestSize::Bit256, root);
+    hasher.update_framed(label);
+    hasher.finalize_xof().squeeze_array()
+}
+
+/// Stretches a passphrase into an encryption key, a MAC key and a nonce seed.
+///
+/// # Arguments
+///
+/// * `passphrase` - The passphrase or keyfile contents.
+/// * `salt` - A random salt, at least 16 bytes, stored with the protected data.
+/// * `profile` - The memory and time cost.
+///
+/// # Returns
+///
+/// The derived keys.
+///
+/// # Panics
+///
+/// Panics if `profile.memory_blocks` or `profile.iterations` is 0.
+pub fn stretch_secret(passphrase: &[u8], salt: &[u8], profile: StretchProfile) -> StretchedKeys {
+    assert!(
+        profile.memory_blocks > 0 && profile.iterations > 0,
+        "stretch profile costs must be positive"
+    );
+    let root = balloon(passphrase, salt, profile);
+    StretchedKeys {
+        encryption_key: expand(&root, b"encryption key"),
+        mac_key: expand(&root, b"mac key"),
+        nonce_seed: expand(&root, b"nonce seed"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tiny costs keep the test fast in debug builds.
+    const TEST_PROFILE: StretchProfile = StretchProfile {
+        memory_blocks: 4,
+        iterations: 1,
+    };
+
+    #[test]
+    fn test_stretch_secret() {
+        let keys = stretch_secret(b"hunter2", b"0123456789abcdef", TEST_PROFILE);
+        assert_ne!(keys.encryption_key, keys.mac_key);
+        assert_ne!(keys.mac_key, keys.nonce_seed);
+        assert!(keys == stretch_secret(b"hunter2", b"0123456789abcdef", TEST_PROFILE));
+
+        let other_salt = stretch_secret(b"hunter2", b"fedcba9876543210", TEST_PROFILE);
+        assert_ne!(other_salt.encryption_key, keys.encryption_key);
+        let mut tweaked = keys.clone();
+        tweaked.nonce_seed[31] ^= 1;
+        assert!(tweaked != keys);
+        let more_memory = StretchProfile {
+            memory_blocks: 5,
+            ..TEST_PROFILE
+        };
+        assert_ne!(
+            stretch_secret(b"hunter2", b"0123456789abcdef", more_memory).encryption_key,
+            keys.encryption_key
+        );
+    }
+}
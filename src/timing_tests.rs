@@ -0,0 +1,180 @@
+//! dudect-style statistical timing tests.
+//!
+//! Each test times an operation on two input classes, a fixed input and
+//! fresh random inputs, interleaved in random order, and compares the two
+//! timing distributions with Welch's t-test. A |t| above [`T_THRESHOLD`]
+//! indicates a data-dependent timing difference. See Reparaz, Balasch and
+//! Verbauwhede, "Dude, is my code constant time?" (DATE 2017).
+//!
+//! The measurements are noisy on shared machines, so the threshold checks
+//! are `#[ignore]`d and meant to be run explicitly on a quiet host:
+//! `cargo test --features timing-tests -- --ignored timing`.
+//!
+//! `time_update` and `time_keyed` used to report a leak: the LWE noise
+//! sampler behind the round constants stopped at the first accepted
+//! candidate, so its running time depended on the message and the key. It
+//! now processes a fixed number of generator words for every seed.
+
+use crate::{constant_time_eq, BlueHashCore, Digest, DigestSize};
+use rand::Rng;
+use std::hint::black_box;
+use std::time::Instant;
+
+/// |t| above this value is treated as evidence of a timing leak.
+pub const T_THRESHOLD: f64 = 4.5;
+
+/// Online mean/variance accumulator (Welford).
+#[derive(Debug, Clone, Copy, Default)]
+struct Moments {
+    count: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Moments {
+    fn push(&mut self, x: f64) {
+        self.count += 1.0;
+        let delta = x - self.mean;
+        self.mean += delta / self.count;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    fn variance(&self) -> f64 {
+        if self.count < 2.0 {
+            0.0
+        } else {
+            self.m2 / (self.count - 1.0)
+        }
+    }
+}
+
+/// Result of comparing the fixed-input and random-input timing classes.
+#[derive(Debug, Clone, Copy)]
+pub struct TimingReport {
+    /// Welch's t statistic between the two classes.
+    pub t_statistic: f64,
+    /// Number of measurements per class.
+    pub samples_per_class: usize,
+}
+
+impl TimingReport {
+    /// Whether the t statistic exceeds [`T_THRESHOLD`].
+    pub fn is_leaky(&self) -> bool {
+        self.t_statistic.abs() > T_THRESHOLD
+    }
+}
+
+/// Welch's t statistic for two independent samples.
+fn welch_t(a: &Moments, b: &Moments) -> f64 {
+    let denominator = (a.variance() / a.count + b.variance() / b.count).sqrt();
+    if denominator == 0.0 {
+        0.0
+    } else {
+        (a.mean - b.mean) / denominator
+    }
+}
+
+/// Runs a dudect measurement.
+///
+/// # Arguments
+///
+/// * `samples` - Total number of measurements (split between both classes).
+/// * `input_len` - Length of each generated input.
+/// * `operation` - The operation under test; it must take the same code path
+///   for every input of the same length.
+///
+/// # Returns
+///
+/// The t statistic comparing a fixed all-zero input against random inputs.
+pub fn measure<F>(samples: usize, input_len: usize, mut operation: F) -> TimingReport
+where
+    F: FnMut(&[u8]),
+{
+    let mut rng = rand::thread_rng();
+    let fixed = vec![0u8; input_len];
+    let mut random = vec![0u8; input_len];
+    let mut classes = [Moments::default(); 2];
+    for _ in 0..samples {
+        let class = rng.gen_range(0..2usize);
+        let input: &[u8] = if class == 0 {
+            &fixed
+        } else {
+            rng.fill(&mut random[..]);
+            &random
+        };
+        let start = Instant::now();
+        operation(black_box(input));
+        let elapsed = start.elapsed().as_nanos() as f64;
+        classes[class].push(elapsed);
+    }
+    TimingReport {
+        t_statistic: welch_t(&classes[0], &classes[1]),
+        samples_per_class: samples / 2,
+    }
+}
+
+/// Times `update` + `finalize` on fixed versus random messages.
+pub fn time_update(samples: usize) -> TimingReport {
+    measure(samples, 64, |input| {
+        let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+        hasher.update(input);
+        black_box(hasher.finalize());
+    })
+}
+
+/// Times keyed hashing with a fixed versus random key.
+pub fn time_keyed(samples: usize) -> TimingReport {
+    measure(samples, 32, |key| {
+        let mut hasher = BlueHashCore::new_keyed(DigestSize::Bit128, key);
+        hasher.update(b"fixed message");
+        black_box(hasher.finalize());
+    })
+}
+
+/// Times `constant_time_eq` against a fixed reference, comparing an equal
+/// input (all zeros) with random, almost always unequal, inputs.
+pub fn time_constant_time_eq(samples: usize) -> TimingReport {
+    let reference = vec![0u8; 4096];
+    measure(samples, 4096, |input| {
+        black_box(constant_time_eq(&reference, input));
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_welch_t_statistic() {
+        let mut a = Moments::default();
+        let mut b = Moments::default();
+        for x in [1.0, 2.0, 3.0, 4.0] {
+            a.push(x);
+            b.push(x);
+        }
+        assert_eq!(welch_t(&a, &b), 0.0);
+        let mut c = Moments::default();
+        for x in [11.0, 12.0, 13.0, 14.0] {
+            c.push(x);
+        }
+        assert!(welch_t(&a, &c) < -T_THRESHOLD);
+    }
+
+    #[test]
+    #[ignore]
+    fn timing_update_is_constant_time() {
+        assert!(!time_update(20_000).is_leaky());
+    }
+
+    #[test]
+    #[ignore]
+    fn timing_keyed_is_constant_time() {
+        assert!(!time_keyed(20_000).is_leaky());
+    }
+
+    #[test]
+    #[ignore]
+    fn timing_constant_time_eq_is_constant_time() {
+        assert!(!time_constant_time_eq(200_000).is_leaky());
+    }
+}
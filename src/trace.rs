@@ -0,0 +1,139 @@
+//! Round-by-round state introspection (`debug-introspection` feature).
+//!
+//! `TraceHasher` computes the same digest as `BlueHashCore` but records the
+//! permutation state after every round, so the mixing can be studied without
+//! forking the internals. A finalization runs two phases:
+//!
+//! * `TracePhase::Permutation`: rounds `0..round_count`, with round constants
+//!   seeded by the wrapping sum of the input bytes (`HashTrace::noise_seed`);
+//! * `TracePhase::FinalMix`: four more rounds after the total length has been
+//!   mixed in, with constants seeded by the padding block
+//!   (`HashTrace::final_noise_seed`).
+//!
+//! Traces contain the full internal state, so never enable this feature in a
+//! build that hashes secrets.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec::Vec;
+
+/// The phase a traced round belongs to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TracePhase {
+    /// The main permutation rounds.
+    Permutation,
+    /// The extra rounds after the length has been mixed in.
+    FinalMix,
+}
+
+/// State after one permutation round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundSnapshot {
+    /// The phase of the round.
+    pub phase: TracePhase,
+    /// The round number, counted across both phases.
+    pub round: usize,
+    /// The round constant mixed into the state.
+    pub constant: u64,
+    /// The state words after the round.
+    pub state: Vec<u64>,
+}
+
+/// Everything recorded while finalizing a `TraceHasher`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashTrace {
+    /// The digest size that was traced.
+    pub digest_size: DigestSize,
+    /// The wrapping sum of the input bytes, which seeds the round constants of
+    /// the permutation phase.
+    pub noise_seed: u64,
+    /// The wrapping sum of the padding block, which seeds the round constants
+    /// of the final-mix phase.
+    pub final_noise_seed: u64,
+    /// The state after absorbing the whole input, before the first round.
+    pub absorbed_state: Vec<u64>,
+    /// One snapshot per round, in order.
+    pub rounds: Vec<RoundSnapshot>,
+    /// The digest, equal to `BlueHashCore::finalize` on the same input.
+    pub digest: Vec<u8>,
+}
+
+/// A hasher that records its state after each permutation round.
+#[derive(Debug, Clone)]
+pub struct TraceHasher {
+    hasher: BlueHashCore,
+}
+
+impl TraceHasher {
+    /// Creates a tracing hasher with the default configuration.
+    pub fn new(digest_size: DigestSize) -> Self {
+        Self::from_hasher(BlueHashCore::new(digest_size))
+    }
+
+    /// Traces an already configured hasher, e.g. one from `BlueHashBuilder`.
+    pub fn from_hasher(hasher: BlueHashCore) -> Self {
+        Self { hasher }
+    }
+
+    /// Absorbs data.
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+    }
+
+    /// Finalizes the hash and returns the trace.
+    pub fn finalize(mut self) -> HashTrace {
+        let hasher = &mut self.hasher;
+        hasher.flush_tail();
+        let absorbed_state = hasher.words().to_vec();
+        let mut rounds = Vec::with_capacity(hasher.round_count + 4);
+        hasher.run_rounds(&mut |round, constant, state| {
+            rounds.push(RoundSnapshot {
+                phase: TracePhase::Permutation,
+                round,
+                constant,
+                state: state.to_vec(),
+            })
+        });
+        let final_noise_seed = hasher
+            .pad()
+            .iter()
+            .fold(0u64, |acc, &byte| acc.wrapping_add(byte as u64));
+        hasher.final_mix_observed(&mut |round, constant, state| {
+            rounds.push(RoundSnapshot {
+                phase: TracePhase::FinalMix,
+                round,
+                constant,
+                state: state.to_vec(),
+            })
+        });
+        HashTrace {
+            digest_size: hasher.digest_size,
+            noise_seed: hasher.input_sum,
+            final_noise_seed,
+            absorbed_state,
+            rounds,
+            digest: hasher.output_bytes(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_matches_digest() {
+        let mut tracer = TraceHasher::new(DigestSize::Bit128);
+        tracer.update(b"trace me");
+        let trace = tracer.finalize();
+
+        let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+        hasher.update(b"trace me");
+        assert_eq!(trace.digest, hasher.finalize());
+
+        assert_eq!(trace.rounds.len(), DigestSize::Bit128.round_count() + 4);
+        assert_eq!(trace.rounds[0].phase, TracePhase::Permutation);
+        assert_eq!(trace.rounds.last().unwrap().phase, TracePhase::FinalMix);
+        assert!(trace.rounds.iter().all(|r| r.state.len() == 25));
+        assert_ne!(trace.absorbed_state, trace.rounds[0].state);
+    }
+}
@@ -0,0 +1,224 @@
+//! Node hashing for state tries.
+//!
+//! Trie libraries for Ethereum-style state commitments are usually generic
+//! over a node hasher so that Keccak can be swapped for another function.
+//! `NodeHasher` captures that interface, and this module provides BlueHash
+//! implementations for binary tries (as in the proposed unified binary tree)
+//! and hexary Patricia tries:
+//!
+//! ```text
+//! leaf(key, value)        = BlueHash(leaf_tag || u64(len(key)) || key || value)
+//! extension(path, child)  = BlueHash(extension_tag || u64(len(path)) || path || child)
+//! binary(left, right)     = BlueHash(branch_tag || left || right)
+//! hexary(c0, ..., c15)    = BlueHash(branch_tag || u16(bitmap) || non-empty children)
+//! ```
+//!
+//! Integers are big-endian. An empty subtree hashes to all zeros, and a branch
+//! whose children are all empty is itself empty, so sparse tries need not
+//! materialize unused subtrees. The domain tags default to `DomainTags::DEFAULT`
+//! and can be replaced per chain with `with_tags`.
+
+use crate::{BlueHashCore, Digest, DigestSize};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Hashing rules for the nodes of a trie.
+pub trait NodeHasher {
+    /// Length of every node hash in bytes.
+    fn hash_len(&self) -> usize;
+
+    /// Hash of an empty subtree.
+    fn empty_hash(&self) -> Vec<u8> {
+        vec![0; self.hash_len()]
+    }
+
+    /// Hashes a leaf holding `value` under the (remaining) key path `key`.
+    fn hash_leaf(&self, key: &[u8], value: &[u8]) -> Vec<u8>;
+
+    /// Hashes a branch from the hashes of its children, in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of children does not match the trie arity.
+    fn hash_branch(&self, children: &[&[u8]]) -> Vec<u8>;
+
+    /// Hashes an extension node sharing the key path `path` above `child`.
+    fn hash_extension(&self, path: &[u8], child: &[u8]) -> Vec<u8>;
+}
+
+/// One-byte domain tags prepended to every node encoding.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DomainTags {
+    pub leaf: u8,
+    pub branch: u8,
+    pub extension: u8,
+}
+
+impl DomainTags {
+    /// Tags used unless a chain chooses its own. They are disjoint from the
+    /// `merkle` module prefixes.
+    pub const DEFAULT: DomainTags = DomainTags {
+        leaf: 0x10,
+        branch: 0x11,
+        extension: 0x12,
+    };
+}
+
+impl Default for DomainTags {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+fn leaf_like(digest_size: DigestSize, tag: u8, path: &[u8], tail: &[u8]) -> Vec<u8> {
+    let mut hasher = BlueHashCore::new(digest_size);
+    hasher.update(&[tag]);
+    hasher.update_framed(path);
+    hasher.update(tail);
+    hasher.finalize()
+}
+
+fn is_empty(hash: &[u8]) -> bool {
+    hash.iter().all(|&b| b == 0)
+}
+
+/// Node hasher for binary tries.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BinaryTrieHasher {
+    digest_size: DigestSize,
+    tags: DomainTags,
+}
+
+impl BinaryTrieHasher {
+    pub fn new(digest_size: DigestSize) -> Self {
+        Self {
+            digest_size,
+            tags: DomainTags::DEFAULT,
+        }
+    }
+
+    /// Replaces the domain tags.
+    pub fn with_tags(mut self, tags: DomainTags) -> Self {
+        self.tags = tags;
+        self
+    }
+}
+
+impl NodeHasher for BinaryTrieHasher {
+    fn hash_len(&self) -> usize {
+        self.digest_size.digest_length()
+    }
+
+    fn hash_leaf(&self, key: &[u8], value: &[u8]) -> Vec<u8> {
+        leaf_like(self.digest_size, self.tags.leaf, key, value)
+    }
+
+    fn hash_branch(&self, children: &[&[u8]]) -> Vec<u8> {
+        assert_eq!(children.len(), 2, "binary branches have two children");
+        if children.iter().all(|child| is_empty(child)) {
+            return self.empty_hash();
+        }
+        let mut hasher = BlueHashCore::new(self.digest_size);
+        hasher.update(&[self.tags.branch]);
+        hasher.update(children[0]);
+        hasher.update(children[1]);
+        hasher.finalize()
+    }
+
+    fn hash_extension(&self, path: &[u8], child: &[u8]) -> Vec<u8> {
+        leaf_like(self.digest_size, self.tags.extension, path, child)
+    }
+}
+
+/// Node hasher for hexary (16-way) Patricia tries.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HexaryTrieHasher {
+    digest_size: DigestSize,
+    tags: DomainTags,
+}
+
+impl HexaryTrieHasher {
+    pub fn new(digest_size: DigestSize) -> Self {
+        Self {
+            digest_size,
+            tags: DomainTags::DEFAULT,
+        }
+    }
+
+    /// Replaces the domain tags.
+    pub fn with_tags(mut self, tags: DomainTags) -> Self {
+        self.tags = tags;
+        self
+    }
+}
+
+impl NodeHasher for HexaryTrieHasher {
+    fn hash_len(&self) -> usize {
+        self.digest_size.digest_length()
+    }
+
+    fn hash_leaf(&self, key: &[u8], value: &[u8]) -> Vec<u8> {
+        leaf_like(self.digest_size, self.tags.leaf, key, value)
+    }
+
+    fn hash_branch(&self, children: &[&[u8]]) -> Vec<u8> {
+        assert_eq!(children.len(), 16, "hexary branches have sixteen children");
+        let bitmap = children
+            .iter()
+            .enumerate()
+            .filter(|(_, child)| !is_empty(child))
+            .fold(0u16, |bitmap, (i, _)| bitmap | (1 << i));
+        if bitmap == 0 {
+            return self.empty_hash();
+        }
+        let mut hasher = BlueHashCore::new(self.digest_size);
+        hasher.update(&[self.tags.branch]);
+        hasher.update(&bitmap.to_be_bytes());
+        for child in children.iter().filter(|child| !is_empty(child)) {
+            hasher.update(child);
+        }
+        hasher.finalize()
+    }
+
+    fn hash_extension(&self, path: &[u8], child: &[u8]) -> Vec<u8> {
+        leaf_like(self.digest_size, self.tags.extension, path, child)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_trie_hashing() {
+        let hasher = BinaryTrieHasher::new(DigestSize::Bit256);
+        let empty = hasher.empty_hash();
+        assert_eq!(empty.len(), 32);
+        assert_eq!(hasher.hash_branch(&[&empty, &empty]), empty);
+
+        let leaf = hasher.hash_leaf(b"key", b"value");
+        assert_ne!(leaf, hasher.hash_extension(b"key", b"value"));
+        let left = hasher.hash_branch(&[&leaf, &empty]);
+        assert_ne!(left, hasher.hash_branch(&[&empty, &leaf]));
+
+        let retagged = hasher.with_tags(DomainTags {
+            leaf: 0,
+            ..DomainTags::DEFAULT
+        });
+        assert_ne!(retagged.hash_leaf(b"key", b"value"), leaf);
+    }
+
+    #[test]
+    fn test_hexary_branch_binds_positions() {
+        let hasher = HexaryTrieHasher::new(DigestSize::Bit256);
+        let empty = hasher.empty_hash();
+        let leaf = hasher.hash_leaf(&[0x0a], b"balance");
+        let mut children: [&[u8]; 16] = [&empty; 16];
+        assert_eq!(hasher.hash_branch(&children), empty);
+
+        children[3] = &leaf;
+        let at_three = hasher.hash_branch(&children);
+        children.swap(3, 4);
+        assert_ne!(hasher.hash_branch(&children), at_three);
+    }
+}
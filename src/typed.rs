@@ -0,0 +1,182 @@
+//! Compile-time digest sizes.
+//!
+//! `TypedBlueHash<BITS>` wraps `BlueHashCore` with the digest size fixed by a
+//! const generic, so generic code is monomorphized per size and the digest
+//! comes back as an array instead of a `Vec`. `BITS` must be 128, 256 or 512;
+//! anything else is rejected when the type is used:
+//!
+//! ```compile_fail
+//! let hasher = BlueHash::TypedBlueHash::<100>::new();
+//! ```
+//!
+//! The sizes a protocol needs for its buffers are associated constants, both
+//! inherent (`BlueHash256::OUTPUT_SIZE`) and through `HashParameters` for code
+//! generic over the hasher type.
+//!
+//! The type is not named `BlueHash`, so a glob import of the crate does not
+//! make the name ambiguous:
+//!
+//! ```
+//! use BlueHash::*;
+//!
+//! let mut hasher = BlueHash256::new();
+//! hasher.update(b"glob");
+//! let digest: [u8; 32] = hasher.finalize();
+//! assert_eq!(digest.len(), TypedBlueHash::<256>::OUTPUT_SIZE);
+//! ```
+
+use crate::{BlueHashCore, Digest, DigestSize, BLOCK_LEN};
+
+/// Compile-time sizes of a typed hasher.
+pub trait HashParameters {
+    /// Bytes absorbed per input block.
+    const BLOCK_SIZE: usize;
+    /// Bytes of input absorbed between two permutations (the sponge rate,
+    /// `DigestSize::rate_bytes`). This is not the XOF output block, which is
+    /// half the state.
+    const RATE: usize;
+    /// Digest length in bytes.
+    const OUTPUT_SIZE: usize;
+    /// Number of 64-bit words in the permutation state.
+    const STATE_WORDS: usize;
+}
+
+/// BlueHash with a digest size of `BITS` bits, checked at compile time.
+#[derive(Debug, Clone)]
+pub struct TypedBlueHash<const BITS: usize> {
+    core: BlueHashCore,
+}
+
+/// BlueHash-128.
+pub type BlueHash128 = TypedBlueHash<128>;
+/// BlueHash-256.
+pub type BlueHash256 = TypedBlueHash<256>;
+/// BlueHash-512.
+pub type BlueHash512 = TypedBlueHash<512>;
+
+impl<const BITS: usize> TypedBlueHash<BITS> {
+    /// The runtime digest size matching `BITS`.
+    pub const DIGEST_SIZE: DigestSize = match BITS {
+        128 => DigestSize::Bit128,
+        256 => DigestSize::Bit256,
+        512 => DigestSize::Bit512,
+        _ => panic!("BlueHash digest size must be 128, 256 or 512 bits"),
+    };
+
+    /// Bytes absorbed per input block.
+    pub const BLOCK_SIZE: usize = BLOCK_LEN;
+    /// Bytes of input absorbed between two permutations (the sponge rate,
+    /// `DigestSize::rate_bytes`). This is not the XOF output block, which is
+    /// half the state.
+    pub const RATE: usize = Self::DIGEST_SIZE.rate_bytes();
+    /// Digest length in bytes.
+    pub const OUTPUT_SIZE: usize = Self::DIGEST_SIZE.digest_length();
+    /// Number of 64-bit words in the permutation state.
+    pub const STATE_WORDS: usize = Self::DIGEST_SIZE.state_size();
+
+    /// Creates a hasher with the default configuration.
+    pub fn new() -> Self {
+        Self {
+            core: BlueHashCore::new(Self::DIGEST_SIZE),
+        }
+    }
+
+    /// Creates a keyed hasher.
+    pub fn new_keyed(key: &[u8]) -> Self {
+        Self {
+            core: BlueHashCore::new_keyed(Self::DIGEST_SIZE, key),
+        }
+    }
+
+    /// Absorbs data.
+    pub fn update(&mut self, data: &[u8]) {
+        self.core.update(data);
+    }
+
+    /// Returns the digest as an array; `N` must equal `BITS / 8`, which is
+    /// checked at compile time.
+    pub fn finalize_array<const N: usize>(mut self) -> [u8; N] {
+        const { assert!(N * 8 == BITS, "output array must be BITS / 8 bytes") };
+        let mut out = [0u8; N];
+        self.core.finalize_into(&mut out);
+        out
+    }
+
+    /// Unwraps the underlying runtime hasher.
+    pub fn into_core(self) -> BlueHashCore {
+        self.core
+    }
+}
+
+impl<const BITS: usize> Default for TypedBlueHash<BITS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const BITS: usize> HashParameters for TypedBlueHash<BITS> {
+    const BLOCK_SIZE: usize = Self::BLOCK_SIZE;
+    const RATE: usize = Self::RATE;
+    const OUTPUT_SIZE: usize = Self::OUTPUT_SIZE;
+    const STATE_WORDS: usize = Self::STATE_WORDS;
+}
+
+macro_rules! impl_finalize {
+    ($($bits:literal => $len:literal),*) => {
+        $(
+            impl TypedBlueHash<$bits> {
+                /// Returns the digest.
+                pub fn finalize(self) -> [u8; $len] {
+                    self.finalize_array()
+                }
+            }
+        )*
+    };
+}
+
+impl_finalize!(128 => 16, 256 => 32, 512 => 64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_matches_runtime() {
+        fn typed_digest<const BITS: usize, const N: usize>(data: &[u8]) -> [u8; N] {
+            let mut hasher = TypedBlueHash::<BITS>::new();
+            hasher.update(data);
+            hasher.finalize_array()
+        }
+
+        let mut runtime = BlueHashCore::new(DigestSize::Bit256);
+        runtime.update(b"typed");
+        let digest = runtime.finalize();
+        assert_eq!(typed_digest::<256, 32>(b"typed")[..], digest[..]);
+
+        let mut hasher = BlueHash256::new();
+        hasher.update(b"typed");
+        assert_eq!(hasher.finalize()[..], digest[..]);
+        assert_eq!(BlueHash512::DIGEST_SIZE, DigestSize::Bit512);
+    }
+
+    #[test]
+    fn test_associated_constants() {
+        fn output_size<H: HashParameters>() -> usize {
+            H::OUTPUT_SIZE
+        }
+
+        assert_eq!(output_size::<BlueHash256>(), 32);
+        assert_eq!(BlueHash128::STATE_WORDS, 25);
+        assert_eq!(BlueHash128::RATE, 168);
+        assert_eq!(BlueHash512::RATE, 192);
+        assert_eq!(BlueHash256::BLOCK_SIZE, 8);
+
+        // RATE sizes input blocks; it is not the XOF output block.
+        let mut hasher = BlueHash128::new();
+        hasher.update(&[0u8; BlueHash128::RATE]);
+        let mut reader = hasher.into_core().finalize_xof();
+        let _digest: [u8; BlueHash128::OUTPUT_SIZE] = reader.squeeze_array();
+        let mut buffer = [0u8; BlueHash512::OUTPUT_SIZE];
+        BlueHash512::new().into_core().finalize_into(&mut buffer);
+    }
+}
@@ -1,6 +1,8 @@
 //! Utility functions used in the BlueHash algorithm.
 // <Author: BlueOkanna>
 // <Email: blueokanna@gmail.com>
+use alloc::string::String;
+
 /// Converts a slice of bytes into a 64-bit unsigned integer.
 ///
 /// # Arguments
@@ -13,3 +15,125 @@
 pub fn to_u64(chunk: &[u8]) -> u64 {
     chunk.iter().fold(0, |acc, &b| (acc << 8) | b as u64)
 }
+
+/// Converts a slice of bytes into a lowercase hexadecimal string.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to be encoded.
+///
+/// # Returns
+///
+/// A string containing two lowercase hex digits per input byte.
+pub fn to_hex_string(bytes: &[u8]) -> String {
+    const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for &byte in bytes {
+        hex.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+        hex.push(HEX_DIGITS[(byte & 0x0f) as usize] as char);
+    }
+    hex
+}
+
+/// Decodes a single ASCII hex digit (upper or lower case).
+///
+/// # Arguments
+///
+/// * `digit` - The ASCII character to decode.
+///
+/// # Returns
+///
+/// The 4-bit value of the digit, or `None` if it is not a hex digit.
+pub fn hex_value(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Encodes bytes as standard base64 (RFC 4648 alphabet).
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to be encoded.
+/// * `pad` - Whether to append `=` padding to a multiple of four characters.
+///
+/// # Returns
+///
+/// The base64 string.
+pub fn to_base64(bytes: &[u8], pad: bool) -> String {
+    encode_base64(
+        bytes,
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/",
+        pad,
+    )
+}
+
+/// Encodes bytes as unpadded base64url (RFC 4648 section 5), as used by JOSE.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to be encoded.
+///
+/// # Returns
+///
+/// The base64url string without padding.
+pub fn to_base64url(bytes: &[u8]) -> String {
+    encode_base64(
+        bytes,
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+        false,
+    )
+}
+
+/// Encodes bytes as base32 (RFC 4648 section 6), as used by OTP secrets.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to be encoded.
+/// * `pad` - Whether to append `=` padding to a multiple of eight characters.
+///
+/// # Returns
+///
+/// The base32 string.
+pub fn to_base32(bytes: &[u8], pad: bool) -> String {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    for chunk in bytes.chunks(5) {
+        let mut b = [0u8; 8];
+        b[3..3 + chunk.len()].copy_from_slice(chunk);
+        let n = u64::from_be_bytes(b);
+        let symbols = (chunk.len() * 8).div_ceil(5);
+        for i in 0..8 {
+            if i < symbols {
+                out.push(ALPHABET[((n >> (35 - 5 * i)) & 0x1f) as usize] as char);
+            } else if pad {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn encode_base64(bytes: &[u8], alphabet: &[u8; 64], pad: bool) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+        let symbols = chunk.len() + 1;
+        for i in 0..4 {
+            if i < symbols {
+                out.push(alphabet[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else if pad {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,212 @@
+//! Incremental re-hashing of a directory tree.
+//!
+//! `TreeWatcher` keeps the digest, size and modification time of every
+//! regular file below a root. Each `rescan` lists the tree again and re-hashes
+//! only files that are new or whose size or modification time changed, then
+//! reports what happened as `ChangeEvent`s; a file whose metadata changed but
+//! whose contents did not produces no event. This is the core of an
+//! integrity-monitoring agent: call `rescan` on a timer, or from a file-system
+//! notification callback, and forward the events.
+//!
+//! There is no `notify` feature and the watcher does not subscribe to
+//! file-system notifications itself; it only detects changes when `rescan`
+//! is called, and each rescan lists the whole tree. An agent that wants
+//! prompt events wires a notification library (such as the `notify` crate)
+//! to `rescan` and keeps a timer as a fallback for missed notifications.
+
+use crate::manifest::{walk, Manifest};
+use crate::output::DigestOutput;
+use crate::reader::hash_file;
+use crate::DigestSize;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// A change found by `TreeWatcher::rescan`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeEvent {
+    /// A file appeared.
+    Added { path: PathBuf, digest: DigestOutput },
+    /// A file's contents changed.
+    Modified {
+        path: PathBuf,
+        old: DigestOutput,
+        new: DigestOutput,
+    },
+    /// A file disappeared.
+    Removed { path: PathBuf, digest: DigestOutput },
+    /// A file or directory could not be read; its previous digest, if any,
+    /// is kept until it can.
+    Unreadable { path: PathBuf, kind: io::ErrorKind },
+}
+
+#[derive(Debug, Clone)]
+struct Tracked {
+    len: u64,
+    modified: Option<SystemTime>,
+    digest: DigestOutput,
+}
+
+/// Tracks the digests of the files below a directory.
+#[derive(Debug, Clone)]
+pub struct TreeWatcher {
+    root: PathBuf,
+    digest_size: DigestSize,
+    files: BTreeMap<PathBuf, Tracked>,
+}
+
+impl TreeWatcher {
+    /// Creates a watcher that knows no files yet, so the first `rescan`
+    /// reports every file as added.
+    pub fn new<P: Into<PathBuf>>(root: P, digest_size: DigestSize) -> Self {
+        Self {
+            root: root.into(),
+            digest_size,
+            files: BTreeMap::new(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Lists the tree and re-hashes new and changed files.
+    ///
+    /// # Returns
+    ///
+    /// The changes since the previous scan, sorted by path, or an error if the
+    /// root itself cannot be listed.
+    pub fn rescan(&mut self) -> io::Result<Vec<ChangeEvent>> {
+        let mut failed = Vec::new();
+        let mut found = walk(&self.root, &mut failed)?;
+        found.sort();
+        let mut events = Vec::new();
+        let mut unreadable: Vec<PathBuf> = failed.iter().map(|(path, _)| path.clone()).collect();
+        for (path, err) in failed {
+            events.push(ChangeEvent::Unreadable {
+                path,
+                kind: err.kind(),
+            });
+        }
+
+        for path in &found {
+            if let Err(err) = self.refresh(path, &mut events) {
+                events.push(ChangeEvent::Unreadable {
+                    path: path.clone(),
+                    kind: err.kind(),
+                });
+                unreadable.push(path.clone());
+            }
+        }
+
+        // Files below an unreadable directory are kept, not reported removed.
+        let gone: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|path| found.binary_search(path).is_err())
+            .filter(|path| !unreadable.iter().any(|dir| path.starts_with(dir)))
+            .cloned()
+            .collect();
+        for path in gone {
+            let tracked = self.files.remove(&path).expect("listed above");
+            events.push(ChangeEvent::Removed {
+                path,
+                digest: tracked.digest,
+            });
+        }
+        events.sort_by(|a, b| event_path(a).cmp(event_path(b)));
+        Ok(events)
+    }
+
+    fn refresh(&mut self, path: &Path, events: &mut Vec<ChangeEvent>) -> io::Result<()> {
+        let full = self.root.join(path);
+        let metadata = fs::metadata(&full)?;
+        let (len, modified) = (metadata.len(), metadata.modified().ok());
+        let previous = self.files.get(path);
+        if let Some(tracked) = previous {
+            if tracked.len == len && tracked.modified.is_some() && tracked.modified == modified {
+                return Ok(());
+            }
+        }
+        let digest = hash_file(&full, self.digest_size)?;
+        let digest = DigestOutput::from_bytes(&digest).expect("BlueHash digest length");
+        match previous {
+            None => events.push(ChangeEvent::Added {
+                path: path.to_path_buf(),
+                digest: digest.clone(),
+            }),
+            Some(tracked) if tracked.digest != digest => events.push(ChangeEvent::Modified {
+                path: path.to_path_buf(),
+                old: tracked.digest.clone(),
+                new: digest.clone(),
+            }),
+            Some(_) => {}
+        }
+        self.files.insert(
+            path.to_path_buf(),
+            Tracked {
+                len,
+                modified,
+                digest,
+            },
+        );
+        Ok(())
+    }
+
+    /// Returns the digests as of the last scan.
+    pub fn manifest(&self) -> Manifest {
+        let mut manifest = Manifest::new();
+        for (path, tracked) in &self.files {
+            manifest.insert(path.clone(), tracked.digest.clone());
+        }
+        manifest
+    }
+}
+
+fn event_path(event: &ChangeEvent) -> &Path {
+    match event {
+        ChangeEvent::Added { path, .. }
+        | ChangeEvent::Modified { path, .. }
+        | ChangeEvent::Removed { path, .. }
+        | ChangeEvent::Unreadable { path, .. } => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rescan_reports_changes_only() {
+        let root = std::env::temp_dir().join(format!("bluehash_watch_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a.txt"), b"alpha").unwrap();
+        fs::write(root.join("b.txt"), b"beta").unwrap();
+
+        let mut watcher = TreeWatcher::new(&root, DigestSize::Bit128);
+        let first = watcher.rescan().unwrap();
+        assert_eq!(first.len(), 2);
+        assert!(matches!(&first[0], ChangeEvent::Added { path, .. } if path == Path::new("a.txt")));
+        assert_eq!(watcher.rescan().unwrap(), Vec::new());
+
+        fs::write(root.join("a.txt"), b"alpha, edited").unwrap();
+        fs::remove_file(root.join("b.txt")).unwrap();
+        let second = watcher.rescan();
+        let manifest = watcher.manifest();
+        fs::remove_dir_all(&root).unwrap();
+
+        let second = second.unwrap();
+        assert_eq!(second.len(), 2);
+        assert!(
+            matches!(&second[0], ChangeEvent::Modified { path, .. } if path == Path::new("a.txt"))
+        );
+        assert!(
+            matches!(&second[1], ChangeEvent::Removed { path, .. } if path == Path::new("b.txt"))
+        );
+        assert_eq!(manifest.len(), 1);
+    }
+}
@@ -0,0 +1,277 @@
+//! Extendable-output (XOF) mode.
+//!
+//! After finalization the state is frozen and output is produced in
+//! counter mode: block `i` is derived from the frozen state with the block
+//! counter XORed into the rate and the full permutation (as many rounds as
+//! the hasher's absorb phase) applied, so every output word depends on the
+//! counter. The round constants are derived once when the reader is
+//! created, so a block costs only the permutation rounds. Squeezing never
+//! touches the heap and any block can be computed independently of the ones
+//! before it. `seek` uses this to jump to
+//! any byte position without producing the output before it. The keccak
+//! backend derives its blocks the same way, with keccak-f[1600] in place of
+//! the native rounds.
+
+use crate::constants::generate_constants_from_seed;
+use crate::{Backend, DigestSize, Permutation, MAX_STATE_WORDS};
+
+/// Domain separation constant XORed into the state before an XOF finalization.
+pub(crate) const XOF_DOMAIN: u64 = 0x584F_465F_424C_5545;
+
+/// Largest output block, in bytes: the keccak rate for 128-bit digests,
+/// which exceeds half of the largest native state.
+const MAX_RATE: usize = 168;
+
+const _: () = assert!(MAX_RATE >= MAX_STATE_WORDS / 2 * 8);
+
+/// Largest round count of any profile: `Paranoid` BlueHash-512.
+const MAX_ROUNDS: usize = 240;
+
+const _: () = assert!(MAX_ROUNDS >= DigestSize::Bit512.round_count() / 2 * 3);
+
+/// Reader producing an arbitrary amount of output from a finalized hasher.
+#[derive(Debug, Clone)]
+pub struct XofReader {
+    base: [u64; MAX_STATE_WORDS],
+    state_size: usize,
+    rounds: usize,
+    constants: [u64; MAX_ROUNDS],
+    permutation: &'static dyn Permutation,
+    #[cfg_attr(not(feature = "keccak"), allow(dead_code))]
+    backend: Backend,
+    rate: usize,
+    block: [u8; MAX_RATE],
+    block_index: u64,
+    offset: usize,
+}
+
+impl XofReader {
+    pub(crate) fn new(
+        state: &[u64],
+        round_base: usize,
+        rounds: usize,
+        permutation: &'static dyn Permutation,
+        backend: Backend,
+        rate: usize,
+    ) -> Self {
+        let mut base = [0u64; MAX_STATE_WORDS];
+        base[..state.len()].copy_from_slice(state);
+        // The seed only reaches a round constant through the top byte of the
+        // LWE noise, which the bounded noise never changes, so the constants
+        // are the same for every block and need deriving only once.
+        let mut constants = [0u64; MAX_ROUNDS];
+        for (round, constant) in constants[..rounds].iter_mut().enumerate() {
+            *constant = generate_constants_from_seed(round_base + round, 0);
+        }
+        let mut reader = Self {
+            base,
+            state_size: state.len(),
+            rounds,
+            constants,
+            permutation,
+            backend,
+            rate,
+            block: [0u8; MAX_RATE],
+            block_index: 0,
+            offset: 0,
+        };
+        reader.fill_block();
+        reader
+    }
+
+    /// Number of output bytes produced per block: half of the state for the
+    /// native backend (so the other half is never revealed), the sponge rate
+    /// for the keccak backend.
+    fn rate(&self) -> usize {
+        self.rate
+    }
+
+    /// Computes the output block for the current `block_index`.
+    fn fill_block(&mut self) {
+        let mut state = [0u64; MAX_STATE_WORDS];
+        let state = &mut state[..self.state_size];
+        state.copy_from_slice(&self.base[..self.state_size]);
+        #[cfg(feature = "keccak")]
+        if self.backend == Backend::KeccakF1600 {
+            // The counter goes into the last lane, which is always capacity.
+            state[self.state_size - 1] ^= self.block_index;
+            crate::keccak::keccak_f1600(state);
+            let rate = self.rate;
+            for (chunk, lane) in self.block[..rate].chunks_exact_mut(8).zip(&state[..]) {
+                chunk.copy_from_slice(&lane.to_le_bytes());
+            }
+            self.offset = 0;
+            return;
+        }
+        // The permutation moves a word's influence three words per round, so
+        // the full round count spreads the counter over the whole state.
+        state[0] ^= self.block_index;
+        for &constant in &self.constants[..self.rounds] {
+            self.permutation.mix(state, constant);
+        }
+        let rate_words = self.state_size / 2;
+        for (chunk, word) in self.block.chunks_exact_mut(8).zip(&state[..rate_words]) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        self.offset = 0;
+    }
+
+    /// Fills `out` with the next output bytes. Never allocates.
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - Caller-provided buffer to be filled completely.
+    pub fn squeeze(&mut self, out: &mut [u8]) {
+        let rate = self.rate();
+        let mut written = 0;
+        while written < out.len() {
+            if self.offset == rate {
+                self.block_index = self.block_index.wrapping_add(1);
+                self.fill_block();
+            }
+            let take = (rate - self.offset).min(out.len() - written);
+            out[written..written + take]
+                .copy_from_slice(&self.block[self.offset..self.offset + take]);
+            self.offset += take;
+            written += take;
+        }
+    }
+
+    /// Fills `out` with the output starting at the current position; the same
+    /// as `squeeze`, named for use after `seek`.
+    pub fn fill(&mut self, out: &mut [u8]) {
+        self.squeeze(out);
+    }
+
+    /// Moves to byte `position` of the output stream, so the next `fill`
+    /// returns the same bytes as squeezing linearly from the start and
+    /// discarding the first `position` bytes.
+    pub fn seek(&mut self, position: u64) {
+        let rate = self.rate() as u64;
+        self.block_index = position / rate;
+        self.fill_block();
+        self.offset = (position % rate) as usize;
+    }
+
+    /// Returns the current byte position in the output stream, modulo 2^64
+    /// (like the block counter, the position wraps instead of panicking).
+    pub fn position(&self) -> u64 {
+        self.block_index
+            .wrapping_mul(self.rate() as u64)
+            .wrapping_add(self.offset as u64)
+    }
+
+    /// Returns the next `N` output bytes as an array. Never allocates.
+    pub fn squeeze_array<const N: usize>(&mut self) -> [u8; N] {
+        let mut out = [0u8; N];
+        self.squeeze(&mut out);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::generate_constants_from_seed;
+    use crate::{BlueHashCore, Digest, DigestSize};
+    use alloc::collections::BTreeSet;
+    use alloc::vec::Vec;
+
+    fn reader(data: &[u8]) -> crate::XofReader {
+        let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+        hasher.update(data);
+        hasher.finalize_xof()
+    }
+
+    #[test]
+    fn test_squeeze_is_split_independent() {
+        let mut whole = [0u8; 300];
+        reader(b"xof").squeeze(&mut whole);
+
+        let mut parts = reader(b"xof");
+        let mut split = [0u8; 300];
+        let (a, rest) = split.split_at_mut(7);
+        let (b, c) = rest.split_at_mut(150);
+        parts.squeeze(a);
+        parts.squeeze(b);
+        parts.squeeze(c);
+        assert_eq!(whole, split);
+
+        let array: [u8; 300] = reader(b"xof").squeeze_array();
+        assert_eq!(array, whole);
+    }
+
+    #[test]
+    fn test_seek_matches_linear_squeeze() {
+        let mut linear = [0u8; 200];
+        reader(b"seek").squeeze(&mut linear);
+
+        let mut seeking = reader(b"seek");
+        for position in [150u64, 64, 0, 63, 199] {
+            seeking.seek(position);
+            let mut byte = [0u8; 1];
+            seeking.fill(&mut byte);
+            assert_eq!(byte[0], linear[position as usize]);
+            assert_eq!(seeking.position(), position + 1);
+        }
+        seeking.seek(37);
+        let mut tail = [0u8; 163];
+        seeking.fill(&mut tail);
+        assert_eq!(tail, linear[37..]);
+    }
+
+    #[test]
+    fn test_seek_far_yields_fresh_output() {
+        let mut start = [0u8; 512];
+        reader(b"seek").squeeze(&mut start);
+
+        let mut seeking = reader(b"seek");
+        let mut far = [0u8; 512];
+        seeking.seek(1 << 40);
+        seeking.fill(&mut far);
+        let mut words: BTreeSet<&[u8]> = start.chunks_exact(16).collect();
+        words.extend(far.chunks_exact(16));
+        assert_eq!(words.len(), (start.len() + far.len()) / 16);
+
+        let ones: u32 = far.iter().map(|b| b.count_ones()).sum();
+        assert!((1_850..2_250).contains(&ones), "{} one bits", ones);
+    }
+
+    #[test]
+    fn test_output_blocks_do_not_repeat() {
+        for digest_size in [DigestSize::Bit128, DigestSize::Bit256, DigestSize::Bit512] {
+            let mut hasher = BlueHashCore::new(digest_size);
+            hasher.update(b"xof quality");
+            let mut out = [0u8; 4096];
+            hasher.finalize_xof().squeeze(&mut out);
+
+            let rate = digest_size.state_size() / 2 * 8;
+            let blocks: Vec<&[u8]> = out.chunks_exact(rate).collect();
+            for (i, a) in blocks.iter().enumerate() {
+                for b in &blocks[i + 1..] {
+                    assert_ne!(a[8..], b[8..], "{:?}", digest_size);
+                }
+            }
+            let words: BTreeSet<&[u8]> = out.chunks_exact(16).collect();
+            assert_eq!(words.len(), out.len() / 16, "{:?}", digest_size);
+        }
+    }
+
+    #[test]
+    fn test_round_constants_ignore_the_seed() {
+        for round in [116, 117, 300, 343] {
+            let constant = generate_constants_from_seed(round, 0);
+            for seed in [1, 0x5eed, u64::MAX] {
+                assert_eq!(generate_constants_from_seed(round, seed), constant);
+            }
+        }
+    }
+
+    #[test]
+    fn test_xof_separated_from_finalize() {
+        let mut hasher = BlueHashCore::new(DigestSize::Bit128);
+        hasher.update(b"xof");
+        let digest = hasher.finalize();
+        let prefix: [u8; 16] = reader(b"xof").squeeze_array();
+        assert_ne!(digest, prefix);
+    }
+}
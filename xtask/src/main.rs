@@ -0,0 +1,95 @@
+//! Development tasks for the BlueHash workspace, run as `cargo xtask <task>`.
+//!
+//! `portability` runs the `BlueHash_portability` known-answer tests on the
+//! targets the host CI does not cover:
+//!
+//! ```text
+//! cargo xtask portability             # all of the below
+//! cargo xtask portability wasm        # wasm32-wasip1 under wasmtime
+//! cargo xtask portability big-endian  # powerpc64 under cross (qemu)
+//! cargo xtask portability miri        # s390x (big-endian) under Miri
+//! ```
+//!
+//! The runners are external tools: `rustup target add wasm32-wasip1` plus
+//! `wasmtime`, `cargo install cross` plus Docker or Podman, and a nightly
+//! toolchain with the `miri` component.
+
+use std::env;
+use std::process::{exit, Command};
+
+const KAT_PACKAGE: &str = "BlueHash_portability";
+
+/// One way of running the known-answer tests on a foreign target.
+struct Run {
+    name: &'static str,
+    program: &'static str,
+    args: &'static [&'static str],
+    target: &'static str,
+    env: &'static [(&'static str, &'static str)],
+}
+
+const RUNS: &[Run] = &[
+    Run {
+        name: "wasm",
+        program: "cargo",
+        args: &["test"],
+        target: "wasm32-wasip1",
+        env: &[("CARGO_TARGET_WASM32_WASIP1_RUNNER", "wasmtime")],
+    },
+    Run {
+        name: "big-endian",
+        program: "cross",
+        args: &["test"],
+        target: "powerpc64-unknown-linux-gnu",
+        env: &[],
+    },
+    Run {
+        name: "miri",
+        program: "cargo",
+        args: &["+nightly", "miri", "test"],
+        target: "s390x-unknown-linux-gnu",
+        env: &[],
+    },
+];
+
+fn run(run: &Run) -> bool {
+    println!("==> {}: {} on {}", run.name, KAT_PACKAGE, run.target);
+    let status = Command::new(run.program)
+        .args(run.args)
+        .args(["--package", KAT_PACKAGE, "--target", run.target])
+        .envs(run.env.iter().copied())
+        .status();
+    match status {
+        Ok(status) if status.success() => true,
+        Ok(status) => {
+            eprintln!("{} failed: {}", run.name, status);
+            false
+        }
+        Err(err) => {
+            eprintln!("{} could not start `{}`: {}", run.name, run.program, err);
+            false
+        }
+    }
+}
+
+fn usage() -> ! {
+    let names: Vec<&str> = RUNS.iter().map(|run| run.name).collect();
+    eprintln!("usage: cargo xtask portability [{}]", names.join("|"));
+    exit(2);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let selected: Vec<&Run> = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["portability"] => RUNS.iter().collect(),
+        ["portability", name] => match RUNS.iter().find(|run| run.name == name) {
+            Some(run) => vec![run],
+            None => usage(),
+        },
+        _ => usage(),
+    };
+    let failed = selected.into_iter().filter(|r| !run(r)).count();
+    if failed > 0 {
+        exit(1);
+    }
+}